@@ -0,0 +1,44 @@
+//! Boards and timing shared by the benches.
+//!
+//! The benches are plain timing loops so they run on stable without a
+//! benchmarking framework.
+
+// Each bench uses only part of this module.
+#![allow(dead_code)]
+
+use std::time::{Duration, Instant};
+
+pub const PUZZLE: [[u8; 9]; 9] = [
+    [0, 0, 0, 8, 6, 0, 2, 3, 0],
+    [7, 0, 5, 0, 0, 0, 9, 0, 8],
+    [0, 6, 0, 3, 0, 7, 0, 4, 0],
+    [0, 2, 0, 7, 0, 8, 0, 5, 0],
+    [0, 7, 8, 5, 0, 0, 0, 0, 0],
+    [4, 0, 0, 9, 0, 6, 0, 7, 0],
+    [3, 0, 9, 0, 5, 0, 7, 0, 2],
+    [0, 4, 0, 1, 0, 9, 0, 8, 0],
+    [5, 0, 7, 0, 8, 0, 0, 9, 4],
+];
+
+pub const SOLUTION: [[u8; 9]; 9] = [
+    [1, 9, 4, 8, 6, 5, 2, 3, 7],
+    [7, 3, 5, 4, 1, 2, 9, 6, 8],
+    [8, 6, 2, 3, 9, 7, 1, 4, 5],
+    [9, 2, 1, 7, 4, 8, 3, 5, 6],
+    [6, 7, 8, 5, 3, 1, 4, 2, 9],
+    [4, 5, 3, 9, 2, 6, 8, 7, 1],
+    [3, 8, 9, 6, 5, 4, 7, 1, 2],
+    [2, 4, 6, 1, 7, 9, 5, 8, 3],
+    [5, 1, 7, 2, 8, 3, 6, 9, 4],
+];
+
+/// Average time of `f` over enough runs to take about a second.
+pub fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || start.elapsed() < Duration::from_secs(1) {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
@@ -0,0 +1,69 @@
+//! Constraint count and proving time of one `MultiSudokuCircuit<9, M>`
+//! proof against `M` separate `SudokuCircuit<9>` proofs.
+//!
+//! Run with `cargo bench --bench multi`.
+
+mod common;
+
+use std::time::Instant;
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::Groth16;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_snark::SNARK;
+use rand::thread_rng;
+use sudoku_snark::circuit::SudokuCircuit;
+use sudoku_snark::multi::MultiSudokuCircuit;
+
+use common::{PUZZLE, SOLUTION};
+
+fn num_constraints(circuit: impl ConstraintSynthesizer<Fr>) -> usize {
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.num_constraints()
+}
+
+fn compare<const M: usize>() {
+    let rng = &mut thread_rng();
+    let single = || SudokuCircuit {
+        puzzle: Some(PUZZLE),
+        solution: Some(SOLUTION),
+    };
+    let multi = || MultiSudokuCircuit {
+        puzzles: Some([PUZZLE; M]),
+        solutions: Some([SOLUTION; M]),
+    };
+
+    let (pk, _) =
+        Groth16::<Bls12_381>::circuit_specific_setup(SudokuCircuit::<9>::default(), rng).unwrap();
+    let start = Instant::now();
+    for _ in 0..M {
+        Groth16::<Bls12_381>::prove(&pk, single(), rng).unwrap();
+    }
+    let separate = start.elapsed();
+
+    let (pk, _) =
+        Groth16::<Bls12_381>::circuit_specific_setup(MultiSudokuCircuit::<9, M>::default(), rng)
+            .unwrap();
+    let start = Instant::now();
+    Groth16::<Bls12_381>::prove(&pk, multi(), rng).unwrap();
+    let packed = start.elapsed();
+
+    println!(
+        "{M:>2} {:>12} {:>12} {separate:>12.2?} {packed:>12.2?}",
+        M * num_constraints(single()),
+        num_constraints(multi()),
+    );
+}
+
+fn main() {
+    println!(
+        "{:>2} {:>12} {:>12} {:>12} {:>12}",
+        "M", "separate", "packed", "separate", "packed"
+    );
+    println!("{:>2} {:>25} {:>25}", "", "constraints", "proving time");
+    compare::<1>();
+    compare::<2>();
+    compare::<4>();
+    compare::<8>();
+}
@@ -0,0 +1,70 @@
+//! Verification with a prepared verifying key against preparing the key on
+//! every call, for `SudokuCircuit<9>`.
+//!
+//! Run with `cargo bench --bench prepared`.
+
+mod common;
+
+use ark_bls12_381::Bls12_381;
+use ark_groth16::{Groth16, Proof};
+use rand::thread_rng;
+use sudoku_snark::snark::{
+    prepare_tagged, serialize_tagged, verify_many_tagged, verify_tagged, Groth16Bls12_381, Kind,
+    ProvingBackend,
+};
+
+use common::{time, PUZZLE, SOLUTION};
+
+fn main() {
+    let rng = &mut thread_rng();
+    let (pk, vk) = Groth16Bls12_381::setup::<9, _>(rng).unwrap();
+    let pvk = Groth16Bls12_381::prepare(&vk).unwrap();
+    let proof = Groth16Bls12_381::prove(&pk, PUZZLE, SOLUTION, rng).unwrap();
+
+    let prepare = time(|| {
+        Groth16Bls12_381::prepare(&vk).unwrap();
+    });
+    let unprepared = time(|| assert!(Groth16Bls12_381::verify(&vk, &PUZZLE, &proof).unwrap()));
+    let prepared = time(|| {
+        assert!(Groth16Bls12_381::verify_prepared(&pvk, &PUZZLE, &proof).unwrap());
+    });
+    println!("prepare key            {prepare:>12.2?}");
+    println!("verify with key        {unprepared:>12.2?}");
+    println!("verify with prepared   {prepared:>12.2?}");
+    println!(
+        "saved per proof        {:>12.2?} ({:.0}%)",
+        unprepared - prepared,
+        100.0 * (1.0 - prepared.as_secs_f64() / unprepared.as_secs_f64())
+    );
+
+    // The same through the serialized formats a verification service stores.
+    let tagged_vk = serialize_tagged::<Groth16Bls12_381, _>(Kind::VerifyingKey, &vk).unwrap();
+    let tagged_pvk = prepare_tagged(&tagged_vk).unwrap();
+    println!();
+    println!("{:>6} {:>14} {:>14}", "proofs", "with key", "with prepared");
+    for n in [1, 16, 256] {
+        // Rerandomized copies are distinct, valid proofs of the same puzzle.
+        let proofs: Vec<_> = (0..n)
+            .map(|_| {
+                let proof: Proof<Bls12_381> =
+                    Groth16::<Bls12_381>::rerandomize_proof(&vk, &proof, rng);
+                (
+                    PUZZLE,
+                    serialize_tagged::<Groth16Bls12_381, _>(Kind::Proof, &proof).unwrap(),
+                )
+            })
+            .collect();
+        let with_key = time(|| {
+            for (puzzle, proof) in &proofs {
+                assert!(verify_tagged(&tagged_vk, puzzle, proof).unwrap());
+            }
+        });
+        let with_prepared = time(|| {
+            assert!(verify_many_tagged(&tagged_pvk, &proofs)
+                .unwrap()
+                .into_iter()
+                .all(|ok| ok));
+        });
+        println!("{n:>6} {with_key:>14.2?} {with_prepared:>14.2?}");
+    }
+}
@@ -0,0 +1,44 @@
+//! Verification throughput: one proof at a time against [`verify_batch`].
+//!
+//! Run with `cargo bench --bench verify`.
+
+mod common;
+
+use ark_bls12_381::Bls12_381;
+use ark_groth16::{Groth16, Proof};
+use rand::thread_rng;
+use sudoku_snark::batch::verify_batch;
+use sudoku_snark::snark::{Groth16Bls12_381, ProvingBackend};
+
+use common::{time, PUZZLE, SOLUTION};
+
+fn main() {
+    let rng = &mut thread_rng();
+    let (pk, vk) = Groth16Bls12_381::setup::<9, _>(rng).unwrap();
+    let proof = Groth16Bls12_381::prove(&pk, PUZZLE, SOLUTION, rng).unwrap();
+
+    println!(
+        "{:>6} {:>14} {:>14} {:>8}",
+        "proofs", "one-by-one", "batch", "speedup"
+    );
+    for n in [1, 4, 16, 64, 256] {
+        // Rerandomized copies are distinct, valid proofs of the same puzzle.
+        let batch: Vec<(_, Proof<Bls12_381>)> = (0..n)
+            .map(|_| {
+                let proof = Groth16::<Bls12_381>::rerandomize_proof(&vk, &proof, rng);
+                (PUZZLE, proof)
+            })
+            .collect();
+
+        let one_by_one = time(|| {
+            for (puzzle, proof) in &batch {
+                assert!(Groth16Bls12_381::verify(&vk, puzzle, proof).unwrap());
+            }
+        });
+        let batched = time(|| verify_batch(&vk, &batch).unwrap());
+        println!(
+            "{n:>6} {one_by_one:>14.2?} {batched:>14.2?} {:>7.1}x",
+            one_by_one.as_secs_f64() / batched.as_secs_f64()
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use ark_bls12_381::Bls12_381;
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_json_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+};
+use sudoku_snark::snark::{Groth16Bls12_381, ProvingBackend};
+
+use crate::error::ContractError;
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, N};
+
+/// Storage key of the verifying key. It is validated once at instantiation and
+/// stored uncompressed, so that `VerifySudoku` skips point decompression and
+/// subgroup checks.
+const VERIFYING_KEY: &[u8] = b"verifying_key";
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let vk = VerifyingKey::<Bls12_381>::deserialize_compressed(msg.verifying_key.as_slice())
+        .map_err(|err| ContractError::InvalidVerifyingKey(err.to_string()))?;
+    let mut serialized = vec![0; vk.serialized_size(ark_serialize::Compress::No)];
+    vk.serialize_uncompressed(&mut serialized[..])
+        .map_err(|err| ContractError::InvalidVerifyingKey(err.to_string()))?;
+    deps.storage.set(VERIFYING_KEY, &serialized);
+    Ok(Response::new().add_attribute("action", "instantiate"))
+}
+
+fn load_verifying_key(deps: Deps) -> Result<VerifyingKey<Bls12_381>, ContractError> {
+    let serialized = deps
+        .storage
+        .get(VERIFYING_KEY)
+        .ok_or_else(|| StdError::msg("verifying key not found"))?;
+    VerifyingKey::deserialize_uncompressed_unchecked(&serialized[..])
+        .map_err(|err| ContractError::InvalidVerifyingKey(err.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::VerifySudoku { puzzle, proof } => verify_sudoku(deps.as_ref(), puzzle, proof),
+    }
+}
+
+fn to_grid(puzzle: Vec<Vec<u8>>) -> Result<[[u8; N]; N], ContractError> {
+    let rows: Vec<[u8; N]> = puzzle
+        .into_iter()
+        .map(|row| {
+            let len = row.len();
+            row.try_into().map_err(|_| {
+                ContractError::InvalidPuzzle(format!("row has {len} cells, expected {N}"))
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    let len = rows.len();
+    rows.try_into()
+        .map_err(|_| ContractError::InvalidPuzzle(format!("{len} rows, expected {N}")))
+}
+
+fn verify_sudoku(
+    deps: Deps,
+    puzzle: Vec<Vec<u8>>,
+    proof: Binary,
+) -> Result<Response, ContractError> {
+    let puzzle = to_grid(puzzle)?;
+    let vk = load_verifying_key(deps)?;
+    let proof = Proof::<Bls12_381>::deserialize_compressed(proof.as_slice())
+        .map_err(|err| ContractError::InvalidProof(err.to_string()))?;
+    match Groth16Bls12_381::verify(&vk, &puzzle, &proof) {
+        Ok(true) => Ok(Response::new()
+            .add_attribute("action", "verify_sudoku")
+            .add_attribute("verified", "true")),
+        Ok(false) => Err(ContractError::ProofRejected),
+        Err(err) => Err(ContractError::VerificationFailed(err.to_string())),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::VerifyingKey {} => {
+            let serialized = deps.storage.get(VERIFYING_KEY).unwrap_or_default();
+            to_json_binary(&Binary::from(serialized))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::Addr;
+    use cw_multi_test::{App, ContractWrapper, Executor};
+    use rand::prelude::*;
+
+    const PUZZLE: [[u8; 9]; 9] = [
+        [0, 0, 0, 8, 6, 0, 2, 3, 0],
+        [7, 0, 5, 0, 0, 0, 9, 0, 8],
+        [0, 6, 0, 3, 0, 7, 0, 4, 0],
+        [0, 2, 0, 7, 0, 8, 0, 5, 0],
+        [0, 7, 8, 5, 0, 0, 0, 0, 0],
+        [4, 0, 0, 9, 0, 6, 0, 7, 0],
+        [3, 0, 9, 0, 5, 0, 7, 0, 2],
+        [0, 4, 0, 1, 0, 9, 0, 8, 0],
+        [5, 0, 7, 0, 8, 0, 0, 9, 4],
+    ];
+
+    const SOLUTION: [[u8; 9]; 9] = [
+        [1, 9, 4, 8, 6, 5, 2, 3, 7],
+        [7, 3, 5, 4, 1, 2, 9, 6, 8],
+        [8, 6, 2, 3, 9, 7, 1, 4, 5],
+        [9, 2, 1, 7, 4, 8, 3, 5, 6],
+        [6, 7, 8, 5, 3, 1, 4, 2, 9],
+        [4, 5, 3, 9, 2, 6, 8, 7, 1],
+        [3, 8, 9, 6, 5, 4, 7, 1, 2],
+        [2, 4, 6, 1, 7, 9, 5, 8, 3],
+        [5, 1, 7, 2, 8, 3, 6, 9, 4],
+    ];
+
+    fn compressed<T: CanonicalSerialize>(value: &T) -> Binary {
+        let mut serialized = vec![0; value.serialized_size(ark_serialize::Compress::Yes)];
+        value.serialize_compressed(&mut serialized[..]).unwrap();
+        serialized.into()
+    }
+
+    fn rows(puzzle: [[u8; 9]; 9]) -> Vec<Vec<u8>> {
+        puzzle.iter().map(|row| row.to_vec()).collect()
+    }
+
+    fn instantiate_verifier(app: &mut App, verifying_key: Binary) -> StdResult<Addr> {
+        let code_id = app.store_code(Box::new(ContractWrapper::new(execute, instantiate, query)));
+        let owner = app.api().addr_make("owner");
+        app.instantiate_contract(
+            code_id,
+            owner,
+            &InstantiateMsg { verifying_key },
+            &[],
+            "sudoku-verifier",
+            None,
+        )
+    }
+
+    #[test]
+    fn verify_sudoku() {
+        let rng = &mut thread_rng();
+        let (pk, vk) = Groth16Bls12_381::setup::<9, _>(rng)
+            .map_err(|err| format!("Failed to generate setup parameters: {err}").to_string())
+            .unwrap();
+        let proof = Groth16Bls12_381::prove(&pk, PUZZLE, SOLUTION, rng)
+            .map_err(|_| "Failed to generate proof".to_string())
+            .unwrap();
+
+        let mut app = App::default();
+        let verifier = instantiate_verifier(&mut app, compressed(&vk)).unwrap();
+        let solver = app.api().addr_make("solver");
+
+        let response = app
+            .execute_contract(
+                solver.clone(),
+                verifier.clone(),
+                &ExecuteMsg::VerifySudoku {
+                    puzzle: rows(PUZZLE),
+                    proof: compressed(&proof),
+                },
+                &[],
+            )
+            .unwrap();
+        assert!(
+            response.has_event(&cosmwasm_std::Event::new("wasm").add_attribute("verified", "true"))
+        );
+
+        let mut wrong_puzzle = PUZZLE;
+        wrong_puzzle[0][0] = 1;
+        let err = app
+            .execute_contract(
+                solver.clone(),
+                verifier.clone(),
+                &ExecuteMsg::VerifySudoku {
+                    puzzle: rows(wrong_puzzle),
+                    proof: compressed(&proof),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("Proof rejected"), "{err}");
+
+        let err = app
+            .execute_contract(
+                solver.clone(),
+                verifier.clone(),
+                &ExecuteMsg::VerifySudoku {
+                    puzzle: rows(PUZZLE),
+                    proof: Binary::from(vec![0; 3]),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid proof"), "{err}");
+
+        let err = app
+            .execute_contract(
+                solver,
+                verifier.clone(),
+                &ExecuteMsg::VerifySudoku {
+                    puzzle: rows(PUZZLE)[1..].to_vec(),
+                    proof: compressed(&proof),
+                },
+                &[],
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid puzzle"), "{err}");
+
+        let stored: Binary = app
+            .wrap()
+            .query_wasm_smart(verifier, &QueryMsg::VerifyingKey {})
+            .unwrap();
+        assert_eq!(
+            VerifyingKey::<Bls12_381>::deserialize_uncompressed(stored.as_slice()).unwrap(),
+            vk
+        );
+    }
+
+    #[test]
+    fn invalid_verifying_key_rejected() {
+        let mut app = App::default();
+        let err = instantiate_verifier(&mut app, Binary::from(vec![1; 64])).unwrap_err();
+        assert!(err.to_string().contains("Invalid verifying key"), "{err}");
+    }
+}
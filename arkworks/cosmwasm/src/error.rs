@@ -0,0 +1,32 @@
+use cosmwasm_std::StdError;
+
+#[derive(Debug)]
+pub enum ContractError {
+    Std(StdError),
+    InvalidVerifyingKey(String),
+    InvalidPuzzle(String),
+    InvalidProof(String),
+    VerificationFailed(String),
+    ProofRejected,
+}
+
+impl std::fmt::Display for ContractError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContractError::Std(err) => write!(f, "{err}"),
+            ContractError::InvalidVerifyingKey(s) => write!(f, "Invalid verifying key: {s}"),
+            ContractError::InvalidPuzzle(s) => write!(f, "Invalid puzzle: {s}"),
+            ContractError::InvalidProof(s) => write!(f, "Invalid proof: {s}"),
+            ContractError::VerificationFailed(s) => write!(f, "Failed to verify proof: {s}"),
+            ContractError::ProofRejected => write!(f, "Proof rejected"),
+        }
+    }
+}
+
+impl std::error::Error for ContractError {}
+
+impl From<StdError> for ContractError {
+    fn from(err: StdError) -> Self {
+        ContractError::Std(err)
+    }
+}
@@ -0,0 +1,28 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::Binary;
+
+/// The board size the contract verifies proofs for.
+pub const N: usize = 9;
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// A `VerifyingKey<Bls12_381>` for `SudokuCircuit<9>`, in arkworks'
+    /// compressed canonical serialization.
+    pub verifying_key: Binary,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Verifies a compressed `Proof<Bls12_381>` against the public `puzzle`:
+    /// `N` rows of `N` cells, with 0 for blanks. Fails unless the proof is
+    /// accepted.
+    VerifySudoku { puzzle: Vec<Vec<u8>>, proof: Binary },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// The stored verifying key, uncompressed.
+    #[returns(Binary)]
+    VerifyingKey {},
+}
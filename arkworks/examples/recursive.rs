@@ -0,0 +1,57 @@
+//! Proves a Sudoku proof was verified, inside a second proof, and reports
+//! its size.
+//!
+//! The inner Groth16 proof is over BLS12-377 and the outer one over BW6-761,
+//! whose scalar field is BLS12-377's base field (see
+//! `sudoku_snark::recursion`). Run with `cargo run --release --example
+//! recursive`.
+
+use ark_bls12_377::{constraints::PairingVar, Bls12_377};
+use ark_bw6_761::BW6_761;
+use ark_groth16::Groth16;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_snark::SNARK;
+use rand::thread_rng;
+use sudoku_snark::circuit::SudokuCircuit;
+use sudoku_snark::recursion::RecursiveCircuit;
+
+type Recursive = RecursiveCircuit<4, Bls12_377, PairingVar>;
+
+const PUZZLE: [[u8; 4]; 4] = [[1, 0, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]];
+const SOLUTION: [[u8; 4]; 4] = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+
+fn main() {
+    let rng = &mut thread_rng();
+    let (pk, vk) =
+        Groth16::<Bls12_377>::circuit_specific_setup(SudokuCircuit::<4>::default(), rng).unwrap();
+    let circuit = SudokuCircuit {
+        puzzle: Some(PUZZLE),
+        solution: Some(SOLUTION),
+    };
+    let proof = Groth16::<Bls12_377>::prove(&pk, circuit, rng).unwrap();
+    let inputs = SudokuCircuit::public_inputs(&PUZZLE);
+    println!(
+        "Inner proof verifies: {}",
+        Groth16::<Bls12_377>::verify(&vk, &inputs, &proof).unwrap()
+    );
+
+    let cs = ConstraintSystem::new_ref();
+    Recursive::new(vk.clone(), Some(PUZZLE), Some(proof.clone()))
+        .generate_constraints(cs.clone())
+        .unwrap();
+    println!("Outer circuit: {} constraints", cs.num_constraints());
+
+    let (outer_pk, outer_vk) =
+        Groth16::<BW6_761>::circuit_specific_setup(Recursive::new(vk.clone(), None, None), rng)
+            .unwrap();
+    let outer_proof = Groth16::<BW6_761>::prove(
+        &outer_pk,
+        Recursive::new(vk, Some(PUZZLE), Some(proof)),
+        rng,
+    )
+    .unwrap();
+    println!(
+        "Outer proof verifies without the puzzle: {}",
+        Groth16::<BW6_761>::verify(&outer_vk, &[], &outer_proof).unwrap()
+    );
+}
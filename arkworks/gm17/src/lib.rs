@@ -0,0 +1,98 @@
+//! An implementation of the [`Groth-Maller`] simulation extractable zkSNARK.
+//!
+//! This is `ark-gm17` 0.3 ported to arkworks 0.5, for which there is no
+//! release. Besides the API changes, [`GM17`] implements the `ark-snark`
+//! [`SNARK`] trait.
+//!
+//! [`Groth-Maller`]: https://eprint.iacr.org/2017/540
+#![deny(
+    unused,
+    future_incompatible,
+    nonstandard_style,
+    rust_2018_idioms,
+    missing_docs
+)]
+// Kept as upstream wrote them, to ease comparing the port against it
+#![allow(
+    clippy::many_single_char_names,
+    clippy::op_ref,
+    clippy::type_complexity
+)]
+#![forbid(unsafe_code)]
+
+#[macro_use]
+extern crate ark_std;
+
+/// Reduce an R1CS instance to a *Square Arithmetic Program* instance.
+pub mod r1cs_to_sap;
+
+/// Data structures used by the prover, verifier, and generator.
+pub mod data_structures;
+
+/// Generate public parameters for the GM17 zkSNARK construction.
+pub mod generator;
+
+/// Create proofs for the GM17 zkSNARK construction.
+pub mod prover;
+
+/// Verify proofs for the GM17 zkSNARK construction.
+pub mod verifier;
+
+#[cfg(test)]
+mod test;
+
+pub use self::data_structures::*;
+pub use self::{generator::*, prover::*, verifier::*};
+
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::marker::PhantomData;
+use ark_std::rand::{CryptoRng, RngCore};
+
+/// The SNARK of [[GrothMaller17]](https://eprint.iacr.org/2017/540).
+pub struct GM17<E: Pairing> {
+    e_phantom: PhantomData<E>,
+}
+
+impl<E: Pairing> SNARK<E::ScalarField> for GM17<E> {
+    type ProvingKey = ProvingKey<E>;
+    type VerifyingKey = VerifyingKey<E>;
+    type Proof = Proof<E>;
+    type ProcessedVerifyingKey = PreparedVerifyingKey<E>;
+    type Error = SynthesisError;
+
+    fn circuit_specific_setup<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Self::Error> {
+        let pk = generate_random_parameters::<E, C, R>(circuit, rng)?;
+        let vk = pk.vk.clone();
+
+        Ok((pk, vk))
+    }
+
+    fn prove<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        pk: &Self::ProvingKey,
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Self::Proof, Self::Error> {
+        create_random_proof::<E, _, _>(circuit, pk, rng)
+    }
+
+    fn process_vk(
+        circuit_vk: &Self::VerifyingKey,
+    ) -> Result<Self::ProcessedVerifyingKey, Self::Error> {
+        Ok(prepare_verifying_key(circuit_vk))
+    }
+
+    fn verify_with_processed_vk(
+        circuit_pvk: &Self::ProcessedVerifyingKey,
+        x: &[E::ScalarField],
+        proof: &Self::Proof,
+    ) -> Result<bool, Self::Error> {
+        verify_proof(circuit_pvk, proof, x)
+    }
+}
+
+impl<E: Pairing> CircuitSpecificSetupSNARK<E::ScalarField> for GM17<E> {}
@@ -0,0 +1,71 @@
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::One;
+use ark_relations::r1cs::{Result as R1CSResult, SynthesisError};
+
+use super::{PreparedVerifyingKey, Proof, VerifyingKey};
+
+/// Prepare the verifying key `vk` for use in proof verification.
+pub fn prepare_verifying_key<E: Pairing>(vk: &VerifyingKey<E>) -> PreparedVerifyingKey<E> {
+    PreparedVerifyingKey {
+        vk: vk.clone(),
+        g_alpha: vk.g_alpha_g1,
+        h_beta: vk.h_beta_g2,
+        g_alpha_h_beta_ml: E::miller_loop(vk.g_alpha_g1, vk.h_beta_g2).0,
+        g_gamma_pc: vk.g_gamma_g1.into(),
+        h_gamma_pc: vk.h_gamma_g2.into(),
+        h_pc: vk.h_g2.into(),
+        query: vk.query.clone(),
+    }
+}
+
+/// Verify a GrothMaller17 proof `proof` against the prepared verification key `pvk`,
+/// with respect to the instance `public_inputs`.
+pub fn verify_proof<E: Pairing>(
+    pvk: &PreparedVerifyingKey<E>,
+    proof: &Proof<E>,
+    public_inputs: &[E::ScalarField],
+) -> R1CSResult<bool> {
+    if (public_inputs.len() + 1) != pvk.query.len() {
+        return Err(SynthesisError::MalformedVerifyingKey);
+    }
+
+    // e(A*G^{alpha}, B*H^{beta}) = e(G^{alpha}, H^{beta}) * e(G^{psi}, H^{gamma}) *
+    // e(C, H) where psi = \sum_{i=0}^l input_i pvk.query[i]
+
+    let mut g_psi = pvk.query[0].into_group();
+    for (i, b) in public_inputs.iter().zip(pvk.query.iter().skip(1)) {
+        g_psi += *b * i;
+    }
+
+    let test1_a_g_alpha = proof.a + pvk.g_alpha;
+    let test1_b_h_beta = proof.b + pvk.h_beta;
+
+    let test1_r1 = pvk.g_alpha_h_beta_ml;
+
+    let test1_r2 = E::multi_miller_loop(
+        [
+            E::G1Prepared::from(-test1_a_g_alpha),
+            g_psi.into_affine().into(),
+            proof.c.into(),
+        ],
+        [
+            E::G2Prepared::from(test1_b_h_beta),
+            pvk.h_gamma_pc.clone(),
+            pvk.h_pc.clone(),
+        ],
+    );
+    let test1 = E::final_exponentiation(ark_ec::pairing::MillerLoopOutput(test1_r2.0 * &test1_r1))
+        .ok_or(SynthesisError::UnexpectedIdentity)?;
+
+    // e(A, H^{gamma}) = e(G^{gamma}, B)
+    let test2_exp = E::multi_miller_loop(
+        [E::G1Prepared::from(proof.a), pvk.g_gamma_pc.clone()],
+        [
+            pvk.h_gamma_pc.clone(),
+            E::G2Prepared::from(-proof.b.into_group()),
+        ],
+    );
+    let test2 = E::final_exponentiation(test2_exp).ok_or(SynthesisError::UnexpectedIdentity)?;
+
+    Ok(test1.0.is_one() && test2.0.is_one())
+}
@@ -0,0 +1,49 @@
+use crate::ahp::Error as AHPError;
+use ark_std::fmt;
+
+/// A `enum` specifying the possible failure modes of the `SNARK`.
+#[derive(Debug)]
+pub enum Error<E> {
+    /// The index is too large for the universal public parameters.
+    IndexTooLarge,
+    /// There was an error in the underlying holographic IOP.
+    AHPError(AHPError),
+    /// There was an error in the underlying polynomial commitment.
+    PolynomialCommitmentError(E),
+}
+
+impl<E> From<AHPError> for Error<E> {
+    fn from(err: AHPError) -> Self {
+        Error::AHPError(err)
+    }
+}
+
+impl<E> Error<E> {
+    /// Convert an error in the underlying polynomial commitment scheme
+    /// to a `Error`.
+    pub fn from_pc_err(err: E) -> Self {
+        Error::PolynomialCommitmentError(err)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IndexTooLarge => write!(f, "the index is too large for the universal SRS"),
+            Error::AHPError(err) => write!(f, "AHP error: {err}"),
+            Error::PolynomialCommitmentError(err) => {
+                write!(f, "polynomial commitment error: {err}")
+            }
+        }
+    }
+}
+
+impl<E: ark_std::error::Error + 'static> ark_std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn ark_std::error::Error + 'static)> {
+        match self {
+            Error::PolynomialCommitmentError(err) => Some(err),
+            Error::AHPError(err) => Some(err),
+            Error::IndexTooLarge => None,
+        }
+    }
+}
@@ -0,0 +1,561 @@
+//! A crate for the Marlin preprocessing zkSNARK for R1CS.
+//!
+//! This is `ark-marlin` 0.3 ported to arkworks 0.5, for which there is no
+//! release. Besides the API changes, [`Marlin`] implements the `ark-snark`
+//! [`SNARK`] and [`UniversalSetupSNARK`] traits, and the polynomial
+//! commitment's opening challenges are squeezed from the Fiat-Shamir
+//! transcript.
+//!
+//! # Note
+//!
+//! Currently, Marlin only supports R1CS instances where the number of inputs
+//! is the same as the number of constraints (i.e., where the constraint
+//! matrices are square). Furthermore, Marlin only supports instances where the
+//! public inputs are of size one less than a power of 2 (i.e., 2^n - 1).
+#![forbid(unsafe_code)]
+// Kept as upstream wrote them, to ease comparing the port against it
+#![allow(clippy::type_complexity, clippy::op_ref)]
+
+#[macro_use]
+extern crate ark_std;
+
+use ark_ff::PrimeField;
+use ark_poly::{univariate::DensePolynomial, EvaluationDomain, GeneralEvaluationDomain};
+use ark_poly_commit::Evaluations;
+use ark_poly_commit::{
+    LabeledCommitment, PCCommitmentState, PCUniversalParams, PolynomialCommitment,
+};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_snark::{UniversalSetupIndexError, UniversalSetupSNARK, SNARK};
+use ark_std::rand::{CryptoRng, RngCore};
+use ark_std::{marker::PhantomData, string::ToString};
+use digest::Digest;
+
+/// Implements a Fiat-Shamir based Rng that allows one to incrementally update
+/// the seed based on new messages in the proof transcript.
+pub mod rng;
+use rng::FiatShamirRng;
+
+mod error;
+pub use error::*;
+
+mod data_structures;
+pub use data_structures::*;
+
+/// Implements an Algebraic Holographic Proof (AHP) for the R1CS indexed relation.
+pub mod ahp;
+pub use ahp::AHPForR1CS;
+use ahp::EvaluationsProvider;
+
+/// The compiled argument system.
+pub struct Marlin<F: PrimeField, PC: PolynomialCommitment<F, DensePolynomial<F>>, D: Digest>(
+    #[doc(hidden)] PhantomData<F>,
+    #[doc(hidden)] PhantomData<PC>,
+    #[doc(hidden)] PhantomData<D>,
+);
+
+/// The size of the largest circuit a universal SRS can index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComputationBound {
+    pub num_constraints: usize,
+    pub num_variables: usize,
+    pub num_non_zero: usize,
+}
+
+impl<F: PrimeField, PC: PolynomialCommitment<F, DensePolynomial<F>>, D: Digest> Marlin<F, PC, D> {
+    /// The personalization string for this protocol. Used to personalize the
+    /// Fiat-Shamir rng.
+    pub const PROTOCOL_NAME: &'static [u8] = b"MARLIN-2019";
+
+    /// Generate the universal prover and verifier keys for the
+    /// argument system.
+    pub fn universal_setup<R: RngCore>(
+        num_constraints: usize,
+        num_variables: usize,
+        num_non_zero: usize,
+        rng: &mut R,
+    ) -> Result<UniversalSRS<F, PC>, Error<PC::Error>> {
+        let max_degree = AHPForR1CS::<F>::max_degree(num_constraints, num_variables, num_non_zero)?;
+        let setup_time = start_timer!(|| {
+            format!(
+            "Marlin::UniversalSetup with max_degree {}, computed for a maximum of {} constraints, {} vars, {} non_zero",
+            max_degree, num_constraints, num_variables, num_non_zero,
+        )
+        });
+
+        let srs = PC::setup(max_degree, None, rng).map_err(Error::from_pc_err);
+        end_timer!(setup_time);
+        srs
+    }
+
+    /// Generate the index-specific (i.e., circuit-specific) prover and verifier
+    /// keys. This is a deterministic algorithm that anyone can rerun.
+    pub fn index<C: ConstraintSynthesizer<F>>(
+        srs: &UniversalSRS<F, PC>,
+        c: C,
+    ) -> Result<(IndexProverKey<F, PC>, IndexVerifierKey<F, PC>), Error<PC::Error>> {
+        // TODO: Add check that c is in the correct mode.
+        Self::commit_to_index(srs, AHPForR1CS::index(c)?)
+    }
+
+    /// Trim `srs` to `index` and commit to the index polynomials.
+    fn commit_to_index(
+        srs: &UniversalSRS<F, PC>,
+        index: ahp::indexer::Index<F>,
+    ) -> Result<(IndexProverKey<F, PC>, IndexVerifierKey<F, PC>), Error<PC::Error>> {
+        let index_time = start_timer!(|| "Marlin::Index");
+
+        if srs.max_degree() < index.max_degree() {
+            return Err(Error::IndexTooLarge);
+        }
+
+        let coeff_support = AHPForR1CS::get_degree_bounds(&index.index_info);
+        // Marlin only needs degree 2 random polynomials
+        let supported_hiding_bound = 1;
+        let (committer_key, verifier_key) = PC::trim(
+            srs,
+            index.max_degree(),
+            supported_hiding_bound,
+            Some(&coeff_support),
+        )
+        .map_err(Error::from_pc_err)?;
+
+        let commit_time = start_timer!(|| "Commit to index polynomials");
+        let (index_comms, _) =
+            PC::commit(&committer_key, index.iter(), None).map_err(Error::from_pc_err)?;
+        end_timer!(commit_time);
+
+        let index_comms = index_comms
+            .into_iter()
+            .map(|c| c.commitment().clone())
+            .collect();
+        let index_vk = IndexVerifierKey {
+            index_info: index.index_info,
+            index_comms,
+            verifier_key,
+        };
+
+        let index_pk = IndexProverKey {
+            index,
+            index_vk: index_vk.clone(),
+            committer_key,
+        };
+
+        end_timer!(index_time);
+
+        Ok((index_pk, index_vk))
+    }
+
+    /// The transcript both parties start from.
+    fn transcript(index_vk: &IndexVerifierKey<F, PC>, public_input: &[F]) -> FiatShamirRng<D> {
+        let mut fs_rng = FiatShamirRng::<D>::from_seed(&Self::PROTOCOL_NAME);
+        fs_rng.absorb(index_vk);
+        fs_rng.absorb(&public_input);
+        fs_rng
+    }
+
+    /// Create a zkSNARK asserting that the constraint system is satisfied.
+    pub fn prove<C: ConstraintSynthesizer<F>, R: RngCore>(
+        index_pk: &IndexProverKey<F, PC>,
+        c: C,
+        zk_rng: &mut R,
+    ) -> Result<Proof<F, PC>, Error<PC::Error>> {
+        let prover_time = start_timer!(|| "Marlin::Prover");
+        // Add check that c is in the correct mode.
+
+        let prover_init_state = AHPForR1CS::prover_init(&index_pk.index, c)?;
+        let public_input = prover_init_state.public_input();
+        let mut fs_rng = Self::transcript(&index_pk.index_vk, &public_input);
+
+        // --------------------------------------------------------------------
+        // First round
+
+        let (prover_first_msg, prover_first_oracles, prover_state) =
+            AHPForR1CS::prover_first_round(prover_init_state, zk_rng)?;
+
+        let first_round_comm_time = start_timer!(|| "Committing to first round polys");
+        let (first_comms, first_comm_rands) = PC::commit(
+            &index_pk.committer_key,
+            prover_first_oracles.iter(),
+            Some(zk_rng),
+        )
+        .map_err(Error::from_pc_err)?;
+        end_timer!(first_round_comm_time);
+
+        let first_round_comms = commitments::<PC, F>(&first_comms);
+        fs_rng.absorb(&first_round_comms);
+        fs_rng.absorb(&prover_first_msg);
+
+        let (verifier_first_msg, verifier_state) =
+            AHPForR1CS::verifier_first_round(index_pk.index_vk.index_info, &mut fs_rng)?;
+        // --------------------------------------------------------------------
+
+        // --------------------------------------------------------------------
+        // Second round
+
+        let (prover_second_msg, prover_second_oracles, prover_state) =
+            AHPForR1CS::prover_second_round(&verifier_first_msg, prover_state, zk_rng);
+
+        let second_round_comm_time = start_timer!(|| "Committing to second round polys");
+        let (second_comms, second_comm_rands) = PC::commit(
+            &index_pk.committer_key,
+            prover_second_oracles.iter(),
+            Some(zk_rng),
+        )
+        .map_err(Error::from_pc_err)?;
+        end_timer!(second_round_comm_time);
+
+        let second_round_comms = commitments::<PC, F>(&second_comms);
+        fs_rng.absorb(&second_round_comms);
+        fs_rng.absorb(&prover_second_msg);
+
+        let (verifier_second_msg, verifier_state) =
+            AHPForR1CS::verifier_second_round(verifier_state, &mut fs_rng);
+        // --------------------------------------------------------------------
+
+        // --------------------------------------------------------------------
+        // Third round
+        let (prover_third_msg, prover_third_oracles) =
+            AHPForR1CS::prover_third_round(&verifier_second_msg, prover_state, zk_rng)?;
+
+        let third_round_comm_time = start_timer!(|| "Committing to third round polys");
+        let (third_comms, third_comm_rands) = PC::commit(
+            &index_pk.committer_key,
+            prover_third_oracles.iter(),
+            Some(zk_rng),
+        )
+        .map_err(Error::from_pc_err)?;
+        end_timer!(third_round_comm_time);
+
+        let third_round_comms = commitments::<PC, F>(&third_comms);
+        fs_rng.absorb(&third_round_comms);
+        fs_rng.absorb(&prover_third_msg);
+
+        let verifier_state = AHPForR1CS::verifier_third_round(verifier_state, &mut fs_rng);
+        // --------------------------------------------------------------------
+
+        // Gather prover polynomials in one vector.
+        let polynomials: Vec<_> = index_pk
+            .index
+            .iter()
+            .chain(prover_first_oracles.iter())
+            .chain(prover_second_oracles.iter())
+            .chain(prover_third_oracles.iter())
+            .collect();
+
+        // Gather commitments in one vector.
+        let commitments = vec![first_round_comms, second_round_comms, third_round_comms];
+        let labeled_comms: Vec<_> = index_pk
+            .index_vk
+            .iter()
+            .cloned()
+            .zip(&AHPForR1CS::<F>::INDEXER_POLYNOMIALS)
+            .map(|(c, l)| LabeledCommitment::new(l.to_string(), c, None))
+            .chain(first_comms.iter().cloned())
+            .chain(second_comms.iter().cloned())
+            .chain(third_comms.iter().cloned())
+            .collect();
+
+        // Gather commitment randomness together. The index polynomials are
+        // committed to without hiding.
+        let comm_rands: Vec<PC::CommitmentState> = index_pk
+            .index_vk
+            .iter()
+            .map(|_| PC::CommitmentState::empty())
+            .chain(first_comm_rands)
+            .chain(second_comm_rands)
+            .chain(third_comm_rands)
+            .collect();
+
+        // Compute the AHP verifier's query set.
+        let (query_set, verifier_state) =
+            AHPForR1CS::verifier_query_set(verifier_state, &mut fs_rng);
+        let lc_s = AHPForR1CS::construct_linear_combinations(
+            &public_input,
+            &polynomials,
+            &verifier_state,
+        )?;
+
+        let eval_time = start_timer!(|| "Evaluating linear combinations over query set");
+        let mut evaluations = Vec::new();
+        for (label, (_, point)) in &query_set {
+            let lc = lc_s
+                .iter()
+                .find(|lc| &lc.label == label)
+                .ok_or(ahp::Error::MissingEval(label.to_string()))?;
+            let eval = polynomials.get_lc_eval(lc, *point)?;
+            if !AHPForR1CS::<F>::LC_WITH_ZERO_EVAL.contains(&lc.label.as_ref()) {
+                evaluations.push((label.to_string(), eval));
+            }
+        }
+
+        evaluations.sort_by(|a, b| a.0.cmp(&b.0));
+        let evaluations = evaluations.into_iter().map(|x| x.1).collect::<Vec<F>>();
+        end_timer!(eval_time);
+
+        fs_rng.absorb(&evaluations);
+
+        let pc_proof = PC::open_combinations(
+            &index_pk.committer_key,
+            &lc_s,
+            polynomials,
+            &labeled_comms,
+            &query_set,
+            &mut fs_rng,
+            &comm_rands,
+            Some(zk_rng),
+        )
+        .map_err(Error::from_pc_err)?;
+
+        // Gather prover messages together.
+        let prover_messages = vec![prover_first_msg, prover_second_msg, prover_third_msg];
+
+        let proof = Proof::new(commitments, evaluations, prover_messages, pc_proof);
+        end_timer!(prover_time);
+        Ok(proof)
+    }
+
+    /// Verify that a proof for the constrain system defined by `C` asserts that
+    /// all constraints are satisfied.
+    pub fn verify<R: RngCore>(
+        index_vk: &IndexVerifierKey<F, PC>,
+        public_input: &[F],
+        proof: &Proof<F, PC>,
+        rng: &mut R,
+    ) -> Result<bool, Error<PC::Error>> {
+        let verifier_time = start_timer!(|| "Marlin::Verify");
+
+        let public_input = {
+            let domain_x = GeneralEvaluationDomain::<F>::new(public_input.len() + 1).unwrap();
+
+            let mut unpadded_input = public_input.to_vec();
+            unpadded_input.resize(
+                core::cmp::max(public_input.len(), domain_x.size() - 1),
+                F::zero(),
+            );
+
+            unpadded_input
+        };
+        if public_input.len() + 1 != index_vk.index_info.num_instance_variables {
+            return Err(ahp::Error::InvalidPublicInputLength.into());
+        }
+
+        // A proof has the shape the prover gives it, or it is rejected.
+        let index_info = index_vk.index_info;
+        let num_round_oracles = [
+            AHPForR1CS::<F>::prover_first_round_degree_bounds(&index_info).count(),
+            AHPForR1CS::<F>::prover_second_round_degree_bounds(&index_info).count(),
+            AHPForR1CS::<F>::prover_third_round_degree_bounds(&index_info).count(),
+        ];
+        if proof.commitments.iter().map(Vec::len).ne(num_round_oracles)
+            || proof.prover_messages.len() != num_round_oracles.len()
+        {
+            return Ok(false);
+        }
+
+        let mut fs_rng = Self::transcript(index_vk, &public_input);
+
+        // --------------------------------------------------------------------
+        // First round
+
+        let first_comms = &proof.commitments[0];
+        fs_rng.absorb(first_comms);
+        fs_rng.absorb(&proof.prover_messages[0]);
+
+        let (_, verifier_state) =
+            AHPForR1CS::verifier_first_round(index_vk.index_info, &mut fs_rng)?;
+        // --------------------------------------------------------------------
+
+        // --------------------------------------------------------------------
+        // Second round
+        let second_comms = &proof.commitments[1];
+        fs_rng.absorb(second_comms);
+        fs_rng.absorb(&proof.prover_messages[1]);
+
+        let (_, verifier_state) = AHPForR1CS::verifier_second_round(verifier_state, &mut fs_rng);
+        // --------------------------------------------------------------------
+
+        // --------------------------------------------------------------------
+        // Third round
+        let third_comms = &proof.commitments[2];
+        fs_rng.absorb(third_comms);
+        fs_rng.absorb(&proof.prover_messages[2]);
+
+        let verifier_state = AHPForR1CS::verifier_third_round(verifier_state, &mut fs_rng);
+        // --------------------------------------------------------------------
+
+        // Collect degree bounds for commitments. Indexed polynomials have *no*
+        // degree bounds because we know the committed index polynomial has the
+        // correct degree.
+        let degree_bounds = vec![None; index_vk.index_comms.len()]
+            .into_iter()
+            .chain(AHPForR1CS::prover_first_round_degree_bounds(&index_info))
+            .chain(AHPForR1CS::prover_second_round_degree_bounds(&index_info))
+            .chain(AHPForR1CS::prover_third_round_degree_bounds(&index_info))
+            .collect::<Vec<_>>();
+
+        // Gather commitments in one vector.
+        let commitments: Vec<_> = index_vk
+            .iter()
+            .chain(first_comms)
+            .chain(second_comms)
+            .chain(third_comms)
+            .cloned()
+            .zip(AHPForR1CS::<F>::polynomial_labels())
+            .zip(degree_bounds)
+            .map(|((c, l), d)| LabeledCommitment::new(l, c, d))
+            .collect();
+
+        let (query_set, verifier_state) =
+            AHPForR1CS::verifier_query_set(verifier_state, &mut fs_rng);
+
+        fs_rng.absorb(&proof.evaluations);
+
+        let mut evaluations = Evaluations::new();
+        let mut evaluation_labels = Vec::new();
+        for (poly_label, (_, point)) in query_set.iter().cloned() {
+            if AHPForR1CS::<F>::LC_WITH_ZERO_EVAL.contains(&poly_label.as_ref()) {
+                evaluations.insert((poly_label, point), F::zero());
+            } else {
+                evaluation_labels.push((poly_label, point));
+            }
+        }
+        if evaluation_labels.len() != proof.evaluations.len() {
+            return Ok(false);
+        }
+        evaluation_labels.sort_by(|a, b| a.0.cmp(&b.0));
+        for (q, eval) in evaluation_labels.into_iter().zip(&proof.evaluations) {
+            evaluations.insert(q, *eval);
+        }
+
+        let lc_s = AHPForR1CS::construct_linear_combinations(
+            &public_input,
+            &evaluations,
+            &verifier_state,
+        )?;
+
+        let evaluations_are_correct = PC::check_combinations(
+            &index_vk.verifier_key,
+            &lc_s,
+            &commitments,
+            &query_set,
+            &evaluations,
+            &proof.pc_proof,
+            &mut fs_rng,
+            rng,
+        )
+        .map_err(Error::from_pc_err)?;
+
+        end_timer!(verifier_time, || format!(
+            " PC::Check for AHP Verifier linear equations: {}",
+            evaluations_are_correct
+        ));
+        Ok(evaluations_are_correct)
+    }
+}
+
+/// The commitments of a round, without their labels.
+fn commitments<PC: PolynomialCommitment<F, DensePolynomial<F>>, F: PrimeField>(
+    comms: &[LabeledCommitment<PC::Commitment>],
+) -> Vec<PC::Commitment> {
+    comms.iter().map(|c| c.commitment().clone()).collect()
+}
+
+impl<F, PC, D> SNARK<F> for Marlin<F, PC, D>
+where
+    F: PrimeField,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+    PC::Error: 'static,
+    D: Digest,
+{
+    type ProvingKey = IndexProverKey<F, PC>;
+    type VerifyingKey = IndexVerifierKey<F, PC>;
+    type Proof = Proof<F, PC>;
+    type ProcessedVerifyingKey = IndexVerifierKey<F, PC>;
+    type Error = Error<PC::Error>;
+
+    /// Indexes `circuit` against a universal SRS just large enough for it.
+    fn circuit_specific_setup<C: ConstraintSynthesizer<F>, R: RngCore + CryptoRng>(
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<(Self::ProvingKey, Self::VerifyingKey), Self::Error> {
+        let index = AHPForR1CS::index(circuit)?;
+        let srs = PC::setup(index.max_degree(), None, rng).map_err(Error::from_pc_err)?;
+        Self::commit_to_index(&srs, index)
+    }
+
+    fn prove<C: ConstraintSynthesizer<F>, R: RngCore + CryptoRng>(
+        circuit_pk: &Self::ProvingKey,
+        circuit: C,
+        rng: &mut R,
+    ) -> Result<Self::Proof, Self::Error> {
+        Self::prove(circuit_pk, circuit, rng)
+    }
+
+    fn process_vk(
+        circuit_vk: &Self::VerifyingKey,
+    ) -> Result<Self::ProcessedVerifyingKey, Self::Error> {
+        Ok(circuit_vk.clone())
+    }
+
+    /// Batches the pairing checks with randomness drawn from a hash of the
+    /// whole statement, proof included.
+    fn verify_with_processed_vk(
+        circuit_pvk: &Self::ProcessedVerifyingKey,
+        public_input: &[F],
+        proof: &Self::Proof,
+    ) -> Result<bool, Self::Error> {
+        let mut rng = Self::transcript(circuit_pvk, public_input);
+        rng.absorb(proof);
+        Self::verify(circuit_pvk, public_input, proof, &mut rng)
+    }
+}
+
+impl<F, PC, D> UniversalSetupSNARK<F> for Marlin<F, PC, D>
+where
+    F: PrimeField,
+    PC: PolynomialCommitment<F, DensePolynomial<F>>,
+    PC::Error: 'static,
+    D: Digest,
+{
+    type ComputationBound = ComputationBound;
+    type PublicParameters = UniversalSRS<F, PC>;
+
+    fn universal_setup<R: RngCore + CryptoRng>(
+        bound: &ComputationBound,
+        rng: &mut R,
+    ) -> Result<Self::PublicParameters, Self::Error> {
+        Self::universal_setup(
+            bound.num_constraints,
+            bound.num_variables,
+            bound.num_non_zero,
+            rng,
+        )
+    }
+
+    fn index<C: ConstraintSynthesizer<F>, R: RngCore + CryptoRng>(
+        pp: &Self::PublicParameters,
+        circuit: C,
+        _: &mut R,
+    ) -> Result<
+        (Self::ProvingKey, Self::VerifyingKey),
+        UniversalSetupIndexError<ComputationBound, Self::Error>,
+    > {
+        let index = AHPForR1CS::index(circuit)
+            .map_err(|err| UniversalSetupIndexError::Other(err.into()))?;
+        if pp.max_degree() < index.max_degree() {
+            let info = index.index_info;
+            return Err(UniversalSetupIndexError::NeedLargerBound(
+                ComputationBound {
+                    num_constraints: info.num_constraints,
+                    num_variables: info.num_variables,
+                    num_non_zero: info.num_non_zero,
+                },
+            ));
+        }
+        Self::commit_to_index(pp, index).map_err(UniversalSetupIndexError::Other)
+    }
+}
+
+#[cfg(test)]
+mod test;
@@ -0,0 +1,119 @@
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
+use ark_serialize::CanonicalSerialize;
+use ark_std::marker::PhantomData;
+use ark_std::rand::{RngCore, SeedableRng};
+use digest::{generic_array::GenericArray, Digest};
+use rand_chacha::ChaChaRng;
+
+/// A `SeedableRng` that refreshes its seed by hashing together the previous seed
+/// and the new seed material.
+///
+/// It is also the `CryptographicSponge` the polynomial commitment squeezes
+/// its opening challenges from, so those are bound to the whole transcript.
+// TODO: later: re-evaluate decision about ChaChaRng
+pub struct FiatShamirRng<D: Digest> {
+    r: ChaChaRng,
+    seed: GenericArray<u8, D::OutputSize>,
+    #[doc(hidden)]
+    digest: PhantomData<D>,
+}
+
+impl<D: Digest> Clone for FiatShamirRng<D> {
+    fn clone(&self) -> Self {
+        Self {
+            r: self.r.clone(),
+            seed: self.seed.clone(),
+            digest: PhantomData,
+        }
+    }
+}
+
+impl<D: Digest> RngCore for FiatShamirRng<D> {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        self.r.next_u32()
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.r.next_u64()
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.r.fill_bytes(dest);
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), ark_std::rand::Error> {
+        self.r.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<D: Digest> FiatShamirRng<D> {
+    /// Create a new `Self` by initializing with a fresh seed.
+    /// `self.seed = H(self.seed || new_seed)`.
+    #[inline]
+    pub fn from_seed<T: CanonicalSerialize>(seed: &T) -> Self {
+        let mut bytes = Vec::new();
+        seed.serialize_compressed(&mut bytes)
+            .expect("failed to convert to bytes");
+        let seed = D::digest(&bytes);
+        Self {
+            r: ChaChaRng::from_seed(Self::chacha_seed(&seed)),
+            seed,
+            digest: PhantomData,
+        }
+    }
+
+    /// Refresh `self.seed` with new material. Achieved by setting
+    /// `self.seed = H(self.seed || new_seed)`.
+    #[inline]
+    pub fn absorb<T: CanonicalSerialize>(&mut self, seed: &T) {
+        let mut bytes = Vec::new();
+        seed.serialize_compressed(&mut bytes)
+            .expect("failed to convert to bytes");
+        self.absorb_bytes(&bytes);
+    }
+
+    fn absorb_bytes(&mut self, bytes: &[u8]) {
+        let mut bytes = bytes.to_vec();
+        bytes.extend_from_slice(&self.seed);
+        self.seed = D::digest(&bytes);
+        self.r = ChaChaRng::from_seed(Self::chacha_seed(&self.seed));
+    }
+
+    fn chacha_seed(digest: &[u8]) -> [u8; 32] {
+        digest
+            .get(..32)
+            .and_then(|seed| seed.try_into().ok())
+            .expect("the digest should have at least 32 bytes")
+    }
+}
+
+impl<D: Digest> CryptographicSponge for FiatShamirRng<D> {
+    type Config = ();
+
+    fn new(_: &()) -> Self {
+        Self::from_seed(&())
+    }
+
+    fn absorb(&mut self, input: &impl Absorb) {
+        self.absorb_bytes(&input.to_sponge_bytes_as_vec());
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        let mut bytes = vec![0; num_bytes];
+        self.fill_bytes(&mut bytes);
+        bytes
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        self.squeeze_bytes(num_bits.div_ceil(8))
+            .into_iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .take(num_bits)
+            .collect()
+    }
+}
@@ -0,0 +1,244 @@
+use ark_ff::Field;
+use ark_relations::{
+    lc,
+    r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError},
+};
+use ark_std::marker::PhantomData;
+
+#[derive(Copy, Clone)]
+struct Circuit<F: Field> {
+    a: Option<F>,
+    b: Option<F>,
+    num_constraints: usize,
+    num_variables: usize,
+}
+
+impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for Circuit<ConstraintF> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> Result<(), SynthesisError> {
+        let a = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        let b = cs.new_witness_variable(|| self.b.ok_or(SynthesisError::AssignmentMissing))?;
+        let c = cs.new_input_variable(|| {
+            let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+            a.mul_assign(&b);
+            Ok(a)
+        })?;
+        let d = cs.new_input_variable(|| {
+            let mut a = self.a.ok_or(SynthesisError::AssignmentMissing)?;
+            let b = self.b.ok_or(SynthesisError::AssignmentMissing)?;
+
+            a.mul_assign(&b);
+            a.mul_assign(&b);
+            Ok(a)
+        })?;
+
+        for _ in 0..(self.num_variables - 3) {
+            let _ = cs.new_witness_variable(|| self.a.ok_or(SynthesisError::AssignmentMissing))?;
+        }
+
+        for _ in 0..(self.num_constraints - 1) {
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+        }
+        cs.enforce_constraint(lc!() + c, lc!() + b, lc!() + d)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+/// Define a constraint system that would trigger outlining.
+struct OutlineTestCircuit<F: Field> {
+    field_phantom: PhantomData<F>,
+}
+
+impl<F: Field> ConstraintSynthesizer<F> for OutlineTestCircuit<F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> Result<(), SynthesisError> {
+        // This program checks if the input elements are between 0 and 9.
+        //
+        // Note that this constraint system is neither the most intuitive way nor
+        // the most efficient way for such a task. It is for testing purposes,
+        // as we want to trigger the outlining.
+        //
+        let mut inputs = Vec::new();
+        for i in 0..5 {
+            inputs.push(cs.new_input_variable(|| Ok(F::from(i as u128)))?);
+        }
+
+        for (i, input) in inputs.iter().enumerate() {
+            let mut total_count_for_this_input = cs.new_lc(lc!()).unwrap();
+
+            for bucket in 0..10 {
+                let count_increment_for_this_bucket =
+                    cs.new_witness_variable(|| Ok(F::from(i == bucket)))?;
+
+                total_count_for_this_input = cs
+                    .new_lc(
+                        lc!()
+                            + (F::one(), total_count_for_this_input)
+                            + (F::one(), count_increment_for_this_bucket),
+                    )
+                    .unwrap();
+
+                // Only when `input[i]` equals `bucket` can `count_increment_for_this_bucket` be nonzero.
+                //
+                // A malicious prover can make `count_increment_for_this_bucket` neither 0 nor 1.
+                // But the constraint on `total_count_for_this_input` will reject such case.
+                //
+                // At a high level, only one of the `count_increment_for_this_bucket` among all the buckets
+                // could be nonzero, which equals `total_count_for_this_input`. Thus, by checking whether
+                // `total_count_for_this_input` is 1, we know this input number is in the range.
+                //
+                cs.enforce_constraint(
+                    lc!() + (F::one(), *input)
+                        - (F::from(bucket as u128), ark_relations::r1cs::Variable::One),
+                    lc!() + (F::one(), count_increment_for_this_bucket),
+                    lc!(),
+                )?;
+            }
+
+            // Enforce `total_count_for_this_input` to be one.
+            cs.enforce_constraint(
+                lc!(),
+                lc!(),
+                lc!() + (F::one(), total_count_for_this_input)
+                    - (F::one(), ark_relations::r1cs::Variable::One),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+mod marlin {
+    use super::*;
+    use crate::{ComputationBound, Marlin};
+
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_ff::UniformRand;
+    use ark_poly::univariate::DensePolynomial;
+    use ark_poly_commit::marlin_pc::MarlinKZG10;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_snark::{UniversalSetupIndexError, UniversalSetupSNARK, SNARK};
+    use ark_std::ops::MulAssign;
+    use ark_std::rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use sha3::Sha3_256;
+
+    type MultiPC = MarlinKZG10<Bls12_381, DensePolynomial<Fr>>;
+    type MarlinInst = Marlin<Fr, MultiPC, Sha3_256>;
+
+    fn test_circuit(num_constraints: usize, num_variables: usize) {
+        let rng = &mut ark_std::test_rng();
+
+        let universal_srs = MarlinInst::universal_setup(100, 25, 100, rng).unwrap();
+
+        for _ in 0..10 {
+            let a = Fr::rand(rng);
+            let b = Fr::rand(rng);
+            let mut c = a;
+            c.mul_assign(&b);
+            let mut d = c;
+            d.mul_assign(&b);
+
+            let circ = Circuit {
+                a: Some(a),
+                b: Some(b),
+                num_constraints,
+                num_variables,
+            };
+
+            let (index_pk, index_vk) = MarlinInst::index(&universal_srs, circ).unwrap();
+            let proof = MarlinInst::prove(&index_pk, circ, rng).unwrap();
+            assert!(MarlinInst::verify(&index_vk, &[c, d], &proof, rng).unwrap());
+            assert!(!MarlinInst::verify(&index_vk, &[a, a], &proof, rng).unwrap());
+        }
+    }
+
+    #[test]
+    fn prove_and_verify_with_tall_matrix_big() {
+        test_circuit(100, 25);
+    }
+
+    #[test]
+    fn prove_and_verify_with_tall_matrix_small() {
+        test_circuit(26, 25);
+    }
+
+    #[test]
+    fn prove_and_verify_with_squat_matrix_big() {
+        test_circuit(25, 100);
+    }
+
+    #[test]
+    fn prove_and_verify_with_squat_matrix_small() {
+        test_circuit(25, 26);
+    }
+
+    #[test]
+    fn prove_and_verify_with_square_matrix() {
+        test_circuit(25, 25);
+    }
+
+    #[test]
+    /// Test on a constraint system that will trigger outlining.
+    fn prove_and_test_outlining() {
+        let rng = &mut ark_std::test_rng();
+
+        let universal_srs = MarlinInst::universal_setup(150, 150, 150, rng).unwrap();
+
+        let circ = OutlineTestCircuit {
+            field_phantom: PhantomData,
+        };
+
+        let (index_pk, index_vk) = MarlinInst::index(&universal_srs, circ.clone()).unwrap();
+        let proof = MarlinInst::prove(&index_pk, circ, rng).unwrap();
+
+        let inputs: Vec<_> = (0..5u128).map(Fr::from).collect();
+        assert!(MarlinInst::verify(&index_vk, &inputs, &proof, rng).unwrap());
+    }
+
+    #[test]
+    fn snark_traits() {
+        let rng = &mut ChaChaRng::seed_from_u64(0);
+        let (a, b) = (Fr::rand(rng), Fr::rand(rng));
+        let circ = Circuit {
+            a: Some(a),
+            b: Some(b),
+            num_constraints: 25,
+            num_variables: 25,
+        };
+        let inputs = [a * b, a * b * b];
+
+        let (pk, vk) = MarlinInst::circuit_specific_setup(circ, rng).unwrap();
+        let proof = <MarlinInst as SNARK<Fr>>::prove(&pk, circ, rng).unwrap();
+        assert!(<MarlinInst as SNARK<Fr>>::verify(&vk, &inputs, &proof).unwrap());
+        assert!(!<MarlinInst as SNARK<Fr>>::verify(&vk, &[a, b], &proof).unwrap());
+        assert!(<MarlinInst as SNARK<Fr>>::verify(&vk, &inputs[..1], &proof).is_err());
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let proof = crate::Proof::deserialize_compressed(&*bytes).unwrap();
+        assert!(<MarlinInst as SNARK<Fr>>::verify(&vk, &inputs, &proof).unwrap());
+
+        // The circuit-specific SRS is just large enough for this circuit.
+        let pp = <MarlinInst as UniversalSetupSNARK<Fr>>::universal_setup(
+            &ComputationBound {
+                num_constraints: 10,
+                num_variables: 10,
+                num_non_zero: 10,
+            },
+            rng,
+        )
+        .unwrap();
+        match <MarlinInst as UniversalSetupSNARK<Fr>>::index(&pp, circ, rng) {
+            Err(UniversalSetupIndexError::NeedLargerBound(bound)) => {
+                assert!(bound.num_constraints > 10)
+            }
+            _ => panic!("a small SRS indexed a large circuit"),
+        }
+    }
+}
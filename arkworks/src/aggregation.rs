@@ -0,0 +1,795 @@
+//! Aggregation of many Groth16 proofs of [`SudokuCircuit`] into one proof
+//! with logarithmic verification, after SnarkPack
+//! (Gailly, Maller, Nitulescu, <https://eprint.iacr.org/2021/529>).
+//!
+//! The prover commits to the `A`, `B` and `C` elements of the `n` proofs and
+//! derives a random `r` from the commitments, the puzzles and both
+//! verifying keys. Every Groth16
+//! check `e(A_i, B_i) = e(α, β) · e(S_i, γ) · e(C_i, δ)` is then folded into
+//! one check on
+//!
+//! - `Z_AB = ∏ e(A_i, B_i)^{r^i}`, proven against the commitment to `A` and
+//!   `B` by a TIPP (target inner pairing product) argument, and
+//! - `Z_C = ∑ r^i · C_i`, proven against the commitment to `C` by a MIPP
+//!   (multiexponentiation inner product) argument.
+//!
+//! Both arguments halve their vectors for `log n` rounds, so the aggregate
+//! proof and the verifier's pairing work are logarithmic in `n`. The
+//! commitment keys the prover folds along the way are checked with KZG
+//! openings. Only the aggregated public input `∑ r^i · S_i` is linear in `n`,
+//! as the verifier has to read every puzzle anyway.
+//!
+//! The commitment keys are powers of two secrets `α` and `β` in both groups.
+//! [`setup`] samples them, which is only sound if the secrets are discarded;
+//! a deployment would take them from two existing powers-of-tau ceremonies.
+
+use crate::circuit::SudokuCircuit;
+use crate::transcript::Transcript;
+use ark_ec::pairing::{Pairing, PairingOutput};
+use ark_ec::{AffineRepr, CurveGroup, PrimeGroup, ScalarMul, VariableBaseMSM};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{CryptoRng, RngCore};
+
+/// Commitment keys for aggregating up to [`ProverKey::max_proofs`] proofs.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProverKey<E: Pairing> {
+    /// The verifier's half, which the transcript starts from.
+    vk: VerifierKey<E>,
+    /// `g^{α^i}` for `i < 2 * max_proofs`.
+    g_alpha_powers: Vec<E::G1Affine>,
+    /// `g^{β^i}` for `i < 2 * max_proofs`.
+    g_beta_powers: Vec<E::G1Affine>,
+    /// `h^{α^i}` for `i < max_proofs`.
+    h_alpha_powers: Vec<E::G2Affine>,
+    /// `h^{β^i}` for `i < max_proofs`.
+    h_beta_powers: Vec<E::G2Affine>,
+}
+
+impl<E: Pairing> ProverKey<E> {
+    pub fn max_proofs(&self) -> usize {
+        self.h_alpha_powers.len()
+    }
+}
+
+/// The elements of the SRS needed to check the KZG openings of the folded
+/// commitment keys.
+#[derive(Clone, Copy, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifierKey<E: Pairing> {
+    g: E::G1Affine,
+    h: E::G2Affine,
+    g_alpha: E::G1Affine,
+    g_beta: E::G1Affine,
+    h_alpha: E::G2Affine,
+    h_beta: E::G2Affine,
+}
+
+/// A commitment to vectors under the `α` keys (`t`) and the `β` keys (`u`).
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+struct Commitment<E: Pairing> {
+    t: PairingOutput<E>,
+    u: PairingOutput<E>,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+struct TippRound<E: Pairing> {
+    com_l: Commitment<E>,
+    com_r: Commitment<E>,
+    z_l: PairingOutput<E>,
+    z_r: PairingOutput<E>,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+struct TippProof<E: Pairing> {
+    rounds: Vec<TippRound<E>>,
+    final_a: E::G1Affine,
+    final_b: E::G2Affine,
+    final_v1: E::G2Affine,
+    final_v2: E::G2Affine,
+    final_w1: E::G1Affine,
+    final_w2: E::G1Affine,
+    v1_opening: E::G2Affine,
+    v2_opening: E::G2Affine,
+    w1_opening: E::G1Affine,
+    w2_opening: E::G1Affine,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+struct MippRound<E: Pairing> {
+    com_l: Commitment<E>,
+    com_r: Commitment<E>,
+    z_l: E::G1Affine,
+    z_r: E::G1Affine,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+struct MippProof<E: Pairing> {
+    rounds: Vec<MippRound<E>>,
+    final_c: E::G1Affine,
+    final_v1: E::G2Affine,
+    final_v2: E::G2Affine,
+    v1_opening: E::G2Affine,
+    v2_opening: E::G2Affine,
+}
+
+/// One proof standing in for `n` Groth16 proofs.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregateProof<E: Pairing> {
+    com_ab: Commitment<E>,
+    com_c: Commitment<E>,
+    z_ab: PairingOutput<E>,
+    z_c: E::G1Affine,
+    tipp: TippProof<E>,
+    mipp: MippProof<E>,
+}
+
+#[derive(Debug)]
+pub enum AggregationError {
+    NotPowerOfTwo(usize),
+    TooManyProofs { max: usize, found: usize },
+    LengthMismatch { proofs: usize, puzzles: usize },
+}
+
+impl std::fmt::Display for AggregationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AggregationError::NotPowerOfTwo(n) => {
+                write!(f, "Can only aggregate a power of two proofs, got {n}")
+            }
+            AggregationError::TooManyProofs { max, found } => {
+                write!(f, "Got {found} proofs, the keys support at most {max}")
+            }
+            AggregationError::LengthMismatch { proofs, puzzles } => {
+                write!(f, "Got {proofs} proofs for {puzzles} puzzles")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AggregationError {}
+
+/// Samples commitment keys for aggregating up to `max_proofs` proofs.
+pub fn setup<E: Pairing, R: RngCore + CryptoRng>(
+    max_proofs: usize,
+    rng: &mut R,
+) -> (ProverKey<E>, VerifierKey<E>) {
+    let alpha = E::ScalarField::rand(rng);
+    let beta = E::ScalarField::rand(rng);
+    let g = E::G1::generator();
+    let h = E::G2::generator();
+    let alpha_powers = powers(alpha, 2 * max_proofs);
+    let beta_powers = powers(beta, 2 * max_proofs);
+    let vk = VerifierKey {
+        g: g.into_affine(),
+        h: h.into_affine(),
+        g_alpha: (g * alpha).into_affine(),
+        g_beta: (g * beta).into_affine(),
+        h_alpha: (h * alpha).into_affine(),
+        h_beta: (h * beta).into_affine(),
+    };
+    let pk = ProverKey {
+        vk,
+        g_alpha_powers: g.batch_mul(&alpha_powers),
+        g_beta_powers: g.batch_mul(&beta_powers),
+        h_alpha_powers: h.batch_mul(&alpha_powers[..max_proofs]),
+        h_beta_powers: h.batch_mul(&beta_powers[..max_proofs]),
+    };
+    (pk, vk)
+}
+
+/// Aggregates `proofs`, where `proofs[i]` proves a solution to `puzzles[i]`
+/// under `groth16_vk`. The number of proofs must be a power of two.
+pub fn aggregate<E: Pairing, const N: usize>(
+    pk: &ProverKey<E>,
+    groth16_vk: &VerifyingKey<E>,
+    puzzles: &[[[u8; N]; N]],
+    proofs: &[Proof<E>],
+) -> Result<AggregateProof<E>, AggregationError> {
+    if proofs.len() != puzzles.len() {
+        return Err(AggregationError::LengthMismatch {
+            proofs: proofs.len(),
+            puzzles: puzzles.len(),
+        });
+    }
+    let n = check_count(puzzles.len())?;
+    if n > pk.max_proofs() {
+        return Err(AggregationError::TooManyProofs {
+            max: pk.max_proofs(),
+            found: n,
+        });
+    }
+
+    let a: Vec<_> = proofs.iter().map(|proof| proof.a).collect();
+    let b: Vec<_> = proofs.iter().map(|proof| proof.b).collect();
+    let c: Vec<_> = proofs.iter().map(|proof| proof.c).collect();
+    let v1 = &pk.h_alpha_powers[..n];
+    let v2 = &pk.h_beta_powers[..n];
+    let w1 = &pk.g_alpha_powers[n..2 * n];
+    let w2 = &pk.g_beta_powers[n..2 * n];
+
+    let com_ab = Commitment {
+        t: pair(&[&a, w1], &[v1, &b]),
+        u: pair(&[&a, w2], &[v2, &b]),
+    };
+    let com_c = Commitment {
+        t: pair(&[&c], &[v1]),
+        u: pair(&[&c], &[v2]),
+    };
+    let mut transcript = transcript(&pk.vk, groth16_vk, puzzles);
+    transcript.append(&com_ab);
+    transcript.append(&com_c);
+    let r: E::ScalarField = transcript.challenge();
+    let r_inv = r.inverse().expect("challenges are non-zero");
+    let r_powers = powers(r, n);
+    let r_inv_powers = powers(r_inv, n);
+
+    // `A_i^{r^i}` under the keys `v_i^{r^{-i}}` has the same commitment as
+    // `A_i` under `v_i`, so the TIPP can run against `com_ab`.
+    let a = scale::<E::G1>(&a, &r_powers);
+    let z_ab = pair(&[&a], &[&b]);
+    let z_c = E::G1::msm_unchecked(&c, &r_powers).into_affine();
+    transcript.append(&z_ab);
+    transcript.append(&z_c);
+
+    let tipp = prove_tipp(
+        pk,
+        &mut transcript,
+        r_inv,
+        [a, w1.to_vec(), w2.to_vec()],
+        [
+            b,
+            scale::<E::G2>(v1, &r_inv_powers),
+            scale::<E::G2>(v2, &r_inv_powers),
+        ],
+    );
+    let mipp = prove_mipp(pk, &mut transcript, c, r_powers, [v1.to_vec(), v2.to_vec()]);
+
+    Ok(AggregateProof {
+        com_ab,
+        com_c,
+        z_ab,
+        z_c,
+        tipp,
+        mipp,
+    })
+}
+
+/// Checks an aggregate proof against the puzzles of the proofs it
+/// aggregates, in the same order. `groth16_vk` is the verifying key of the
+/// individual proofs.
+pub fn verify_aggregate<E: Pairing, const N: usize>(
+    vk: &VerifierKey<E>,
+    groth16_vk: &VerifyingKey<E>,
+    puzzles: &[[[u8; N]; N]],
+    proof: &AggregateProof<E>,
+) -> Result<bool, AggregationError> {
+    let n = check_count(puzzles.len())?;
+    if groth16_vk.gamma_abc_g1.len() != SudokuCircuit::<N>::num_inputs::<E::ScalarField>() + 1 {
+        return Ok(false);
+    }
+
+    let mut transcript = transcript(vk, groth16_vk, puzzles);
+    transcript.append(&proof.com_ab);
+    transcript.append(&proof.com_c);
+    let r: E::ScalarField = transcript.challenge();
+    let r_inv = r.inverse().expect("challenges are non-zero");
+    transcript.append(&proof.z_ab);
+    transcript.append(&proof.z_c);
+
+    // The folded Groth16 equation:
+    // Z_AB = e(α, β)^{∑ r^i} · e(∑ r^i · S_i, γ) · e(Z_C, δ).
+    let r_powers = powers(r, n);
+    let mut input_scalars = vec![E::ScalarField::zero(); groth16_vk.gamma_abc_g1.len()];
+    input_scalars[0] = r_powers.iter().sum();
+    for (puzzle, r_power) in puzzles.iter().zip(&r_powers) {
+        let inputs = SudokuCircuit::public_inputs::<E::ScalarField>(puzzle);
+        for (scalar, input) in input_scalars[1..].iter_mut().zip(inputs) {
+            *scalar += input * r_power;
+        }
+    }
+    let inputs = E::G1::msm_unchecked(&groth16_vk.gamma_abc_g1, &input_scalars).into_affine();
+    let groth16_holds = proof.z_ab
+        == E::pairing(groth16_vk.alpha_g1, groth16_vk.beta_g2) * input_scalars[0]
+            + E::multi_pairing(
+                [inputs, proof.z_c],
+                [groth16_vk.gamma_g2, groth16_vk.delta_g2],
+            );
+
+    Ok(groth16_holds
+        && verify_tipp(vk, &mut transcript, n, r_inv, proof)
+        && verify_mipp(vk, &mut transcript, n, r, proof))
+}
+
+/// The transcript both sides start from, bound to the keys as well as the
+/// puzzles so a proof can't be replayed under others.
+fn transcript<E: Pairing, const N: usize>(
+    vk: &VerifierKey<E>,
+    groth16_vk: &VerifyingKey<E>,
+    puzzles: &[[[u8; N]; N]],
+) -> Transcript {
+    let mut transcript = Transcript::new(b"sudoku-snark/aggregation", puzzles);
+    transcript.append(vk);
+    transcript.append(groth16_vk);
+    transcript
+}
+
+fn check_count(n: usize) -> Result<usize, AggregationError> {
+    if n.is_power_of_two() {
+        Ok(n)
+    } else {
+        Err(AggregationError::NotPowerOfTwo(n))
+    }
+}
+
+/// `1, x, x^2, ..., x^{len - 1}`.
+fn powers<F: Field>(x: F, len: usize) -> Vec<F> {
+    std::iter::successors(Some(F::one()), |power| Some(*power * x))
+        .take(len)
+        .collect()
+}
+
+/// The product of the pairings of matching elements of the concatenations
+/// of `left` and `right`.
+fn pair<E: Pairing>(left: &[&[E::G1Affine]], right: &[&[E::G2Affine]]) -> PairingOutput<E> {
+    E::multi_pairing(
+        left.iter().flat_map(|part| part.iter().copied()),
+        right.iter().flat_map(|part| part.iter().copied()),
+    )
+}
+
+fn scale<G: CurveGroup>(points: &[G::Affine], scalars: &[G::ScalarField]) -> Vec<G::Affine> {
+    let scaled: Vec<G> = points.iter().zip(scalars).map(|(p, s)| *p * s).collect();
+    G::normalize_batch(&scaled)
+}
+
+/// `left + x · right`, element-wise.
+fn fold<G: CurveGroup>(
+    left: &[G::Affine],
+    right: &[G::Affine],
+    x: G::ScalarField,
+) -> Vec<G::Affine> {
+    let folded: Vec<G> = left.iter().zip(right).map(|(l, r)| *r * x + l).collect();
+    G::normalize_batch(&folded)
+}
+
+/// The coefficients of `∏_j (1 + x_j · X^{n / 2^{j + 1}})`, the polynomial
+/// whose coefficients are the weights of the original keys in a key folded
+/// with the challenges `x_j`.
+fn key_polynomial<F: Field>(challenges: &[F]) -> Vec<F> {
+    let n = 1 << challenges.len();
+    let mut coeffs = vec![F::one(); n];
+    for (round, x) in challenges.iter().enumerate() {
+        let half = n >> (round + 1);
+        for (i, coeff) in coeffs.iter_mut().enumerate() {
+            if i & half != 0 {
+                *coeff *= x;
+            }
+        }
+    }
+    coeffs
+}
+
+/// Evaluates [`key_polynomial`] in `log n` steps.
+fn evaluate_key_polynomial<F: Field>(challenges: &[F], point: F) -> F {
+    let n = 1u64 << challenges.len();
+    challenges
+        .iter()
+        .enumerate()
+        .map(|(round, x)| F::one() + *x * point.pow([n >> (round + 1)]))
+        .product()
+}
+
+/// The quotient of `coeffs` by `X - point`, dropping the remainder.
+fn divide_by_linear<F: Field>(coeffs: &[F], point: F) -> Vec<F> {
+    let mut quotient = vec![F::zero(); coeffs.len().saturating_sub(1)];
+    let mut carry = F::zero();
+    for i in (1..coeffs.len()).rev() {
+        carry = coeffs[i] + carry * point;
+        quotient[i - 1] = carry;
+    }
+    quotient
+}
+
+/// Proves `com_ab` and `z_ab` for `a` (`A_i^{r^i}`) and `b`, where `v`
+/// holds the `α` and `β` keys for `a` (scaled by `r^{-i}`) and `w` those
+/// for `b`.
+fn prove_tipp<E: Pairing>(
+    pk: &ProverKey<E>,
+    transcript: &mut Transcript,
+    r_inv: E::ScalarField,
+    [mut a, mut w1, mut w2]: [Vec<E::G1Affine>; 3],
+    [mut b, mut v1, mut v2]: [Vec<E::G2Affine>; 3],
+) -> TippProof<E> {
+    let n = a.len();
+    let mut rounds = Vec::new();
+    let mut challenges = Vec::new();
+    while a.len() > 1 {
+        let half = a.len() / 2;
+        let (a_l, a_r) = a.split_at(half);
+        let (b_l, b_r) = b.split_at(half);
+        let (v1_l, v1_r) = v1.split_at(half);
+        let (v2_l, v2_r) = v2.split_at(half);
+        let (w1_l, w1_r) = w1.split_at(half);
+        let (w2_l, w2_r) = w2.split_at(half);
+        let round = TippRound {
+            com_l: Commitment {
+                t: pair(&[a_r, w1_r], &[v1_l, b_l]),
+                u: pair(&[a_r, w2_r], &[v2_l, b_l]),
+            },
+            com_r: Commitment {
+                t: pair(&[a_l, w1_l], &[v1_r, b_r]),
+                u: pair(&[a_l, w2_l], &[v2_r, b_r]),
+            },
+            z_l: pair(&[a_r], &[b_l]),
+            z_r: pair(&[a_l], &[b_r]),
+        };
+        transcript.append(&round);
+        let x: E::ScalarField = transcript.challenge();
+        let x_inv = x.inverse().expect("challenges are non-zero");
+        a = fold::<E::G1>(a_l, a_r, x);
+        b = fold::<E::G2>(b_l, b_r, x_inv);
+        v1 = fold::<E::G2>(v1_l, v1_r, x_inv);
+        v2 = fold::<E::G2>(v2_l, v2_r, x_inv);
+        w1 = fold::<E::G1>(w1_l, w1_r, x);
+        w2 = fold::<E::G1>(w2_l, w2_r, x);
+        rounds.push(round);
+        challenges.push(x);
+    }
+    let (final_a, final_b) = (a[0], b[0]);
+    let (final_v1, final_v2, final_w1, final_w2) = (v1[0], v2[0], w1[0], w2[0]);
+    transcript.append(&[final_a, final_w1, final_w2]);
+    transcript.append(&[final_b, final_v1, final_v2]);
+    let point: E::ScalarField = transcript.challenge();
+
+    // v = h^{f(α)} for the key polynomial of the inverse challenges with
+    // its coefficients scaled by `r^{-i}`.
+    let challenge_invs: Vec<_> = challenges.iter().map(|x| x.inverse().unwrap()).collect();
+    let mut v_polynomial = key_polynomial(&challenge_invs);
+    for (coeff, r_inv_power) in v_polynomial.iter_mut().zip(powers(r_inv, n)) {
+        *coeff *= r_inv_power;
+    }
+    let v_quotient = divide_by_linear(&v_polynomial, point);
+    // w = g^{f(α)} for `X^n` times the key polynomial of the challenges.
+    let mut w_polynomial = vec![E::ScalarField::zero(); n];
+    w_polynomial.extend(key_polynomial(&challenges));
+    let w_quotient = divide_by_linear(&w_polynomial, point);
+
+    let commit_g1 = |bases: &[E::G1Affine], coeffs: &[E::ScalarField]| {
+        E::G1::msm_unchecked(&bases[..coeffs.len()], coeffs).into_affine()
+    };
+    let commit_g2 = |bases: &[E::G2Affine], coeffs: &[E::ScalarField]| {
+        E::G2::msm_unchecked(&bases[..coeffs.len()], coeffs).into_affine()
+    };
+    TippProof {
+        rounds,
+        final_a,
+        final_b,
+        final_v1,
+        final_v2,
+        final_w1,
+        final_w2,
+        v1_opening: commit_g2(&pk.h_alpha_powers, &v_quotient),
+        v2_opening: commit_g2(&pk.h_beta_powers, &v_quotient),
+        w1_opening: commit_g1(&pk.g_alpha_powers, &w_quotient),
+        w2_opening: commit_g1(&pk.g_beta_powers, &w_quotient),
+    }
+}
+
+/// Proves `com_c` and `z_c` for `c` and the scalars `b` (`r^i`), where `v`
+/// holds the `α` and `β` keys for `c`.
+fn prove_mipp<E: Pairing>(
+    pk: &ProverKey<E>,
+    transcript: &mut Transcript,
+    mut c: Vec<E::G1Affine>,
+    mut b: Vec<E::ScalarField>,
+    [mut v1, mut v2]: [Vec<E::G2Affine>; 2],
+) -> MippProof<E> {
+    let mut rounds = Vec::new();
+    let mut challenges = Vec::new();
+    while c.len() > 1 {
+        let half = c.len() / 2;
+        let (c_l, c_r) = c.split_at(half);
+        let (b_l, b_r) = b.split_at(half);
+        let (v1_l, v1_r) = v1.split_at(half);
+        let (v2_l, v2_r) = v2.split_at(half);
+        let round = MippRound {
+            com_l: Commitment {
+                t: pair(&[c_r], &[v1_l]),
+                u: pair(&[c_r], &[v2_l]),
+            },
+            com_r: Commitment {
+                t: pair(&[c_l], &[v1_r]),
+                u: pair(&[c_l], &[v2_r]),
+            },
+            z_l: E::G1::msm_unchecked(c_r, b_l).into_affine(),
+            z_r: E::G1::msm_unchecked(c_l, b_r).into_affine(),
+        };
+        transcript.append(&round);
+        let x: E::ScalarField = transcript.challenge();
+        let x_inv = x.inverse().expect("challenges are non-zero");
+        c = fold::<E::G1>(c_l, c_r, x);
+        b = b_l.iter().zip(b_r).map(|(l, r)| *l + x_inv * r).collect();
+        v1 = fold::<E::G2>(v1_l, v1_r, x_inv);
+        v2 = fold::<E::G2>(v2_l, v2_r, x_inv);
+        rounds.push(round);
+        challenges.push(x_inv);
+    }
+    let (final_c, final_v1, final_v2) = (c[0], v1[0], v2[0]);
+    transcript.append(&final_c);
+    transcript.append(&[final_v1, final_v2]);
+    let point: E::ScalarField = transcript.challenge();
+
+    let v_quotient = divide_by_linear(&key_polynomial(&challenges), point);
+    let commit = |bases: &[E::G2Affine]| {
+        E::G2::msm_unchecked(&bases[..v_quotient.len()], &v_quotient).into_affine()
+    };
+    MippProof {
+        rounds,
+        final_c,
+        final_v1,
+        final_v2,
+        v1_opening: commit(&pk.h_alpha_powers),
+        v2_opening: commit(&pk.h_beta_powers),
+    }
+}
+
+fn verify_tipp<E: Pairing>(
+    vk: &VerifierKey<E>,
+    transcript: &mut Transcript,
+    n: usize,
+    r_inv: E::ScalarField,
+    proof: &AggregateProof<E>,
+) -> bool {
+    let tipp = &proof.tipp;
+    if 1 << tipp.rounds.len() != n {
+        return false;
+    }
+    let (mut t, mut u, mut z) = (proof.com_ab.t, proof.com_ab.u, proof.z_ab);
+    let mut challenges = Vec::new();
+    for round in &tipp.rounds {
+        transcript.append(round);
+        let x: E::ScalarField = transcript.challenge();
+        let x_inv = x.inverse().expect("challenges are non-zero");
+        t += round.com_l.t * x + round.com_r.t * x_inv;
+        u += round.com_l.u * x + round.com_r.u * x_inv;
+        z += round.z_l * x + round.z_r * x_inv;
+        challenges.push(x);
+    }
+    transcript.append(&[tipp.final_a, tipp.final_w1, tipp.final_w2]);
+    transcript.append(&[tipp.final_b, tipp.final_v1, tipp.final_v2]);
+    let point: E::ScalarField = transcript.challenge();
+
+    let challenge_invs: Vec<_> = challenges.iter().map(|x| x.inverse().unwrap()).collect();
+    let v_eval = evaluate_key_polynomial(&challenge_invs, point * r_inv);
+    let w_eval = point.pow([n as u64]) * evaluate_key_polynomial(&challenges, point);
+
+    z == E::pairing(tipp.final_a, tipp.final_b)
+        && t == pair(
+            &[&[tipp.final_a, tipp.final_w1]],
+            &[&[tipp.final_v1, tipp.final_b]],
+        )
+        && u == pair(
+            &[&[tipp.final_a, tipp.final_w2]],
+            &[&[tipp.final_v2, tipp.final_b]],
+        )
+        && check_g2_opening(
+            vk,
+            vk.g_alpha,
+            tipp.final_v1,
+            tipp.v1_opening,
+            point,
+            v_eval,
+        )
+        && check_g2_opening(vk, vk.g_beta, tipp.final_v2, tipp.v2_opening, point, v_eval)
+        && check_g1_opening(
+            vk,
+            vk.h_alpha,
+            tipp.final_w1,
+            tipp.w1_opening,
+            point,
+            w_eval,
+        )
+        && check_g1_opening(vk, vk.h_beta, tipp.final_w2, tipp.w2_opening, point, w_eval)
+}
+
+fn verify_mipp<E: Pairing>(
+    vk: &VerifierKey<E>,
+    transcript: &mut Transcript,
+    n: usize,
+    r: E::ScalarField,
+    proof: &AggregateProof<E>,
+) -> bool {
+    let mipp = &proof.mipp;
+    if 1 << mipp.rounds.len() != n {
+        return false;
+    }
+    let (mut t, mut u, mut z) = (proof.com_c.t, proof.com_c.u, proof.z_c.into_group());
+    let mut challenges = Vec::new();
+    for round in &mipp.rounds {
+        transcript.append(round);
+        let x: E::ScalarField = transcript.challenge();
+        let x_inv = x.inverse().expect("challenges are non-zero");
+        t += round.com_l.t * x + round.com_r.t * x_inv;
+        u += round.com_l.u * x + round.com_r.u * x_inv;
+        z += round.z_l * x + round.z_r * x_inv;
+        challenges.push(x_inv);
+    }
+    transcript.append(&mipp.final_c);
+    transcript.append(&[mipp.final_v1, mipp.final_v2]);
+    let point: E::ScalarField = transcript.challenge();
+
+    let final_b = evaluate_key_polynomial(&challenges, r);
+    let v_eval = evaluate_key_polynomial(&challenges, point);
+
+    z == mipp.final_c * final_b
+        && t == E::pairing(mipp.final_c, mipp.final_v1)
+        && u == E::pairing(mipp.final_c, mipp.final_v2)
+        && check_g2_opening(
+            vk,
+            vk.g_alpha,
+            mipp.final_v1,
+            mipp.v1_opening,
+            point,
+            v_eval,
+        )
+        && check_g2_opening(vk, vk.g_beta, mipp.final_v2, mipp.v2_opening, point, v_eval)
+}
+
+/// Checks that `commitment = h^{f(τ)}` with `f(point) = eval`, given
+/// `g_tau = g^τ` and `opening = h^{(f(τ) - eval) / (τ - point)}`.
+fn check_g2_opening<E: Pairing>(
+    vk: &VerifierKey<E>,
+    g_tau: E::G1Affine,
+    commitment: E::G2Affine,
+    opening: E::G2Affine,
+    point: E::ScalarField,
+    eval: E::ScalarField,
+) -> bool {
+    E::multi_pairing(
+        [vk.g, (vk.g * point - g_tau).into_affine()],
+        [(commitment - vk.h * eval).into_affine(), opening],
+    )
+    .is_zero()
+}
+
+/// Checks that `commitment = g^{f(τ)}` with `f(point) = eval`, given
+/// `h_tau = h^τ` and `opening = g^{(f(τ) - eval) / (τ - point)}`.
+fn check_g1_opening<E: Pairing>(
+    vk: &VerifierKey<E>,
+    h_tau: E::G2Affine,
+    commitment: E::G1Affine,
+    opening: E::G1Affine,
+    point: E::ScalarField,
+    eval: E::ScalarField,
+) -> bool {
+    E::multi_pairing(
+        [(commitment - vk.g * eval).into_affine(), opening],
+        [vk.h, (vk.h * point - h_tau).into_affine()],
+    )
+    .is_zero()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{Grid, SOLUTION};
+    use crate::snark::{Groth16Bls12_381, ProvingBackend};
+    use ark_bls12_381::Bls12_381;
+    use ark_groth16::Groth16;
+    use rand::prelude::*;
+
+    /// The puzzle left by blanking the cells set in `mask`.
+    fn puzzle(mask: u16) -> Grid {
+        let mut puzzle = SOLUTION;
+        for (i, cell) in puzzle.as_flattened_mut().iter_mut().enumerate() {
+            if mask & (1 << i) != 0 {
+                *cell = 0;
+            }
+        }
+        puzzle
+    }
+
+    /// Proves `n` puzzles. Past the first 16, the proofs are rerandomized
+    /// copies, which keeps the test fast but still gives distinct proofs.
+    fn prove_puzzles(
+        n: usize,
+        rng: &mut ThreadRng,
+    ) -> (VerifyingKey<Bls12_381>, Vec<Grid>, Vec<Proof<Bls12_381>>) {
+        let (pk, vk) = Groth16Bls12_381::setup::<4, _>(rng).unwrap();
+        let puzzles: Vec<_> = (0..n).map(|i| puzzle((i % 16) as u16 * 0x1f3)).collect();
+        let mut proofs: Vec<Proof<Bls12_381>> = Vec::new();
+        for (i, puzzle) in puzzles.iter().enumerate() {
+            let proof = match proofs.get(i % 16) {
+                Some(proof) => Groth16::<Bls12_381>::rerandomize_proof(&vk, proof, rng),
+                None => Groth16Bls12_381::prove(&pk, *puzzle, SOLUTION, rng).unwrap(),
+            };
+            proofs.push(proof);
+        }
+        (vk, puzzles, proofs)
+    }
+
+    fn check_aggregation(n: usize) {
+        let rng = &mut thread_rng();
+        let (groth16_vk, puzzles, proofs) = prove_puzzles(n, rng);
+        let (pk, vk) = setup::<Bls12_381, _>(n, rng);
+
+        let proof = aggregate(&pk, &groth16_vk, &puzzles, &proofs).unwrap();
+        assert_eq!(proof.tipp.rounds.len(), n.ilog2() as usize);
+        assert_eq!(proof.mipp.rounds.len(), n.ilog2() as usize);
+        assert!(verify_aggregate(&vk, &groth16_vk, &puzzles, &proof).unwrap());
+
+        let mut serialized = Vec::new();
+        proof.serialize_compressed(&mut serialized).unwrap();
+        let deserialized = AggregateProof::deserialize_compressed(&serialized[..]).unwrap();
+        assert_eq!(deserialized, proof);
+
+        let mut wrong_puzzles = puzzles.clone();
+        wrong_puzzles[n - 1] = puzzle(0xffff);
+        assert!(!verify_aggregate(&vk, &groth16_vk, &wrong_puzzles, &proof).unwrap());
+    }
+
+    #[test]
+    fn aggregates_2_proofs() {
+        check_aggregation(2);
+    }
+
+    #[test]
+    fn aggregates_16_proofs() {
+        check_aggregation(16);
+    }
+
+    #[test]
+    fn aggregates_128_proofs() {
+        check_aggregation(128);
+    }
+
+    #[test]
+    fn proof_for_another_puzzle_rejected() {
+        let rng = &mut thread_rng();
+        let (groth16_vk, puzzles, mut proofs) = prove_puzzles(4, rng);
+        let (pk, vk) = setup::<Bls12_381, _>(4, rng);
+
+        proofs[0] = proofs[3].clone();
+        let proof = aggregate(&pk, &groth16_vk, &puzzles, &proofs).unwrap();
+        assert!(!verify_aggregate(&vk, &groth16_vk, &puzzles, &proof).unwrap());
+    }
+
+    #[test]
+    fn aggregate_bound_to_keys() {
+        let rng = &mut thread_rng();
+        let (groth16_vk, puzzles, proofs) = prove_puzzles(4, rng);
+        let (pk, vk) = setup::<Bls12_381, _>(4, rng);
+        let proof = aggregate(&pk, &groth16_vk, &puzzles, &proofs).unwrap();
+        assert!(verify_aggregate(&vk, &groth16_vk, &puzzles, &proof).unwrap());
+
+        let (_, other_vk) = setup::<Bls12_381, _>(4, rng);
+        assert!(!verify_aggregate(&other_vk, &groth16_vk, &puzzles, &proof).unwrap());
+        let (_, other_groth16_vk) = Groth16Bls12_381::setup::<4, _>(rng).unwrap();
+        assert!(!verify_aggregate(&vk, &other_groth16_vk, &puzzles, &proof).unwrap());
+    }
+
+    #[test]
+    fn rejects_unsupported_counts() {
+        let rng = &mut thread_rng();
+        let (pk, _) = setup::<Bls12_381, _>(4, rng);
+        let (groth16_pk, groth16_vk) = Groth16Bls12_381::setup::<4, _>(rng).unwrap();
+        let proof = Groth16Bls12_381::prove(&groth16_pk, puzzle(0), SOLUTION, rng).unwrap();
+
+        let result = aggregate(&pk, &groth16_vk, &[puzzle(0); 3], &vec![proof.clone(); 3]);
+        assert!(matches!(result, Err(AggregationError::NotPowerOfTwo(3))));
+        let result = aggregate(&pk, &groth16_vk, &[puzzle(0); 8], &vec![proof.clone(); 8]);
+        assert!(matches!(
+            result,
+            Err(AggregationError::TooManyProofs { max: 4, found: 8 })
+        ));
+        let result = aggregate(&pk, &groth16_vk, &[puzzle(0); 2], &[proof]);
+        assert!(matches!(
+            result,
+            Err(AggregationError::LengthMismatch {
+                proofs: 1,
+                puzzles: 2
+            })
+        ));
+    }
+}
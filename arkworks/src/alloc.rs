@@ -2,13 +2,32 @@ use std::borrow::Borrow;
 
 use ark_ff::PrimeField;
 use ark_r1cs_std::{
-    prelude::{AllocVar, AllocationMode},
+    convert::ToConstraintFieldGadget,
+    fields::fp::FpVar,
+    prelude::{AllocVar, AllocationMode, EqGadget},
     uint8::UInt8,
 };
 use ark_relations::r1cs::{Namespace, SynthesisError};
 
-use crate::circuit::{Puzzle, Solution};
+use crate::circuit::{Puzzle, Solution, SudokuCircuit};
 
+fn new_cells<const N: usize, F: PrimeField>(
+    cs: Namespace<F>,
+    value: [[u8; N]; N],
+    mode: AllocationMode,
+) -> Result<[[UInt8<F>; N]; N], SynthesisError> {
+    let row = [(); N].map(|_| UInt8::constant(0));
+    let mut cells = [(); N].map(|_| row.clone());
+    for (i, row) in value.into_iter().enumerate() {
+        for (j, cell) in row.into_iter().enumerate() {
+            cells[i][j] = UInt8::new_variable(cs.clone(), || Ok(cell), mode)?;
+        }
+    }
+    Ok(cells)
+}
+
+/// A public puzzle's cells are witnesses, packed into as few inputs as they
+/// fit in (see [`SudokuCircuit::public_inputs`]).
 impl<const N: usize, F: PrimeField> AllocVar<[[u8; N]; N], F> for Puzzle<N, F> {
     fn new_variable<T: Borrow<[[u8; N]; N]>>(
         cs: impl Into<Namespace<F>>,
@@ -16,13 +35,20 @@ impl<const N: usize, F: PrimeField> AllocVar<[[u8; N]; N], F> for Puzzle<N, F> {
         mode: AllocationMode,
     ) -> Result<Self, SynthesisError> {
         let cs = cs.into();
-        let row = [(); N].map(|_| UInt8::constant(0));
-        let mut puzzle = Puzzle([(); N].map(|_| row.clone()));
         let value = f().map_or([[0; N]; N], |f| *f.borrow());
-        for (i, row) in value.into_iter().enumerate() {
-            for (j, cell) in row.into_iter().enumerate() {
-                puzzle.0[i][j] = UInt8::new_variable(cs.clone(), || Ok(cell), mode)?;
-            }
+        if mode != AllocationMode::Input {
+            return Ok(Puzzle(new_cells(cs, value, mode)?));
+        }
+        let puzzle = Puzzle(new_cells(cs.clone(), value, AllocationMode::Witness)?);
+        let packed = SudokuCircuit::public_inputs::<F>(&value);
+        for (chunk, packed) in puzzle
+            .0
+            .as_flattened()
+            .to_constraint_field()?
+            .iter()
+            .zip(packed)
+        {
+            FpVar::new_input(cs.clone(), || Ok(packed))?.enforce_equal(chunk)?;
         }
         Ok(puzzle)
     }
@@ -34,15 +60,7 @@ impl<const N: usize, F: PrimeField> AllocVar<[[u8; N]; N], F> for Solution<N, F>
         f: impl FnOnce() -> Result<T, SynthesisError>,
         mode: AllocationMode,
     ) -> Result<Self, SynthesisError> {
-        let cs = cs.into();
-        let row = [(); N].map(|_| UInt8::constant(0));
-        let mut solution = Solution([(); N].map(|_| row.clone()));
         let value = f().map_or([[0; N]; N], |f| *f.borrow());
-        for (i, row) in value.into_iter().enumerate() {
-            for (j, cell) in row.into_iter().enumerate() {
-                solution.0[i][j] = UInt8::new_variable(cs.clone(), || Ok(cell), mode)?;
-            }
-        }
-        Ok(solution)
+        Ok(Solution(new_cells(cs.into(), value, mode)?))
     }
 }
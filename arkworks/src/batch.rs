@@ -0,0 +1,172 @@
+//! Batch verification of Groth16 proofs of [`SudokuCircuit`] under one
+//! verifying key.
+//!
+//! Every proof's check `e(A_i, B_i) = e(α, β) · e(S_i, γ) · e(C_i, δ)` is
+//! weighted by a random `ρ_i` and the weighted checks are multiplied
+//! together, so `n` proofs cost `n + 3` Miller loops and one final
+//! exponentiation instead of `3n` Miller loops and `n` final
+//! exponentiations. The weights are derived from the verifying key and all
+//! puzzles and proofs by Fiat-Shamir, so a prover cannot pick invalid proofs
+//! whose errors cancel.
+//! When the combined check fails, each proof is checked on its own to find
+//! the invalid ones.
+
+use ark_ec::pairing::Pairing;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::Zero;
+use ark_groth16::{prepare_verifying_key, Groth16, Proof, VerifyingKey};
+use ark_relations::r1cs::SynthesisError;
+
+use crate::circuit::SudokuCircuit;
+use crate::transcript::Transcript;
+
+#[derive(Debug)]
+pub enum BatchError {
+    Synthesis(SynthesisError),
+    /// The indices of the proofs that failed to verify.
+    Rejected(Vec<usize>),
+}
+
+impl std::fmt::Display for BatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BatchError::Synthesis(err) => write!(f, "Synthesis error: {err}"),
+            BatchError::Rejected(indices) => write!(f, "Proofs {indices:?} rejected"),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BatchError::Synthesis(err) => Some(err),
+            BatchError::Rejected(_) => None,
+        }
+    }
+}
+
+impl From<SynthesisError> for BatchError {
+    fn from(err: SynthesisError) -> Self {
+        BatchError::Synthesis(err)
+    }
+}
+
+/// Checks every `(puzzle, proof)` pair against `vk` at once.
+pub fn verify_batch<E: Pairing, const N: usize>(
+    vk: &VerifyingKey<E>,
+    proofs: &[([[u8; N]; N], Proof<E>)],
+) -> Result<(), BatchError> {
+    if vk.gamma_abc_g1.len() != SudokuCircuit::<N>::num_inputs::<E::ScalarField>() + 1 {
+        return Err(SynthesisError::MalformedVerifyingKey.into());
+    }
+
+    let weights = weights(vk, proofs);
+
+    // ∏ e(ρ_i · A_i, B_i) = e(∑ ρ_i · α, β) · e(∑ ρ_i · S_i, γ) · e(∑ ρ_i · C_i, δ)
+    let mut input_scalars = vec![E::ScalarField::zero(); vk.gamma_abc_g1.len()];
+    input_scalars[0] = weights.iter().sum();
+    for ((puzzle, _), weight) in proofs.iter().zip(&weights) {
+        let inputs = SudokuCircuit::public_inputs::<E::ScalarField>(puzzle);
+        for (scalar, input) in input_scalars[1..].iter_mut().zip(inputs) {
+            *scalar += input * weight;
+        }
+    }
+    let c: Vec<_> = proofs.iter().map(|(_, proof)| proof.c).collect();
+    let mut g1: Vec<_> = proofs
+        .iter()
+        .zip(&weights)
+        .map(|((_, proof), weight)| proof.a * weight)
+        .collect();
+    g1.push(-(vk.alpha_g1 * input_scalars[0]));
+    g1.push(-E::G1::msm_unchecked(&vk.gamma_abc_g1, &input_scalars));
+    g1.push(-E::G1::msm_unchecked(&c, &weights));
+    let g2 = proofs
+        .iter()
+        .map(|(_, proof)| proof.b)
+        .chain([vk.beta_g2, vk.gamma_g2, vk.delta_g2]);
+    if E::multi_pairing(E::G1::normalize_batch(&g1), g2).is_zero() {
+        return Ok(());
+    }
+
+    let pvk = prepare_verifying_key(vk);
+    let mut rejected = Vec::new();
+    for (i, (puzzle, proof)) in proofs.iter().enumerate() {
+        let inputs = SudokuCircuit::public_inputs::<E::ScalarField>(puzzle);
+        if !Groth16::<E>::verify_proof(&pvk, proof, &inputs)? {
+            rejected.push(i);
+        }
+    }
+    if rejected.is_empty() {
+        Ok(())
+    } else {
+        Err(BatchError::Rejected(rejected))
+    }
+}
+
+/// The Fiat-Shamir weights `ρ_i` of `proofs` under `vk`.
+fn weights<E: Pairing, const N: usize>(
+    vk: &VerifyingKey<E>,
+    proofs: &[([[u8; N]; N], Proof<E>)],
+) -> Vec<E::ScalarField> {
+    let puzzles: Vec<_> = proofs.iter().map(|(puzzle, _)| *puzzle).collect();
+    let mut transcript = Transcript::new(b"sudoku-snark/batch", &puzzles);
+    transcript.append(vk);
+    for (_, proof) in proofs {
+        transcript.append(proof);
+    }
+    proofs.iter().map(|_| transcript.challenge()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SOLUTION;
+    use crate::snark::{Groth16Bls12_381, ProvingBackend};
+    use ark_bls12_381::Bls12_381;
+    use rand::prelude::*;
+
+    fn puzzle(blank: usize) -> [[u8; 4]; 4] {
+        let mut puzzle = SOLUTION;
+        puzzle[blank / 4][blank % 4] = 0;
+        puzzle
+    }
+
+    #[test]
+    fn batch_verifies_and_reports_failures() {
+        let rng = &mut thread_rng();
+        let (pk, vk) = Groth16Bls12_381::setup::<4, _>(rng).unwrap();
+        let mut batch: Vec<_> = (0..8)
+            .map(|i| {
+                let proof = Groth16Bls12_381::prove(&pk, puzzle(i), SOLUTION, rng).unwrap();
+                (puzzle(i), proof)
+            })
+            .collect();
+        verify_batch(&vk, &batch).unwrap();
+        verify_batch::<Bls12_381, 4>(&vk, &[]).unwrap();
+
+        batch[2].0 = puzzle(10);
+        batch[5].1 = batch[6].1.clone();
+        assert!(matches!(
+            verify_batch(&vk, &batch),
+            Err(BatchError::Rejected(indices)) if indices == [2, 5]
+        ));
+
+        assert!(matches!(
+            verify_batch::<_, 9>(&vk, &[]),
+            Err(BatchError::Synthesis(SynthesisError::MalformedVerifyingKey))
+        ));
+    }
+
+    #[test]
+    fn weights_bound_to_key() {
+        let rng = &mut thread_rng();
+        let (pk, vk) = Groth16Bls12_381::setup::<4, _>(rng).unwrap();
+        let (_, other_vk) = Groth16Bls12_381::setup::<4, _>(rng).unwrap();
+        let batch = [(
+            puzzle(0),
+            Groth16Bls12_381::prove(&pk, puzzle(0), SOLUTION, rng).unwrap(),
+        )];
+        assert_eq!(weights(&vk, &batch), weights(&vk, &batch));
+        assert_ne!(weights(&vk, &batch), weights(&other_vk, &batch));
+    }
+}
@@ -0,0 +1,372 @@
+//! Phase-2 MPC ceremony for the Groth16 keys of [`SudokuCircuit`], after
+//! Bowe, Gabizon, Miers (<https://eprint.iacr.org/2017/1050>).
+//!
+//! Whoever knows the secrets behind Groth16 keys can forge proofs. The
+//! circuit-specific one is `δ`, which appears in `[δ]₁` and `[δ]₂` and
+//! divides the `H` and `L` queries. Each participant multiplies `δ` by a
+//! secret `δ'` of their own and divides the queries by it, so the final `δ`
+//! is unknown as long as one participant discarded theirs.
+//!
+//! A [`Contribution`] proves knowledge of `δ'` with a random `s` and `s·δ'`
+//! in G1 and `r·δ'` in G2, where `r` is hashed from the transcript so far and
+//! `s`, `s·δ'`. [`Parameters::verify`] replays the chain of transcript
+//! hashes, checks these ratios, and checks that the final keys are the
+//! initial ones with only `δ` changed.
+//!
+//! The other secrets (`τ`, `α`, `β`) belong to phase 1, which
+//! [`Parameters::new`] still samples in one place. The keys are only as
+//! trustworthy as that phase 1; [`Parameters::from_powers_of_tau`] takes it
+//! from a powers-of-tau ceremony instead.
+
+use crate::circuit::SudokuCircuit;
+use crate::ptau::{PowersOfTau, PtauError};
+use ark_bn254::Bn254;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_groth16::{Groth16, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_snark::SNARK;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha3::{Digest, Keccak256};
+
+const LABEL: &[u8] = b"sudoku-snark/ceremony";
+
+/// One participant's update of `δ` and their proof of knowing it.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Contribution<E: Pairing> {
+    /// `[δ]₁` after this contribution.
+    delta_after: E::G1Affine,
+    s: E::G1Affine,
+    s_delta: E::G1Affine,
+    r_delta: E::G2Affine,
+}
+
+/// The proving key as it passes from participant to participant.
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Parameters<E: Pairing> {
+    pk: ProvingKey<E>,
+    /// The hash of the proving key before any contribution.
+    initial_hash: [u8; 32],
+    contributions: Vec<Contribution<E>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CeremonyError {
+    /// The parameters did not start from the given initial parameters.
+    WrongInitialParameters,
+    /// Parts of the keys other than `δ` were changed.
+    ChangedParameters,
+    /// The contribution at this index does not prove knowledge of its `δ'`.
+    InvalidContribution(usize),
+    /// `[δ]₁` or `[δ]₂` does not match the contributions.
+    InconsistentDelta,
+    /// The `H` or `L` query was not divided by the contributed `δ'`s.
+    InconsistentQueries,
+}
+
+impl std::fmt::Display for CeremonyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CeremonyError::WrongInitialParameters => {
+                write!(f, "Parameters do not start from the initial parameters")
+            }
+            CeremonyError::ChangedParameters => {
+                write!(f, "Parameters other than delta were changed")
+            }
+            CeremonyError::InvalidContribution(i) => write!(f, "Contribution {i} is invalid"),
+            CeremonyError::InconsistentDelta => {
+                write!(f, "Delta does not match the contributions")
+            }
+            CeremonyError::InconsistentQueries => {
+                write!(f, "H and L queries do not match delta")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CeremonyError {}
+
+impl<E: Pairing> Parameters<E> {
+    /// Initial parameters for `N`x`N` boards, before any contribution.
+    pub fn new<const N: usize, R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> Result<Self, SynthesisError> {
+        let (pk, _) = Groth16::<E>::circuit_specific_setup(SudokuCircuit::<N>::default(), rng)?;
+        Ok(Parameters {
+            initial_hash: hash(&pk),
+            pk,
+            contributions: Vec::new(),
+        })
+    }
+
+    /// Multiplies `δ` by a fresh secret, returning the transcript hash after
+    /// this contribution for the participant to publish.
+    pub fn contribute<R: RngCore + CryptoRng>(&mut self, rng: &mut R) -> [u8; 32] {
+        let delta = loop {
+            let delta = E::ScalarField::rand(rng);
+            if !delta.is_zero() {
+                break delta;
+            }
+        };
+        let delta_inverse = delta.inverse().expect("delta is non-zero");
+
+        let s = E::G1::rand(rng).into_affine();
+        let s_delta = (s * delta).into_affine();
+        let r = hash_to_g2::<E>(&self.transcript_hash(), &s, &s_delta);
+
+        self.pk.delta_g1 = (self.pk.delta_g1 * delta).into_affine();
+        self.pk.vk.delta_g2 = (self.pk.vk.delta_g2 * delta).into_affine();
+        for query in [&mut self.pk.h_query, &mut self.pk.l_query] {
+            let divided: Vec<_> = query.iter().map(|p| *p * delta_inverse).collect();
+            *query = E::G1::normalize_batch(&divided);
+        }
+
+        self.contributions.push(Contribution {
+            delta_after: self.pk.delta_g1,
+            s,
+            s_delta,
+            r_delta: (r * delta).into_affine(),
+        });
+        self.transcript_hash()
+    }
+
+    /// The hash of the initial parameters chained with every contribution.
+    pub fn transcript_hash(&self) -> [u8; 32] {
+        self.contributions
+            .iter()
+            .fold(self.initial_hash, |hash, contribution| {
+                chain(&hash, contribution)
+            })
+    }
+
+    /// Checks that these parameters grew from `initial` by valid
+    /// contributions, returning the transcript hash after each of them so
+    /// participants can find their own.
+    pub fn verify(&self, initial: &Self) -> Result<Vec<[u8; 32]>, CeremonyError> {
+        let (pk, initial_pk) = (&self.pk, &initial.pk);
+        if !initial.contributions.is_empty() || self.initial_hash != hash(initial_pk) {
+            return Err(CeremonyError::WrongInitialParameters);
+        }
+        if pk.vk.alpha_g1 != initial_pk.vk.alpha_g1
+            || pk.vk.beta_g2 != initial_pk.vk.beta_g2
+            || pk.vk.gamma_g2 != initial_pk.vk.gamma_g2
+            || pk.vk.gamma_abc_g1 != initial_pk.vk.gamma_abc_g1
+            || pk.beta_g1 != initial_pk.beta_g1
+            || pk.a_query != initial_pk.a_query
+            || pk.b_g1_query != initial_pk.b_g1_query
+            || pk.b_g2_query != initial_pk.b_g2_query
+            || pk.h_query.len() != initial_pk.h_query.len()
+            || pk.l_query.len() != initial_pk.l_query.len()
+        {
+            return Err(CeremonyError::ChangedParameters);
+        }
+
+        let mut hashes = Vec::with_capacity(self.contributions.len());
+        let mut transcript_hash = self.initial_hash;
+        let mut delta = initial_pk.delta_g1;
+        for (i, contribution) in self.contributions.iter().enumerate() {
+            let r = hash_to_g2::<E>(&transcript_hash, &contribution.s, &contribution.s_delta);
+            if contribution.s.is_zero()
+                || contribution.delta_after.is_zero()
+                || !same_ratio::<E>(
+                    (contribution.s, contribution.s_delta),
+                    (r, contribution.r_delta),
+                )
+                || !same_ratio::<E>((delta, contribution.delta_after), (r, contribution.r_delta))
+            {
+                return Err(CeremonyError::InvalidContribution(i));
+            }
+            transcript_hash = chain(&transcript_hash, contribution);
+            hashes.push(transcript_hash);
+            delta = contribution.delta_after;
+        }
+
+        // The setup samples its own generators, so `[δ]₂` is checked against
+        // the initial `δ` rather than the curve's generators.
+        if pk.delta_g1 != delta
+            || !same_ratio::<E>(
+                (initial_pk.delta_g1, pk.delta_g1),
+                (initial_pk.vk.delta_g2, pk.vk.delta_g2),
+            )
+        {
+            return Err(CeremonyError::InconsistentDelta);
+        }
+
+        // A random combination of each query must have been divided by what
+        // `δ` was multiplied with.
+        let mut rng = ChaCha20Rng::from_seed(hash(self));
+        for (query, initial_query) in [
+            (&pk.h_query, &initial_pk.h_query),
+            (&pk.l_query, &initial_pk.l_query),
+        ] {
+            let weights: Vec<E::ScalarField> = (0..query.len())
+                .map(|_| E::ScalarField::rand(&mut rng))
+                .collect();
+            let combined = E::G1::msm_unchecked(query, &weights).into_affine();
+            let initial_combined = E::G1::msm_unchecked(initial_query, &weights).into_affine();
+            if !same_ratio::<E>(
+                (combined, initial_combined),
+                (initial_pk.vk.delta_g2, pk.vk.delta_g2),
+            ) {
+                return Err(CeremonyError::InconsistentQueries);
+            }
+        }
+        Ok(hashes)
+    }
+
+    /// The keys the ceremony produced, to be used once [`Parameters::verify`]
+    /// accepted them.
+    pub fn into_keys(self) -> (ProvingKey<E>, VerifyingKey<E>) {
+        let vk = self.pk.vk.clone();
+        (self.pk, vk)
+    }
+}
+
+impl Parameters<Bn254> {
+    /// Initial parameters for `N`x`N` boards from the phase-1 SRS in `srs`,
+    /// with `δ = 1` until the first contribution.
+    pub fn from_powers_of_tau<const N: usize>(srs: &PowersOfTau) -> Result<Self, PtauError> {
+        let pk = srs.proving_key::<N>()?;
+        Ok(Parameters {
+            initial_hash: hash(&pk),
+            pk,
+            contributions: Vec::new(),
+        })
+    }
+}
+
+/// Whether `g1.1 = g1.0 · x` and `g2.1 = g2.0 · x` for the same `x`.
+fn same_ratio<E: Pairing>(g1: (E::G1Affine, E::G1Affine), g2: (E::G2Affine, E::G2Affine)) -> bool {
+    E::pairing(g1.0, g2.1) == E::pairing(g1.1, g2.0)
+}
+
+fn hash(value: &impl CanonicalSerialize) -> [u8; 32] {
+    let mut bytes = Vec::with_capacity(value.uncompressed_size());
+    value
+        .serialize_uncompressed(&mut bytes)
+        .expect("writing to a Vec cannot fail");
+    Keccak256::digest(bytes).into()
+}
+
+fn chain<E: Pairing>(transcript_hash: &[u8; 32], contribution: &Contribution<E>) -> [u8; 32] {
+    hash(&(*transcript_hash, contribution.clone()))
+}
+
+/// A point in G2 nobody knows the discrete logarithm of, derived from the
+/// transcript hash and the contributor's `s` and `s·δ'`.
+fn hash_to_g2<E: Pairing>(
+    transcript_hash: &[u8; 32],
+    s: &E::G1Affine,
+    s_delta: &E::G1Affine,
+) -> E::G2Affine {
+    let seed = hash(&(LABEL.to_vec(), *transcript_hash, *s, *s_delta));
+    let len = E::G2Affine::generator().compressed_size();
+    for counter in 0u64.. {
+        let bytes: Vec<u8> = (0u8..)
+            .flat_map(|block| {
+                Keccak256::new()
+                    .chain_update(seed)
+                    .chain_update(counter.to_le_bytes())
+                    .chain_update([block])
+                    .finalize()
+            })
+            .take(len)
+            .collect();
+        if let Some(point) = E::G2Affine::from_random_bytes(&bytes) {
+            let point = point.clear_cofactor();
+            if !point.is_zero() {
+                return point;
+            }
+        }
+    }
+    unreachable!("some counter hashes to a point")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{prove, verifies, PUZZLE, SOLUTION};
+    use ark_bls12_381::{Bls12_381, Fr};
+    use rand::prelude::*;
+
+    type G1 = <Bls12_381 as Pairing>::G1Affine;
+    type G2 = <Bls12_381 as Pairing>::G2Affine;
+
+    #[test]
+    fn ceremony_produces_working_keys() {
+        let rng = &mut thread_rng();
+        let initial = Parameters::<Bls12_381>::new::<4, _>(rng).unwrap();
+
+        // Every participant gets the parameters from the last one, checks
+        // them and publishes the hash of their own contribution.
+        let mut parameters = initial.clone();
+        let mut published = Vec::new();
+        for _ in 0..3 {
+            parameters.verify(&initial).unwrap();
+            let mut bytes = Vec::new();
+            parameters.serialize_compressed(&mut bytes).unwrap();
+            parameters = Parameters::deserialize_compressed(&*bytes).unwrap();
+            published.push(parameters.contribute(rng));
+        }
+        assert_eq!(parameters.verify(&initial).unwrap(), published);
+        assert_eq!(parameters.transcript_hash(), published[2]);
+
+        let (pk, vk) = parameters.into_keys();
+        assert_ne!(vk.delta_g2, initial.pk.vk.delta_g2);
+        let circuit = SudokuCircuit {
+            puzzle: Some(PUZZLE),
+            solution: Some(SOLUTION),
+        };
+        let proof = prove(&pk, circuit);
+        let inputs = SudokuCircuit::public_inputs::<Fr>(&PUZZLE);
+        assert!(verifies(&vk, &inputs, &proof));
+    }
+
+    #[test]
+    fn tampering_is_detected() {
+        let rng = &mut thread_rng();
+        let initial = Parameters::<Bls12_381>::new::<4, _>(rng).unwrap();
+        let mut parameters = initial.clone();
+        parameters.contribute(rng);
+        parameters.contribute(rng);
+
+        let other = Parameters::<Bls12_381>::new::<4, _>(rng).unwrap();
+        assert_eq!(
+            parameters.verify(&other),
+            Err(CeremonyError::WrongInitialParameters)
+        );
+
+        let mut forged = parameters.clone();
+        forged.contributions[0].r_delta = G2::generator();
+        assert_eq!(
+            forged.verify(&initial),
+            Err(CeremonyError::InvalidContribution(0))
+        );
+
+        // Swapping in a known `δ` without a contribution proving it.
+        let mut forged = parameters.clone();
+        forged.pk.delta_g1 = G1::generator();
+        forged.pk.vk.delta_g2 = G2::generator();
+        assert_eq!(
+            forged.verify(&initial),
+            Err(CeremonyError::InconsistentDelta)
+        );
+
+        let mut forged = parameters.clone();
+        forged.pk.l_query[0] = (forged.pk.l_query[0] * Fr::from(2)).into_affine();
+        assert_eq!(
+            forged.verify(&initial),
+            Err(CeremonyError::InconsistentQueries)
+        );
+
+        let mut forged = parameters;
+        forged.pk.vk.alpha_g1 = G1::generator();
+        assert_eq!(
+            forged.verify(&initial),
+            Err(CeremonyError::ChangedParameters)
+        );
+    }
+}
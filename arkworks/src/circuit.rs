@@ -1,55 +1,216 @@
 use crate::cmp::CmpGadget;
-use ark_ff::PrimeField;
+use ark_ff::{PrimeField, ToConstraintField};
 use ark_r1cs_std::{
-    prelude::{AllocVar, Boolean, EqGadget},
+    prelude::{AllocVar, AllocationMode, Boolean, EqGadget},
     uint8::UInt8,
+    R1CSVar,
+};
+use ark_relations::ns;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError,
 };
-use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
 
 pub struct Puzzle<const N: usize, ConstraintF: PrimeField>(pub [[UInt8<ConstraintF>; N]; N]);
 pub struct Solution<const N: usize, ConstraintF: PrimeField>(pub [[UInt8<ConstraintF>; N]; N]);
 
-fn no_duplicates<'a, T, ConstraintF: PrimeField>(cells: T) -> Result<(), SynthesisError>
+/// The check of [`SudokuCircuit`] a constraint belongs to, in Sudoku
+/// coordinates (row and column, counting from 0).
+///
+/// Namespace names are static, so the constraint traces of `ns!` only say
+/// which kind of check a constraint is part of. [`SudokuCircuit::first_unsatisfied`]
+/// maps a constraint to its `Location` instead, which displays as the path
+/// the namespaces would have, e.g. `row_3/cell_2_vs_5` or
+/// `box_(3,6)/cell_(3,7)_vs_(4,6)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    /// The puzzle cell at `(row, col)` is blank or a digit.
+    GivenRange { row: usize, col: usize },
+    /// The solution cell at `(row, col)` is a digit.
+    Range { row: usize, col: usize },
+    /// The solution cell at `(row, col)` matches the puzzle's given.
+    Given { row: usize, col: usize },
+    /// Columns `cols` of `row` differ.
+    Row { row: usize, cols: (usize, usize) },
+    /// Rows `rows` of `col` differ.
+    Col { col: usize, rows: (usize, usize) },
+    /// Cells `cells` of the subgrid with top-left cell `corner` differ.
+    Box {
+        corner: (usize, usize),
+        cells: ((usize, usize), (usize, usize)),
+    },
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Location::GivenRange { row, col } => write!(f, "given_range/cell_({row},{col})"),
+            Location::Range { row, col } => write!(f, "range/cell_({row},{col})"),
+            Location::Given { row, col } => write!(f, "given/cell_({row},{col})"),
+            Location::Row { row, cols } => write!(f, "row_{row}/cell_{}_vs_{}", cols.0, cols.1),
+            Location::Col { col, rows } => write!(f, "col_{col}/cell_{}_vs_{}", rows.0, rows.1),
+            Location::Box { corner, cells } => write!(
+                f,
+                "box_({},{})/cell_({},{})_vs_({},{})",
+                corner.0, corner.1, cells.0 .0, cells.0 .1, cells.1 .0, cells.1 .1
+            ),
+        }
+    }
+}
+
+/// Enforces that the `cells` are pairwise distinct, calling `mark` with the
+/// positions of each pair before its constraints. With `blanks`, any number
+/// of cells may be 0.
+fn no_duplicates<'a, P, T, ConstraintF: PrimeField>(
+    cells: T,
+    blanks: bool,
+    mark: &mut dyn FnMut(P, P),
+) -> Result<(), SynthesisError>
 where
-    T: Iterator<Item = &'a UInt8<ConstraintF>> + Clone,
+    P: Copy,
+    T: Iterator<Item = (P, &'a UInt8<ConstraintF>)> + Clone,
 {
     //// cloning an iterator just copies internal state, not the elements
-    for (i, cell) in cells.clone().enumerate() {
-        for prior_cell in cells.clone().take(i) {
-            cell.is_neq(prior_cell)?.enforce_equal(&Boolean::TRUE)?;
+    for (i, (position, cell)) in cells.clone().enumerate() {
+        let is_blank = if blanks {
+            cell.is_eq(&UInt8::constant(0))?
+        } else {
+            Boolean::FALSE
+        };
+        for (prior_position, prior_cell) in cells.clone().take(i) {
+            let _ns = ns!(cell.cs(), "cell_vs");
+            mark(prior_position, position);
+            (cell.is_neq(prior_cell)? | &is_blank).enforce_equal(&Boolean::TRUE)?;
         }
     }
     Ok(())
 }
 
-impl<const N: usize, ConstraintF: PrimeField> Solution<N, ConstraintF> {
-    fn check_rows(&self) -> Result<(), SynthesisError> {
-        for row in &self.0 {
-            no_duplicates(row.iter())?;
+/// Enforces that no digit repeats in a row of `board`.
+fn check_rows<const N: usize, ConstraintF: PrimeField>(
+    board: &[[UInt8<ConstraintF>; N]; N],
+    blanks: bool,
+    mark: &mut dyn FnMut(Location),
+) -> Result<(), SynthesisError> {
+    for (row, cells) in board.iter().enumerate() {
+        let _ns = ns!(board.cs(), "row");
+        no_duplicates(cells.iter().enumerate(), blanks, &mut |a, b| {
+            mark(Location::Row { row, cols: (a, b) })
+        })?;
+    }
+    Ok(())
+}
+
+/// Enforces that no digit repeats in a column of `board`.
+fn check_cols<const N: usize, ConstraintF: PrimeField>(
+    board: &[[UInt8<ConstraintF>; N]; N],
+    blanks: bool,
+    mark: &mut dyn FnMut(Location),
+) -> Result<(), SynthesisError> {
+    for col in 0..N {
+        let _ns = ns!(board.cs(), "col");
+        no_duplicates(
+            board.iter().map(|cells| &cells[col]).enumerate(),
+            blanks,
+            &mut |a, b| mark(Location::Col { col, rows: (a, b) }),
+        )?;
+    }
+    Ok(())
+}
+
+/// Enforces that no digit repeats in a subgrid of `board`.
+fn check_subgrids<const N: usize, ConstraintF: PrimeField>(
+    board: &[[UInt8<ConstraintF>; N]; N],
+    blanks: bool,
+    mark: &mut dyn FnMut(Location),
+) -> Result<(), SynthesisError> {
+    // The subgrids of an `N`x`N` board are `B`x`B` with `N = B * B`. Boards
+    // whose size isn't a perfect square (like Noir's 2x2) have none.
+    let b = N.isqrt();
+    if b * b != N {
+        return Ok(());
+    }
+    for i in (0..N).step_by(b) {
+        for j in (0..N).step_by(b) {
+            let _ns = ns!(board.cs(), "box");
+            let subgrid = (i..(i + b))
+                .flat_map(|row| (j..(j + b)).map(move |col| (row, col)))
+                .map(|(row, col)| ((row, col), &board[row][col]));
+            no_duplicates(subgrid, blanks, &mut |a, b| {
+                mark(Location::Box {
+                    corner: (i, j),
+                    cells: (a, b),
+                })
+            })?;
         }
-        Ok(())
     }
+    Ok(())
+}
 
-    fn check_cols(&self) -> Result<(), SynthesisError> {
-        for col in (0..N).map(|idx| self.0.iter().map(move |row| &row[idx])) {
-            no_duplicates(col)?;
+impl<const N: usize, ConstraintF: PrimeField> Solution<N, ConstraintF> {
+    /// Ensures that every cell is in the range [1, N].
+    pub(crate) fn check_range(&self, mark: &mut dyn FnMut(Location)) -> Result<(), SynthesisError> {
+        let _ns = ns!(self.0.cs(), "range");
+        for (row, cells) in self.0.iter().enumerate() {
+            for (col, s) in cells.iter().enumerate() {
+                mark(Location::Range { row, col });
+                (s.is_leq(&UInt8::constant(N as u8))? & (&s.is_geq(&UInt8::constant(1))?))
+                    .enforce_equal(&Boolean::TRUE)?;
+            }
         }
         Ok(())
     }
 
-    fn check_subgrids(&self) -> Result<(), SynthesisError> {
-        for i in 0..N {
-            for j in 0..N {
-                if i % 3 == 0 && j % 3 == 0 {
-                    let subgrid = self.0[i..(i + 3)].iter().flat_map(|row| &row[j..(j + 3)]);
-                    no_duplicates(subgrid)?;
-                }
+    pub(crate) fn check_rows(&self, mark: &mut dyn FnMut(Location)) -> Result<(), SynthesisError> {
+        check_rows(&self.0, false, mark)
+    }
+
+    pub(crate) fn check_cols(&self, mark: &mut dyn FnMut(Location)) -> Result<(), SynthesisError> {
+        check_cols(&self.0, false, mark)
+    }
+
+    pub(crate) fn check_subgrids(
+        &self,
+        mark: &mut dyn FnMut(Location),
+    ) -> Result<(), SynthesisError> {
+        check_subgrids(&self.0, false, mark)
+    }
+}
+
+impl<const N: usize, ConstraintF: PrimeField> Puzzle<N, ConstraintF> {
+    /// Ensures that every cell is in the range [0, N], 0 being a blank.
+    pub(crate) fn check_range(&self, mark: &mut dyn FnMut(Location)) -> Result<(), SynthesisError> {
+        let _ns = ns!(self.0.cs(), "given_range");
+        for (row, cells) in self.0.iter().enumerate() {
+            for (col, p) in cells.iter().enumerate() {
+                mark(Location::GivenRange { row, col });
+                p.is_leq(&UInt8::constant(N as u8))?
+                    .enforce_equal(&Boolean::TRUE)?;
             }
         }
         Ok(())
     }
+
+    /// Ensures that no digit repeats in a row, column or subgrid, so the
+    /// filled cells could be part of a solution. Blanks may repeat.
+    pub(crate) fn check_consistent(
+        &self,
+        mark: &mut dyn FnMut(Location),
+    ) -> Result<(), SynthesisError> {
+        check_rows(&self.0, true, mark)?;
+        check_cols(&self.0, true, mark)?;
+        check_subgrids(&self.0, true, mark)
+    }
+}
+
+/// How many cells [`SudokuCircuit::public_inputs`] packs into one element of
+/// `ConstraintF`: as many bytes as fit below its modulus.
+pub fn cells_per_input<ConstraintF: PrimeField>() -> usize {
+    ((ConstraintF::MODULUS_BIT_SIZE - 1) / 8) as usize
 }
 
+/// A no-op `mark` for the checks, when nobody asks for [`Location`]s.
+pub(crate) fn unmarked(_: Location) {}
+
 #[derive(Default)]
 pub struct SudokuCircuit<const N: usize> {
     // The puzzle is public
@@ -59,16 +220,76 @@ pub struct SudokuCircuit<const N: usize> {
 }
 
 impl<const N: usize> SudokuCircuit<N> {
-    fn check_puzzle_matches_solution<ConstraintF: PrimeField>(
+    /// The public inputs a verifier passes alongside a proof for `puzzle`.
+    ///
+    /// The cells are packed in row-major order as little-endian bytes into
+    /// as few field elements as they fit in, [`cells_per_input`] to an
+    /// element: 3 for a 9x9 puzzle over a 255-bit field.
+    pub fn public_inputs<ConstraintF: PrimeField>(puzzle: &[[u8; N]; N]) -> Vec<ConstraintF> {
+        puzzle
+            .as_flattened()
+            .to_field_elements()
+            .expect("chunks of bytes always fit in the field")
+    }
+
+    /// The number of [`SudokuCircuit::public_inputs`] of a puzzle.
+    pub fn num_inputs<ConstraintF: PrimeField>() -> usize {
+        (N * N).div_ceil(cells_per_input::<ConstraintF>())
+    }
+
+    /// Enforces that `solution` is a valid solution to `puzzle`.
+    pub(crate) fn enforce_solves<ConstraintF: PrimeField>(
         puzzle: &Puzzle<N, ConstraintF>,
         solution: &Solution<N, ConstraintF>,
     ) -> Result<(), SynthesisError> {
-        for (p_row, s_row) in puzzle.0.iter().zip(&solution.0) {
-            for (p, s) in p_row.iter().zip(s_row) {
-                // Ensure that the solution `s` is in the range [1, N]
-                (s.is_leq(&UInt8::constant(N as u8))? & (&s.is_geq(&UInt8::constant(1))?))
-                    .enforce_equal(&Boolean::TRUE)?;
+        Self::enforce_solves_marked(puzzle, solution, &mut unmarked)
+    }
+
+    /// Allocates `puzzle` in `mode` and `solution` as a witness, and
+    /// [enforces](SudokuCircuit::enforce_solves) that the one solves the
+    /// other.
+    pub(crate) fn new_solved<ConstraintF: PrimeField>(
+        cs: ConstraintSystemRef<ConstraintF>,
+        puzzle: Option<[[u8; N]; N]>,
+        mode: AllocationMode,
+        solution: Option<[[u8; N]; N]>,
+    ) -> Result<(Puzzle<N, ConstraintF>, Solution<N, ConstraintF>), SynthesisError> {
+        let puzzle = Puzzle::new_variable(
+            ns!(cs, "puzzle"),
+            || puzzle.ok_or(SynthesisError::AssignmentMissing),
+            mode,
+        )?;
+        let solution = Solution::new_witness(ns!(cs, "solution"), || {
+            solution.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        Self::enforce_solves(&puzzle, &solution)?;
+        Ok((puzzle, solution))
+    }
 
+    /// [`SudokuCircuit::enforce_solves`], calling `mark` with the
+    /// [`Location`] of the constraints that follow.
+    fn enforce_solves_marked<ConstraintF: PrimeField>(
+        puzzle: &Puzzle<N, ConstraintF>,
+        solution: &Solution<N, ConstraintF>,
+        mark: &mut dyn FnMut(Location),
+    ) -> Result<(), SynthesisError> {
+        puzzle.check_range(mark)?;
+        solution.check_range(mark)?;
+        Self::check_puzzle_matches_solution(puzzle, solution, mark)?;
+        solution.check_rows(mark)?;
+        solution.check_cols(mark)?;
+        solution.check_subgrids(mark)
+    }
+
+    fn check_puzzle_matches_solution<ConstraintF: PrimeField>(
+        puzzle: &Puzzle<N, ConstraintF>,
+        solution: &Solution<N, ConstraintF>,
+        mark: &mut dyn FnMut(Location),
+    ) -> Result<(), SynthesisError> {
+        let _ns = ns!(solution.0.cs(), "given");
+        for (row, (p_row, s_row)) in puzzle.0.iter().zip(&solution.0).enumerate() {
+            for (col, (p, s)) in p_row.iter().zip(s_row).enumerate() {
+                mark(Location::Given { row, col });
                 // Ensure that either the puzzle slot is 0, or that
                 // the slot matches equivalent slot in the solution
                 (p.is_eq(s)? | (&p.is_eq(&UInt8::constant(0))?)).enforce_equal(&Boolean::TRUE)?;
@@ -76,6 +297,47 @@ impl<const N: usize> SudokuCircuit<N> {
         }
         Ok(())
     }
+
+    /// Synthesizes the circuit for `puzzle` and `solution` and returns where
+    /// the first unsatisfied constraint comes from, if any.
+    pub fn first_unsatisfied<ConstraintF: PrimeField>(
+        puzzle: [[u8; N]; N],
+        solution: [[u8; N]; N],
+    ) -> Result<Option<Location>, SynthesisError> {
+        let cs = ConstraintSystem::<ConstraintF>::new_ref();
+        let puzzle_var = Puzzle::new_input(ns!(cs, "puzzle"), || Ok(puzzle))?;
+        let solution_var = Solution::new_witness(ns!(cs, "solution"), || Ok(solution))?;
+        let mut marks = Vec::new();
+        Self::enforce_solves_marked(&puzzle_var, &solution_var, &mut |location| {
+            marks.push((cs.num_constraints(), location))
+        })?;
+
+        cs.finalize();
+        let matrices = cs.to_matrices().expect("proving mode keeps the matrices");
+        let inner = cs.borrow().expect("the constraint system is not `None`");
+        let assignment = [
+            inner.instance_assignment.as_slice(),
+            inner.witness_assignment.as_slice(),
+        ]
+        .concat();
+        let eval = |row: &[(ConstraintF, usize)]| -> ConstraintF {
+            row.iter()
+                .map(|(coeff, index)| *coeff * assignment[*index])
+                .sum()
+        };
+        let Some(index) = (0..matrices.num_constraints)
+            .find(|&i| eval(&matrices.a[i]) * eval(&matrices.b[i]) != eval(&matrices.c[i]))
+        else {
+            return Ok(None);
+        };
+        let location = marks
+            .iter()
+            .rev()
+            .find(|(start, _)| *start <= index)
+            .map(|(_, location)| *location)
+            .expect("allocating bytes cannot fail");
+        Ok(Some(location))
+    }
 }
 
 impl<const N: usize, ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF>
@@ -85,16 +347,7 @@ impl<const N: usize, ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF>
         self,
         cs: ark_relations::r1cs::ConstraintSystemRef<ConstraintF>,
     ) -> ark_relations::r1cs::Result<()> {
-        let puzzle_var = Puzzle::new_input(cs.clone(), || {
-            self.puzzle.ok_or(SynthesisError::AssignmentMissing)
-        })?;
-        let solution_var = Solution::new_witness(cs.clone(), || {
-            self.solution.ok_or(SynthesisError::AssignmentMissing)
-        })?;
-        Self::check_puzzle_matches_solution(&puzzle_var, &solution_var)?;
-        solution_var.check_rows()?;
-        solution_var.check_cols()?;
-        solution_var.check_subgrids()?;
+        Self::new_solved(cs, self.puzzle, AllocationMode::Input, self.solution)?;
         Ok(())
     }
 }
@@ -102,8 +355,10 @@ impl<const N: usize, ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::{PUZZLE, SOLUTION};
     use ark_bls12_381::{Bls12_381, Fr as BlsFr};
     use ark_groth16::*;
+    use ark_relations::r1cs::ConstraintSystem;
     use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
     use ark_snark::SNARK;
     use rand::prelude::*;
@@ -160,13 +415,7 @@ mod tests {
 
         match Groth16::<Bls12_381>::verify(
             &vk,
-            puzzle
-                .into_iter()
-                .flatten()
-                .flat_map(|cell| (0..8).map(move |b| (cell >> b) & 1))
-                .map(BlsFr::from)
-                .collect::<Vec<_>>()
-                .as_slice(),
+            &SudokuCircuit::public_inputs::<BlsFr>(&puzzle),
             &proof,
         ) {
             Ok(true) => {}
@@ -256,13 +505,7 @@ mod tests {
         //// The proof is valid, but not for the correct puzzle.
         match Groth16::<Bls12_381>::verify(
             &vk,
-            puzzle
-                .into_iter()
-                .flatten()
-                .flat_map(|cell| (0..8).map(move |b| (cell >> b) & 1))
-                .map(BlsFr::from)
-                .collect::<Vec<_>>()
-                .as_slice(),
+            &SudokuCircuit::public_inputs::<BlsFr>(&puzzle),
             &proof,
         ) {
             Ok(false) => {}
@@ -271,6 +514,99 @@ mod tests {
         };
     }
 
+    #[test]
+    fn valid_4x4_solution_accepted() {
+        let rng = &mut thread_rng();
+        let (pk, vk) =
+            Groth16::<Bls12_381>::circuit_specific_setup(SudokuCircuit::<4>::default(), rng)
+                .unwrap();
+        let proof = Groth16::<Bls12_381>::prove(
+            &pk,
+            SudokuCircuit {
+                puzzle: Some(PUZZLE),
+                solution: Some(SOLUTION),
+            },
+            rng,
+        )
+        .unwrap();
+
+        match Groth16::<Bls12_381>::verify(
+            &vk,
+            &SudokuCircuit::<4>::public_inputs::<BlsFr>(&PUZZLE),
+            &proof,
+        ) {
+            Ok(true) => {}
+            Ok(false) => panic!("Proof rejected but should have been accepted"),
+            Err(err) => panic!("Failed to verify proof with vk: {err}"),
+        };
+    }
+
+    #[test]
+    fn duplicate_in_4x4_subgrid_rejected() {
+        // Every row and column is fine, but the top-left subgrid holds two 2s.
+        let solution = [[1, 2, 3, 4], [2, 3, 4, 1], [3, 4, 1, 2], [4, 1, 2, 3]];
+
+        let cs = ConstraintSystem::<BlsFr>::new_ref();
+        SudokuCircuit::<4> {
+            puzzle: Some([[0; 4]; 4]),
+            solution: Some(solution),
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn first_unsatisfied_locates_cells() {
+        type Circuit = SudokuCircuit<4>;
+        let (puzzle, solution) = (PUZZLE, SOLUTION);
+        assert_eq!(
+            Circuit::first_unsatisfied::<BlsFr>(puzzle, solution),
+            Ok(None)
+        );
+
+        let mut bad = solution;
+        bad[2][3] = 5;
+        let location = Circuit::first_unsatisfied::<BlsFr>(puzzle, bad).unwrap();
+        assert_eq!(location, Some(Location::Range { row: 2, col: 3 }));
+
+        let mut given = puzzle;
+        given[3][0] = 3;
+        let location = Circuit::first_unsatisfied::<BlsFr>(given, solution).unwrap();
+        assert_eq!(location, Some(Location::Given { row: 3, col: 0 }));
+
+        let mut bad = solution;
+        bad[1][3] = 4;
+        let location = Circuit::first_unsatisfied::<BlsFr>([[0; 4]; 4], bad).unwrap();
+        assert_eq!(location.unwrap().to_string(), "row_1/cell_1_vs_3");
+
+        let bad = [[1, 2, 3, 4], [2, 3, 4, 1], [3, 4, 1, 2], [4, 1, 2, 3]];
+        let location = Circuit::first_unsatisfied::<BlsFr>([[0; 4]; 4], bad).unwrap();
+        assert_eq!(
+            location.unwrap().to_string(),
+            "box_(0,0)/cell_(0,1)_vs_(1,0)"
+        );
+    }
+
+    #[test]
+    fn out_of_range_givens_rejected() {
+        // The solution matches, so only the range check on the public puzzle
+        // can catch the 200.
+        let mut malformed = PUZZLE;
+        malformed[2][1] = 200;
+        assert_eq!(
+            SudokuCircuit::<4>::first_unsatisfied::<BlsFr>(malformed, SOLUTION),
+            Ok(Some(Location::GivenRange { row: 2, col: 1 }))
+        );
+
+        // 5 is a byte like any other given, but not a digit of a 4x4 board.
+        malformed[2][1] = 5;
+        assert_eq!(
+            SudokuCircuit::<4>::first_unsatisfied::<BlsFr>(malformed, SOLUTION),
+            Ok(Some(Location::GivenRange { row: 2, col: 1 }))
+        );
+    }
+
     #[test]
     fn serde() {
         let puzzle = [
@@ -372,13 +708,7 @@ mod tests {
 
         match Groth16::<Bls12_381>::verify(
             &vk,
-            puzzle
-                .into_iter()
-                .flatten()
-                .flat_map(|cell| (0..8).map(move |b| (cell >> b) & 1))
-                .map(BlsFr::from)
-                .collect::<Vec<_>>()
-                .as_slice(),
+            &SudokuCircuit::public_inputs::<BlsFr>(&puzzle),
             &proof,
         ) {
             Ok(true) => {}
@@ -386,17 +716,8 @@ mod tests {
             Err(err) => panic!("Failed to verify proof with vk: {err}"),
         };
 
-        match Groth16::<Bls12_381>::verify(
-            &v,
-            puzzle
-                .into_iter()
-                .flatten()
-                .flat_map(|cell| (0..8).map(move |b| (cell >> b) & 1))
-                .map(BlsFr::from)
-                .collect::<Vec<_>>()
-                .as_slice(),
-            &pr,
-        ) {
+        match Groth16::<Bls12_381>::verify(&v, &SudokuCircuit::public_inputs::<BlsFr>(&puzzle), &pr)
+        {
             Ok(true) => {}
             Ok(false) => panic!("Proof rejected but should have been accepted"),
             Err(err) => panic!("Failed to verify proof with v: {err}"),
@@ -0,0 +1,141 @@
+//! Commit to a solution now, reveal it later.
+//!
+//! [`CommitmentCircuit`] proves the [`SudokuCircuit`] statement for the
+//! public puzzle, and outputs the public commitment
+//! `Poseidon(solution cells, randomness)` to the private solution. The
+//! private randomness hides the solution, which would otherwise be easy to
+//! find by hashing candidates. Once the solver reveals the solution and the
+//! randomness, anyone can [`open`] the commitment natively.
+
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::{AllocVar, AllocationMode, EqGadget};
+use ark_r1cs_std::R1CSVar;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::circuit::SudokuCircuit;
+use crate::poseidon;
+
+/// The commitment to `solution` under `randomness`.
+pub fn commit<const N: usize, F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    solution: &[[u8; N]; N],
+    randomness: F,
+) -> F {
+    let mut inputs: Vec<F> = solution
+        .iter()
+        .flatten()
+        .map(|&cell| F::from(cell))
+        .collect();
+    inputs.push(randomness);
+    poseidon::hash(config, &inputs)
+}
+
+/// Whether `commitment` is the commitment to the revealed `solution` under
+/// `randomness`. Whether the solution solves the puzzle is up to the proof
+/// that came with the commitment.
+pub fn open<const N: usize, F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    commitment: F,
+    solution: &[[u8; N]; N],
+    randomness: F,
+) -> bool {
+    commit(config, solution, randomness) == commitment
+}
+
+pub struct CommitmentCircuit<const N: usize, F: PrimeField> {
+    pub config: PoseidonConfig<F>,
+    // The puzzle is public
+    pub puzzle: Option<[[u8; N]; N]>,
+    // The solution is private
+    pub solution: Option<[[u8; N]; N]>,
+    // The randomness is private
+    pub randomness: Option<F>,
+}
+
+impl<const N: usize, F: PrimeField + Absorb> CommitmentCircuit<N, F> {
+    /// The public inputs a verifier passes alongside a proof for `puzzle`:
+    /// those of [`SudokuCircuit::public_inputs`], then the commitment.
+    pub fn public_inputs(puzzle: &[[u8; N]; N], commitment: F) -> Vec<F> {
+        let mut inputs = SudokuCircuit::public_inputs(puzzle);
+        inputs.push(commitment);
+        inputs
+    }
+}
+
+impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for CommitmentCircuit<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> ark_relations::r1cs::Result<()> {
+        let (_, solution) = SudokuCircuit::new_solved(
+            cs.clone(),
+            self.puzzle,
+            AllocationMode::Input,
+            self.solution,
+        )?;
+        let expected = FpVar::new_input(ns!(cs, "commitment"), || {
+            match (self.solution, self.randomness) {
+                (Some(solution), Some(randomness)) => {
+                    Ok(commit(&self.config, &solution, randomness))
+                }
+                _ => Err(SynthesisError::AssignmentMissing),
+            }
+        })?;
+        let randomness = FpVar::new_witness(ns!(cs, "randomness"), || {
+            self.randomness.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let mut inputs = solution
+            .0
+            .as_flattened()
+            .iter()
+            .map(|cell| cell.to_fp())
+            .collect::<Result<Vec<_>, _>>()?;
+        inputs.push(randomness);
+        poseidon::hash_var(solution.0.cs(), &self.config, &inputs)?.enforce_equal(&expected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{prove, setup, verifies, PUZZLE, SOLUTION};
+    use ark_bls12_381::Fr;
+    use ark_ff::UniformRand;
+    use rand::prelude::*;
+
+    #[test]
+    fn commitment_opens_to_proven_solution() {
+        let rng = &mut thread_rng();
+        let config = poseidon::config::<Fr>();
+        let circuit = |puzzle, solution, randomness| CommitmentCircuit::<4, Fr> {
+            config: config.clone(),
+            puzzle,
+            solution,
+            randomness,
+        };
+        let (pk, vk) = setup(circuit(None, None, None));
+        let randomness = Fr::rand(rng);
+        let proof = prove(&pk, circuit(Some(PUZZLE), Some(SOLUTION), Some(randomness)));
+
+        let commitment = commit(&config, &SOLUTION, randomness);
+        let inputs = CommitmentCircuit::<4, Fr>::public_inputs(&PUZZLE, commitment);
+        assert!(verifies(&vk, &inputs, &proof));
+        let other = commit(&config, &SOLUTION, Fr::rand(rng));
+        let inputs = CommitmentCircuit::<4, Fr>::public_inputs(&PUZZLE, other);
+        assert!(!verifies(&vk, &inputs, &proof));
+
+        // Later, the solver reveals the solution and the randomness.
+        assert!(open(&config, commitment, &SOLUTION, randomness));
+        assert!(!open(
+            &config,
+            commitment,
+            &SOLUTION,
+            randomness + Fr::from(1)
+        ));
+        let mut swapped = SOLUTION;
+        swapped.swap(0, 1);
+        assert!(!open(&config, commitment, &swapped, randomness));
+    }
+}
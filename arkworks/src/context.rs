@@ -0,0 +1,169 @@
+//! Proofs bound to a public context, so that they can't be replayed.
+//!
+//! A proof of [`SudokuCircuit`] only says that someone knows a solution, so
+//! anyone who sees it can submit it again as their own. [`ContextCircuit`]
+//! adds a public [`Context`] (a payee address, a session nonce or a
+//! verifier's challenge) to the inputs, after the puzzle. The proof is bound
+//! to the context like it is to the puzzle, and fails to verify under any
+//! other context.
+
+use ark_ff::{PrimeField, ToConstraintField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::prelude::AllocVar;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::circuit::SudokuCircuit;
+
+/// 32 bytes of context. Shorter values such as 20-byte addresses are
+/// right-aligned, as [`parse`] does.
+pub type Context = [u8; 32];
+
+#[derive(Default)]
+pub struct ContextCircuit<const N: usize> {
+    // The puzzle is public
+    pub puzzle: Option<[[u8; N]; N]>,
+    // The solution is private
+    pub solution: Option<[[u8; N]; N]>,
+    // The context is public
+    pub context: Option<Context>,
+}
+
+impl<const N: usize> ContextCircuit<N> {
+    /// The public inputs a verifier passes alongside a proof for `puzzle`
+    /// under `context`: those of [`SudokuCircuit::public_inputs`], then the
+    /// context bytes packed into as few field elements as they fit in.
+    pub fn public_inputs<ConstraintF: PrimeField>(
+        puzzle: &[[u8; N]; N],
+        context: &Context,
+    ) -> Vec<ConstraintF> {
+        let mut inputs = SudokuCircuit::public_inputs(puzzle);
+        inputs.extend(Self::pack::<ConstraintF>(context));
+        inputs
+    }
+
+    fn pack<ConstraintF: PrimeField>(context: &Context) -> Vec<ConstraintF> {
+        context[..]
+            .to_field_elements()
+            .expect("chunks of bytes always fit in the field")
+    }
+}
+
+impl<const N: usize, ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF>
+    for ContextCircuit<N>
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> ark_relations::r1cs::Result<()> {
+        SudokuCircuit {
+            puzzle: self.puzzle,
+            solution: self.solution,
+        }
+        .generate_constraints(cs.clone())?;
+
+        let packed = self
+            .context
+            .map(|context| Self::pack::<ConstraintF>(&context));
+        let chunks = Self::pack::<ConstraintF>(&[0; 32]).len();
+        for i in 0..chunks {
+            let input = FpVar::new_input(ns!(cs, "context"), || {
+                packed
+                    .as_ref()
+                    .map(|packed| packed[i])
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            // Nothing else depends on the context, so square it to have it
+            // in a constraint, rather than rely on how the proof system
+            // treats unconstrained inputs.
+            let _ = input.square()?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ContextError {
+    InvalidHex(String),
+    TooLong(usize),
+}
+
+impl std::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextError::InvalidHex(value) => write!(f, "`{value}` is not hex-encoded bytes"),
+            ContextError::TooLong(len) => {
+                write!(f, "Context has {len} bytes, expected at most 32")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ContextError {}
+
+/// Parses a context from hex, with or without `0x`, right-aligning values
+/// shorter than 32 bytes.
+pub fn parse(value: &str) -> Result<Context, ContextError> {
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    if !digits.len().is_multiple_of(2) || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ContextError::InvalidHex(value.to_string()));
+    }
+    let bytes: Vec<u8> = (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).expect("checked hex digits"))
+        .collect();
+    if bytes.len() > 32 {
+        return Err(ContextError::TooLong(bytes.len()));
+    }
+    let mut context = [0; 32];
+    context[32 - bytes.len()..].copy_from_slice(&bytes);
+    Ok(context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{PUZZLE, SOLUTION};
+    use crate::snark::{self, Backend};
+    use rand::prelude::*;
+
+    #[test]
+    fn proofs_are_bound_to_context() {
+        let rng = &mut thread_rng();
+        let payee = parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").unwrap();
+        let mut other = payee;
+        other[31] ^= 1;
+        let mut wrong_puzzle = PUZZLE;
+        wrong_puzzle[0][1] = 2;
+
+        for backend in Backend::ALL {
+            let (pk, vk) = backend.setup_with_context::<4, _>(rng).unwrap();
+            let proof =
+                snark::prove_with_context_tagged(&pk, PUZZLE, SOLUTION, &payee, rng).unwrap();
+            assert!(snark::verify_with_context_tagged(&vk, &PUZZLE, &payee, &proof).unwrap());
+            assert!(!snark::verify_with_context_tagged(&vk, &PUZZLE, &other, &proof).unwrap());
+            assert!(
+                !snark::verify_with_context_tagged(&vk, &wrong_puzzle, &payee, &proof).unwrap()
+            );
+            // Keys without a context input don't take these proofs.
+            let (_, plain_vk) = backend.setup::<4, _>(rng).unwrap();
+            assert!(snark::verify_with_context_tagged(&plain_vk, &PUZZLE, &payee, &proof).is_err());
+        }
+    }
+
+    #[test]
+    fn parses_hex() {
+        let mut expected = [0; 32];
+        expected[30..].copy_from_slice(&[0xbe, 0xef]);
+        assert_eq!(parse("0xbeef"), Ok(expected));
+        assert_eq!(parse("BEEF"), Ok(expected));
+        assert_eq!(parse(""), Ok([0; 32]));
+        assert_eq!(
+            parse("0xbee"),
+            Err(ContextError::InvalidHex("0xbee".into()))
+        );
+        assert_eq!(parse("0xzz"), Err(ContextError::InvalidHex("0xzz".into())));
+        assert_eq!(parse(&"00".repeat(33)), Err(ContextError::TooLong(33)));
+    }
+}
@@ -0,0 +1,139 @@
+//! Proofs of a whole solution that reveal only some of its cells.
+//!
+//! [`DisclosureCircuit`] proves the [`SudokuCircuit`] statement for the
+//! public puzzle, and takes as further public inputs a reveal mask and the
+//! revealed cells: every cell the mask selects must match the solution, and
+//! every other cell is 0 in the inputs and stays private. A hint seller can
+//! so sell cells that are known to belong to a valid solution.
+
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::{AllocVar, AllocationMode, Boolean, EqGadget};
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::circuit::{Puzzle, SudokuCircuit};
+
+/// The cells of `solution` selected by `mask`, `None` for the hidden ones.
+pub fn reveal<const N: usize>(
+    solution: &[[u8; N]; N],
+    mask: &[[bool; N]; N],
+) -> [[Option<u8>; N]; N] {
+    std::array::from_fn(|row| {
+        std::array::from_fn(|col| mask[row][col].then_some(solution[row][col]))
+    })
+}
+
+#[derive(Default)]
+pub struct DisclosureCircuit<const N: usize> {
+    // The puzzle is public
+    pub puzzle: Option<[[u8; N]; N]>,
+    // The solution is private
+    pub solution: Option<[[u8; N]; N]>,
+    // The mask is public, and so are the cells it selects
+    pub mask: Option<[[bool; N]; N]>,
+}
+
+impl<const N: usize> DisclosureCircuit<N> {
+    /// The public inputs a verifier passes alongside a proof for `puzzle`
+    /// that reveals the `Some` cells of `revealed`: those of
+    /// [`SudokuCircuit::public_inputs`], then the mask, one input per cell,
+    /// then the revealed cells, 0 where hidden, packed like the puzzle.
+    pub fn public_inputs<ConstraintF: PrimeField>(
+        puzzle: &[[u8; N]; N],
+        revealed: &[[Option<u8>; N]; N],
+    ) -> Vec<ConstraintF> {
+        let mask = revealed.iter().flatten().map(|cell| cell.is_some());
+        let values = revealed.map(|row| row.map(|cell| cell.unwrap_or(0)));
+        let mut inputs = SudokuCircuit::public_inputs(puzzle);
+        inputs.extend(mask.map(ConstraintF::from));
+        inputs.extend(SudokuCircuit::public_inputs::<ConstraintF>(&values));
+        inputs
+    }
+}
+
+impl<const N: usize, ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF>
+    for DisclosureCircuit<N>
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> ark_relations::r1cs::Result<()> {
+        let (_, solution) = SudokuCircuit::new_solved(
+            cs.clone(),
+            self.puzzle,
+            AllocationMode::Input,
+            self.solution,
+        )?;
+        let mut mask = Vec::with_capacity(N * N);
+        for cell in 0..N * N {
+            mask.push(Boolean::new_input(ns!(cs, "mask"), || {
+                self.mask
+                    .map(|mask| mask.as_flattened()[cell])
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?);
+        }
+        let revealed = match (self.solution, self.mask) {
+            (Some(solution), Some(mask)) => Some(reveal(&solution, &mask)),
+            _ => None,
+        };
+        let revealed = Puzzle::new_input(ns!(cs, "revealed"), || {
+            revealed
+                .map(|revealed| revealed.map(|row| row.map(|cell| cell.unwrap_or(0))))
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        for ((shown, value), cell) in mask
+            .iter()
+            .zip(revealed.0.as_flattened())
+            .zip(solution.0.as_flattened())
+        {
+            value.enforce_equal(&shown.select(cell, &UInt8::constant(0))?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{prove, setup, verifies, PUZZLE, SOLUTION};
+    use ark_bls12_381::Fr;
+
+    #[test]
+    fn reveals_only_masked_cells() {
+        let (pk, vk) = setup(DisclosureCircuit::<4>::default());
+        let mut mask = [[false; 4]; 4];
+        mask[0][1] = true;
+        mask[2][3] = true;
+        let proof = prove(
+            &pk,
+            DisclosureCircuit {
+                puzzle: Some(PUZZLE),
+                solution: Some(SOLUTION),
+                mask: Some(mask),
+            },
+        );
+
+        let revealed = reveal(&SOLUTION, &mask);
+        assert_eq!(revealed[0][1], Some(2));
+        assert_eq!(revealed[2][3], Some(3));
+        assert_eq!(revealed.iter().flatten().flatten().count(), 2);
+        let verify = |revealed: &[[Option<u8>; 4]; 4]| {
+            let inputs = DisclosureCircuit::public_inputs::<Fr>(&PUZZLE, revealed);
+            verifies(&vk, &inputs, &proof)
+        };
+        assert!(verify(&revealed));
+
+        // Neither the revealed values nor the mask can be changed.
+        let mut wrong_value = revealed;
+        wrong_value[0][1] = Some(3);
+        assert!(!verify(&wrong_value));
+        let mut more = revealed;
+        more[0][0] = Some(1);
+        assert!(!verify(&more));
+        let mut fewer = revealed;
+        fewer[2][3] = None;
+        assert!(!verify(&fewer));
+    }
+}
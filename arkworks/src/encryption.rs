@@ -0,0 +1,190 @@
+//! Verifiable encryption of a solution, for selling it trustlessly.
+//!
+//! In zero-knowledge contingent payment, the seller publishes a ciphertext of
+//! the solution and proves with [`EncryptionCircuit`] that it encrypts a
+//! solution to the public puzzle under a key whose [`key_hash`] is public.
+//! The buyer then pays against the key hash (for example through a hash
+//! lock that releases the payment for the key's preimage), learns the key
+//! and [`decrypt`]s.
+//!
+//! The cipher is a Poseidon stream cipher over the field: every cell is
+//! added to one element of the keystream squeezed from `Poseidon(key, 1)`.
+//! The same key encrypts the same solution to the same ciphertext, and two
+//! solutions under the same key leak their difference, so sellers have to
+//! use a fresh key for every sale.
+
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::fields::FieldVar;
+use ark_r1cs_std::prelude::{AllocVar, AllocationMode, EqGadget};
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::circuit::SudokuCircuit;
+use crate::poseidon;
+
+/// The public hash of `key`. The keystream absorbs a 1 after the key, as
+/// the first keystream element would otherwise equal the hash.
+pub fn key_hash<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, key: F) -> F {
+    poseidon::hash(config, &[key])
+}
+
+fn keystream<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, key: F, len: usize) -> Vec<F> {
+    poseidon::squeeze(config, &[key, F::one()], len)
+}
+
+/// Encrypts the cells of `solution`, row by row, under `key`.
+pub fn encrypt<const N: usize, F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    key: F,
+    solution: &[[u8; N]; N],
+) -> Vec<F> {
+    solution
+        .iter()
+        .flatten()
+        .zip(keystream(config, key, N * N))
+        .map(|(&cell, pad)| F::from(cell) + pad)
+        .collect()
+}
+
+/// Decrypts a ciphertext of [`encrypt`], or `None` if it doesn't hold `N`x`N`
+/// bytes under `key`.
+pub fn decrypt<const N: usize, F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    key: F,
+    ciphertext: &[F],
+) -> Option<[[u8; N]; N]> {
+    if ciphertext.len() != N * N {
+        return None;
+    }
+    let mut solution = [[0; N]; N];
+    for ((cell, &encrypted), pad) in solution
+        .as_flattened_mut()
+        .iter_mut()
+        .zip(ciphertext)
+        .zip(keystream(config, key, N * N))
+    {
+        let plain = (encrypted - pad).into_bigint();
+        let (low, high) = plain.as_ref().split_first()?;
+        if high.iter().any(|&limb| limb != 0) {
+            return None;
+        }
+        *cell = u8::try_from(*low).ok()?;
+    }
+    Some(solution)
+}
+
+pub struct EncryptionCircuit<const N: usize, F: PrimeField> {
+    pub config: PoseidonConfig<F>,
+    // The puzzle is public
+    pub puzzle: Option<[[u8; N]; N]>,
+    // The solution is private
+    pub solution: Option<[[u8; N]; N]>,
+    // The key is private, its hash and the ciphertext are public
+    pub key: Option<F>,
+}
+
+impl<const N: usize, F: PrimeField + Absorb> EncryptionCircuit<N, F> {
+    /// The public inputs a verifier passes alongside a proof for `puzzle`:
+    /// those of [`SudokuCircuit::public_inputs`], then the key hash, then the
+    /// ciphertext.
+    pub fn public_inputs(puzzle: &[[u8; N]; N], key_hash: F, ciphertext: &[F]) -> Vec<F> {
+        let mut inputs = SudokuCircuit::public_inputs(puzzle);
+        inputs.push(key_hash);
+        inputs.extend_from_slice(ciphertext);
+        inputs
+    }
+}
+
+impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for EncryptionCircuit<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> ark_relations::r1cs::Result<()> {
+        let (_, solution) = SudokuCircuit::new_solved(
+            cs.clone(),
+            self.puzzle,
+            AllocationMode::Input,
+            self.solution,
+        )?;
+        let expected_hash = FpVar::new_input(ns!(cs, "key_hash"), || {
+            self.key
+                .map(|key| key_hash(&self.config, key))
+                .ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let ciphertext = match (self.key, self.solution) {
+            (Some(key), Some(solution)) => Some(encrypt(&self.config, key, &solution)),
+            _ => None,
+        };
+        let expected_ciphertext = (0..N * N)
+            .map(|i| {
+                FpVar::new_input(ns!(cs, "ciphertext"), || {
+                    ciphertext
+                        .as_ref()
+                        .map(|ciphertext| ciphertext[i])
+                        .ok_or(SynthesisError::AssignmentMissing)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = FpVar::new_witness(ns!(cs, "key"), || {
+            self.key.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        poseidon::hash_var(cs.clone(), &self.config, std::slice::from_ref(&key))?
+            .enforce_equal(&expected_hash)?;
+        let keystream = poseidon::squeeze_var(cs, &self.config, &[key, FpVar::one()], N * N)?;
+        for ((cell, pad), expected) in solution
+            .0
+            .as_flattened()
+            .iter()
+            .zip(keystream)
+            .zip(&expected_ciphertext)
+        {
+            (cell.to_fp()? + pad).enforce_equal(expected)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{prove, setup, verifies, PUZZLE, SOLUTION};
+    use crate::validate::validate;
+    use ark_bls12_381::Fr;
+    use ark_ff::UniformRand;
+    use rand::prelude::*;
+
+    #[test]
+    fn buyer_decrypts_proven_solution() {
+        let rng = &mut thread_rng();
+        let config = poseidon::config::<Fr>();
+        let circuit = |puzzle, solution, key| EncryptionCircuit::<4, Fr> {
+            config: config.clone(),
+            puzzle,
+            solution,
+            key,
+        };
+        let (pk, vk) = setup(circuit(None, None, None));
+
+        // The seller publishes the ciphertext, the key hash and the proof.
+        let key = Fr::rand(rng);
+        let ciphertext = encrypt(&config, key, &SOLUTION);
+        let hash = key_hash(&config, key);
+        let proof = prove(&pk, circuit(Some(PUZZLE), Some(SOLUTION), Some(key)));
+
+        // The buyer checks them before paying.
+        let inputs = EncryptionCircuit::<4, Fr>::public_inputs(&PUZZLE, hash, &ciphertext);
+        assert!(verifies(&vk, &inputs, &proof));
+        let mut tampered = ciphertext.clone();
+        tampered[5] += Fr::from(1);
+        let inputs = EncryptionCircuit::<4, Fr>::public_inputs(&PUZZLE, hash, &tampered);
+        assert!(!verifies(&vk, &inputs, &proof));
+        assert_eq!(decrypt::<4, _>(&config, Fr::rand(rng), &ciphertext), None);
+
+        // Payment reveals the key, which opens the ciphertext.
+        assert_eq!(key_hash(&config, key), hash);
+        let solution = decrypt::<4, _>(&config, key, &ciphertext).unwrap();
+        assert_eq!(solution, SOLUTION);
+        assert_eq!(validate(&PUZZLE, &solution), Ok(()));
+    }
+}
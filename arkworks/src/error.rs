@@ -0,0 +1,199 @@
+//! The error of the proving pipeline as a whole.
+//!
+//! Each module has an error for its own step ([`SnarkError`], [`NoirTomlError`],
+//! [`PtauError`], [`CeremonyError`], [`BatchError`], [`AggregationError`],
+//! [`NullifierError`]); [`PipelineError`] says which step of setup, proving
+//! or verification failed and keeps the step's error as its
+//! [`source`](std::error::Error::source),
+//! down to the [`ValidationError`](crate::validate::ValidationError) of an
+//! invalid solution or the
+//! [`SynthesisError`](ark_relations::r1cs::SynthesisError) or
+//! [`SerializationError`](ark_serialize::SerializationError) of arkworks.
+
+use std::path::PathBuf;
+
+use crate::{
+    aggregation::AggregationError, batch::BatchError, ceremony::CeremonyError, noir::NoirTomlError,
+    nullifier::NullifierError, ptau::PtauError, snark::SnarkError,
+};
+
+#[derive(Debug)]
+pub enum PipelineError {
+    /// The puzzle or solution could not be parsed.
+    Input(NoirTomlError),
+    /// The `.ptau` file could not be read.
+    PowersOfTau(PtauError),
+    Setup(SnarkError),
+    /// The setup ceremony's parameters do not check out.
+    Ceremony(CeremonyError),
+    Prove(SnarkError),
+    Rerandomize(SnarkError),
+    Verify(SnarkError),
+    PrepareVk(SnarkError),
+    BatchVerify(BatchError),
+    Aggregate(AggregationError),
+    /// A proof could not claim its puzzle for a nullifier.
+    Claim(NullifierError),
+    /// The proof is well-formed, but does not prove a solution to the puzzle.
+    Rejected,
+    /// The CLI is not built for boards of this size.
+    UnsupportedSize(usize),
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl std::fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PipelineError::Input(_) => write!(f, "Invalid inputs"),
+            PipelineError::PowersOfTau(_) => write!(f, "Failed to read powers of tau"),
+            PipelineError::Setup(_) => write!(f, "Failed to generate setup parameters"),
+            PipelineError::Ceremony(_) => write!(f, "Invalid setup ceremony"),
+            PipelineError::Prove(_) => write!(f, "Failed to generate proof"),
+            PipelineError::Rerandomize(_) => write!(f, "Failed to rerandomize proof"),
+            PipelineError::Verify(_) => write!(f, "Failed to verify proof"),
+            PipelineError::PrepareVk(_) => write!(f, "Failed to prepare verifying key"),
+            PipelineError::BatchVerify(_) => write!(f, "Failed to batch-verify proofs"),
+            PipelineError::Aggregate(_) => write!(f, "Failed to aggregate proofs"),
+            PipelineError::Claim(_) => write!(f, "Failed to claim puzzle"),
+            PipelineError::Rejected => write!(f, "Proof rejected"),
+            PipelineError::UnsupportedSize(size) => {
+                write!(f, "Boards of size {size} are not supported")
+            }
+            PipelineError::Io { path, .. } => write!(f, "Failed to access {}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PipelineError::Input(err) => Some(err),
+            PipelineError::PowersOfTau(err) => Some(err),
+            PipelineError::Setup(err)
+            | PipelineError::Prove(err)
+            | PipelineError::Rerandomize(err)
+            | PipelineError::Verify(err)
+            | PipelineError::PrepareVk(err) => Some(err),
+            PipelineError::Ceremony(err) => Some(err),
+            PipelineError::BatchVerify(err) => Some(err),
+            PipelineError::Aggregate(err) => Some(err),
+            PipelineError::Claim(err) => Some(err),
+            PipelineError::Rejected | PipelineError::UnsupportedSize(_) => None,
+            PipelineError::Io { source, .. } => Some(source),
+        }
+    }
+}
+
+impl From<NoirTomlError> for PipelineError {
+    fn from(err: NoirTomlError) -> Self {
+        PipelineError::Input(err)
+    }
+}
+
+impl From<PtauError> for PipelineError {
+    fn from(err: PtauError) -> Self {
+        PipelineError::PowersOfTau(err)
+    }
+}
+
+impl From<CeremonyError> for PipelineError {
+    fn from(err: CeremonyError) -> Self {
+        PipelineError::Ceremony(err)
+    }
+}
+
+impl From<BatchError> for PipelineError {
+    fn from(err: BatchError) -> Self {
+        PipelineError::BatchVerify(err)
+    }
+}
+
+impl From<AggregationError> for PipelineError {
+    fn from(err: AggregationError) -> Self {
+        PipelineError::Aggregate(err)
+    }
+}
+
+impl From<NullifierError> for PipelineError {
+    fn from(err: NullifierError) -> Self {
+        PipelineError::Claim(err)
+    }
+}
+
+/// `err` and its chain of sources, one per line.
+pub fn report(err: &dyn std::error::Error) -> String {
+    let mut report = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        report.push_str(&format!("\n  caused by: {err}"));
+        source = err.source();
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SOLUTION;
+    use crate::ptau::PowersOfTau;
+    use crate::snark::{self, Backend};
+    use crate::validate::ValidationError;
+    use ark_serialize::SerializationError;
+    use rand::thread_rng;
+
+    #[test]
+    fn sources_chain_down_to_arkworks() {
+        let err =
+            PipelineError::Verify(snark::verify_tagged::<4>(&[], &[[0; 4]; 4], &[]).unwrap_err());
+        assert_eq!(report(&err).lines().count(), 3);
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(matches!(
+            source.downcast_ref::<SnarkError>(),
+            Some(SnarkError::Serialization(
+                SerializationError::NotEnoughSpace
+            ))
+        ));
+        assert!(source.source().unwrap().is::<SerializationError>());
+
+        let (pk, _) = Backend::Groth16Bls12_381
+            .setup::<4, _>(&mut thread_rng())
+            .unwrap();
+        let mut solution = SOLUTION;
+        solution[0][0] = 5;
+        let err = PipelineError::Prove(
+            snark::prove_tagged(&pk, [[0; 4]; 4], solution, &mut thread_rng()).unwrap_err(),
+        );
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(matches!(
+            source.downcast_ref::<SnarkError>(),
+            Some(SnarkError::InvalidSolution(
+                ValidationError::OutOfRange { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn step_errors_map_to_their_variants() {
+        let err = PipelineError::from(PowersOfTau::read(b"zkey").unwrap_err());
+        assert!(matches!(
+            err,
+            PipelineError::PowersOfTau(PtauError::BadMagic)
+        ));
+        assert_eq!(report(&err).lines().count(), 2);
+
+        let err = PipelineError::from(BatchError::Rejected(vec![1]));
+        assert!(matches!(err, PipelineError::BatchVerify(_)));
+        let err = PipelineError::from(AggregationError::NotPowerOfTwo(3));
+        assert!(matches!(err, PipelineError::Aggregate(_)));
+        let err = PipelineError::from(CeremonyError::InconsistentDelta);
+        assert!(matches!(err, PipelineError::Ceremony(_)));
+        let err = PipelineError::from(NullifierError::AlreadyClaimed);
+        assert_eq!(
+            report(&err),
+            "Failed to claim puzzle\n  caused by: Nullifier already claimed"
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! On-chain verification of BN254 Groth16 proofs of [`SudokuCircuit`].
+//!
+//! [`solidity_verifier`] emits a Solidity contract with the ABI
+//! `verifyProof(uint256[8] proof, uint8[N*N] puzzle) returns (bool)`.
+//!
+//! The contract packs the puzzle cells into public inputs the way
+//! [`SudokuCircuit::public_inputs`] does, so its `IC` table holds only
+//! [`SudokuCircuit::num_inputs`]` + 1` points and a 9x9 verifier stays well
+//! under the EIP-170 contract size limit.
+//!
+//! [`SudokuCircuit`]: crate::circuit::SudokuCircuit
+
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+use ark_ff::{BigInteger, PrimeField};
+use ark_groth16::{Proof, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    circuit::{cells_per_input, SudokuCircuit},
+    snark::SnarkError,
+};
+
+type Word = [u8; 32];
+
+fn fq_word(x: &Fq) -> Word {
+    let mut word = [0; 32];
+    word.copy_from_slice(&x.into_bigint().to_bytes_be());
+    word
+}
+
+/// `(x, y)`, with the point at infinity as `(0, 0)`.
+fn g1_words(p: &G1Affine) -> [Word; 2] {
+    match p.infinity {
+        true => [[0; 32]; 2],
+        false => [fq_word(&p.x), fq_word(&p.y)],
+    }
+}
+
+/// `(x.c1, x.c0, y.c1, y.c0)`: the precompiles expect the imaginary part first.
+fn g2_words(p: &G2Affine) -> [Word; 4] {
+    match p.infinity {
+        true => [[0; 32]; 4],
+        false => [
+            fq_word(&p.x.c1),
+            fq_word(&p.x.c0),
+            fq_word(&p.y.c1),
+            fq_word(&p.y.c0),
+        ],
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The Solidity signature of `verifyProof` for an `N`x`N` board.
+pub fn signature<const N: usize>() -> String {
+    format!("verifyProof(uint256[8],uint8[{}])", N * N)
+}
+
+/// The 4-byte function selector of `verifyProof`.
+pub fn selector<const N: usize>() -> [u8; 4] {
+    let hash = Keccak256::digest(signature::<N>().as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// The `uint256[8]` proof argument: `[a.x, a.y, b.x.c1, b.x.c0, b.y.c1, b.y.c0, c.x, c.y]`.
+pub fn encode_proof(proof: &Proof<Bn254>) -> [Word; 8] {
+    let [a_x, a_y] = g1_words(&proof.a);
+    let [b_x1, b_x0, b_y1, b_y0] = g2_words(&proof.b);
+    let [c_x, c_y] = g1_words(&proof.c);
+    [a_x, a_y, b_x1, b_x0, b_y1, b_y0, c_x, c_y]
+}
+
+/// ABI-encoded calldata for `verifyProof(proof, puzzle)`.
+pub fn encode_calldata<const N: usize>(proof: &Proof<Bn254>, puzzle: &[[u8; N]; N]) -> Vec<u8> {
+    let mut calldata = selector::<N>().to_vec();
+    for word in encode_proof(proof) {
+        calldata.extend(word);
+    }
+    for &cell in puzzle.iter().flatten() {
+        let mut word = [0; 32];
+        word[31] = cell;
+        calldata.extend(word);
+    }
+    calldata
+}
+
+/// Solidity source of a Groth16 verifier for `SudokuCircuit<N>` under `vk`.
+///
+/// Fails with [`SnarkError::InputCountMismatch`] if `vk` is not for an
+/// `N`x`N` board.
+pub fn solidity_verifier<const N: usize>(vk: &VerifyingKey<Bn254>) -> Result<String, SnarkError> {
+    let expected = SudokuCircuit::<N>::num_inputs::<Fr>();
+    let found = vk.gamma_abc_g1.len().saturating_sub(1);
+    if found != expected {
+        return Err(SnarkError::InputCountMismatch { expected, found });
+    }
+    let [alpha_x, alpha_y] = g1_words(&vk.alpha_g1).map(|w| hex(&w));
+    let [beta_x1, beta_x0, beta_y1, beta_y0] = g2_words(&vk.beta_g2).map(|w| hex(&w));
+    let [gamma_x1, gamma_x0, gamma_y1, gamma_y0] = g2_words(&vk.gamma_g2).map(|w| hex(&w));
+    let [delta_x1, delta_x0, delta_y1, delta_y0] = g2_words(&vk.delta_g2).map(|w| hex(&w));
+    let cells = N * N;
+    let ic: String = vk
+        .gamma_abc_g1
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let [x, y] = g1_words(p).map(|w| hex(&w));
+            format!(
+                "    uint256 constant IC{i}_X = 0x{x};\n    uint256 constant IC{i}_Y = 0x{y};\n"
+            )
+        })
+        .collect();
+    // Input k packs the cells from `per_input * k`, as in `SudokuCircuit::public_inputs`
+    let per_input = cells_per_input::<Fr>();
+    let accumulate: String = (1..vk.gamma_abc_g1.len())
+        .map(|i| {
+            let start = per_input * (i - 1);
+            let end = cells.min(start + per_input);
+            format!("        vkX = ecAdd(vkX, ecMul(IC{i}_X, IC{i}_Y, pack(puzzle, {start}, {end})));\n")
+        })
+        .collect();
+    Ok(format!(
+        r#"// SPDX-License-Identifier: MIT
+// Generated by sudoku-snark. Do not edit.
+pragma solidity ^0.8.0;
+
+/// Groth16 verifier (BN254) for `SudokuCircuit<{N}>`.
+contract SudokuVerifier {{
+    uint256 constant Q = 0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47;
+
+    uint256 constant ALPHA_X = 0x{alpha_x};
+    uint256 constant ALPHA_Y = 0x{alpha_y};
+    uint256 constant BETA_X1 = 0x{beta_x1};
+    uint256 constant BETA_X0 = 0x{beta_x0};
+    uint256 constant BETA_Y1 = 0x{beta_y1};
+    uint256 constant BETA_Y0 = 0x{beta_y0};
+    uint256 constant GAMMA_X1 = 0x{gamma_x1};
+    uint256 constant GAMMA_X0 = 0x{gamma_x0};
+    uint256 constant GAMMA_Y1 = 0x{gamma_y1};
+    uint256 constant GAMMA_Y0 = 0x{gamma_y0};
+    uint256 constant DELTA_X1 = 0x{delta_x1};
+    uint256 constant DELTA_X0 = 0x{delta_x0};
+    uint256 constant DELTA_Y1 = 0x{delta_y1};
+    uint256 constant DELTA_Y0 = 0x{delta_y0};
+
+    // gamma_abc_g1: IC0, then one point per packed input
+{ic}
+    /// `proof` is `[a.x, a.y, b.x.c1, b.x.c0, b.y.c1, b.y.c0, c.x, c.y]`,
+    /// `puzzle` is the board in row-major order with 0 for blanks.
+    function verifyProof(uint256[8] calldata proof, uint8[{cells}] calldata puzzle)
+        external
+        view
+        returns (bool)
+    {{
+        uint256[2] memory vkX;
+        vkX[0] = IC0_X;
+        vkX[1] = IC0_Y;
+{accumulate}
+        // e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+        uint256[24] memory input;
+        input[0] = proof[0];
+        input[1] = (Q - proof[1]) % Q;
+        input[2] = proof[2];
+        input[3] = proof[3];
+        input[4] = proof[4];
+        input[5] = proof[5];
+        input[6] = ALPHA_X;
+        input[7] = ALPHA_Y;
+        input[8] = BETA_X1;
+        input[9] = BETA_X0;
+        input[10] = BETA_Y1;
+        input[11] = BETA_Y0;
+        input[12] = vkX[0];
+        input[13] = vkX[1];
+        input[14] = GAMMA_X1;
+        input[15] = GAMMA_X0;
+        input[16] = GAMMA_Y1;
+        input[17] = GAMMA_Y0;
+        input[18] = proof[6];
+        input[19] = proof[7];
+        input[20] = DELTA_X1;
+        input[21] = DELTA_X0;
+        input[22] = DELTA_Y1;
+        input[23] = DELTA_Y0;
+
+        uint256[1] memory out;
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x08, input, 0x300, out, 0x20)
+        }}
+        require(success, "pairing failed");
+        return out[0] == 1;
+    }}
+
+    /// Cells `start..end` of `puzzle` as a little-endian integer.
+    function pack(uint8[{cells}] calldata puzzle, uint256 start, uint256 end) internal pure returns (uint256 packed) {{
+        for (uint256 i = start; i < end; i++) {{
+            packed |= uint256(puzzle[i]) << (8 * (i - start));
+        }}
+    }}
+
+    function ecAdd(uint256[2] memory p, uint256[2] memory q) internal view returns (uint256[2] memory r) {{
+        uint256[4] memory input;
+        input[0] = p[0];
+        input[1] = p[1];
+        input[2] = q[0];
+        input[3] = q[1];
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x06, input, 0x80, r, 0x40)
+        }}
+        require(success, "ecAdd failed");
+    }}
+
+    function ecMul(uint256 x, uint256 y, uint256 s) internal view returns (uint256[2] memory r) {{
+        uint256[3] memory input;
+        input[0] = x;
+        input[1] = y;
+        input[2] = s;
+        bool success;
+        assembly {{
+            success := staticcall(gas(), 0x07, input, 0x60, r, 0x40)
+        }}
+        require(success, "ecMul failed");
+    }}
+}}
+"#
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{PUZZLE_9, SOLUTION_9};
+    use ark_groth16::Groth16;
+    use ark_snark::SNARK;
+    use rand::prelude::*;
+    use revm::{
+        db::InMemoryDB,
+        primitives::{ExecutionResult, Output, TxKind},
+        Evm,
+    };
+    use solar::codegen::{lower, EvmCodegen};
+    use solar::config::{CompileOpts, UnstableOpts};
+    use solar::interface::Session;
+    use solar::sema::Compiler;
+    use std::path::PathBuf;
+
+    /// Compiles `source` with the solar Solidity compiler and returns the
+    /// init code of its one contract.
+    fn compile(source: &str) -> Vec<u8> {
+        let opts = CompileOpts {
+            unstable: UnstableOpts {
+                codegen: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let sess = Session::builder().with_stderr_emitter().opts(opts).build();
+        let mut compiler = Compiler::new(sess);
+        compiler.enter_mut(|c| {
+            let file = c
+                .sess()
+                .source_map()
+                .new_source_file(PathBuf::from("SudokuVerifier.sol"), source.to_string())
+                .unwrap();
+            let mut pcx = c.parse();
+            pcx.add_file(file);
+            pcx.parse();
+            assert!(c.lower_asts().expect("Solidity should lower").is_continue());
+            assert!(c
+                .analysis()
+                .expect("Solidity should type-check")
+                .is_continue());
+            let gcx = c.gcx();
+            let contract = gcx.hir.contract_ids().next().expect("one contract");
+            let mut module = lower::lower_contract(gcx, contract);
+            gcx.dcx()
+                .has_errors()
+                .expect("Solidity should lower to MIR");
+            let (deployment, _) = EvmCodegen::new(gcx).generate_deployment_bytecode(&mut module);
+            deployment
+        })
+    }
+
+    /// Deploys `code` to an in-memory EVM, with mainnet's contract size
+    /// limit, and calls it once per `calldata`.
+    fn deploy_and_call(code: Vec<u8>, calls: &[Vec<u8>]) -> Vec<ExecutionResult> {
+        let mut evm = Evm::builder()
+            .with_db(InMemoryDB::default())
+            .modify_tx_env(|tx| {
+                tx.transact_to = TxKind::Create;
+                tx.data = code.into();
+                tx.gas_limit = 30_000_000;
+            })
+            .build();
+        let address = match evm.transact_commit().unwrap() {
+            ExecutionResult::Success {
+                output: Output::Create(_, Some(address)),
+                ..
+            } => address,
+            result => panic!("Deployment failed: {result:?}"),
+        };
+        calls
+            .iter()
+            .map(|calldata| {
+                evm.tx_mut().transact_to = TxKind::Call(address);
+                evm.tx_mut().data = calldata.clone().into();
+                evm.transact_commit().unwrap()
+            })
+            .collect()
+    }
+
+    fn returned_word(result: &ExecutionResult) -> Word {
+        match result {
+            ExecutionResult::Success {
+                output: Output::Call(data),
+                ..
+            } => data[..].try_into().unwrap(),
+            result => panic!("Call failed: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn verifier_contract_in_evm() -> Result<(), SnarkError> {
+        let rng = &mut thread_rng();
+        let (pk, vk) =
+            Groth16::<Bn254>::circuit_specific_setup(SudokuCircuit::<9>::default(), rng)?;
+        let proof = Groth16::<Bn254>::prove(
+            &pk,
+            SudokuCircuit {
+                puzzle: Some(PUZZLE_9),
+                solution: Some(SOLUTION_9),
+            },
+            rng,
+        )?;
+
+        let mut wrong_puzzle = PUZZLE_9;
+        wrong_puzzle[8][8] = 1;
+        let mut bad_selector = encode_calldata(&proof, &PUZZLE_9);
+        bad_selector[0] ^= 1;
+        let mut dirty_cell = encode_calldata(&proof, &PUZZLE_9);
+        dirty_cell[4 + 8 * 32 + 30] = 1;
+
+        let results = deploy_and_call(
+            compile(&solidity_verifier::<9>(&vk)?),
+            &[
+                encode_calldata(&proof, &PUZZLE_9),
+                encode_calldata(&proof, &wrong_puzzle),
+                bad_selector,
+                dirty_cell,
+            ],
+        );
+        let mut accepted = [0; 32];
+        accepted[31] = 1;
+        assert_eq!(returned_word(&results[0]), accepted);
+        assert_eq!(returned_word(&results[1]), [0; 32]);
+        assert!(matches!(results[2], ExecutionResult::Revert { .. }));
+        assert!(matches!(results[3], ExecutionResult::Revert { .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn solidity_source_embeds_vk() -> Result<(), SnarkError> {
+        let rng = &mut thread_rng();
+        let (_, vk) = Groth16::<Bn254>::circuit_specific_setup(SudokuCircuit::<9>::default(), rng)?;
+        let source = solidity_verifier::<9>(&vk)?;
+
+        assert!(source.contains("uint8[81] calldata puzzle"));
+        let g2 = [&vk.beta_g2, &vk.gamma_g2, &vk.delta_g2]
+            .into_iter()
+            .flat_map(g2_words);
+        for word in g1_words(&vk.alpha_g1).into_iter().chain(g2) {
+            assert!(source.contains(&format!("0x{}", hex(&word))));
+        }
+        assert_eq!(vk.gamma_abc_g1.len(), 4);
+        for (i, p) in vk.gamma_abc_g1.iter().enumerate() {
+            let [x, y] = g1_words(p).map(|w| hex(&w));
+            assert!(source.contains(&format!("IC{i}_X = 0x{x};")));
+            assert!(source.contains(&format!("IC{i}_Y = 0x{y};")));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn vk_of_other_board_rejected() -> Result<(), SnarkError> {
+        let rng = &mut thread_rng();
+        let (_, vk) = Groth16::<Bn254>::circuit_specific_setup(SudokuCircuit::<9>::default(), rng)?;
+        assert!(matches!(
+            solidity_verifier::<4>(&vk),
+            Err(SnarkError::InputCountMismatch {
+                expected: 1,
+                found: 3
+            })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+//! Boards and proof helpers shared by the tests.
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_snark::SNARK;
+use rand::thread_rng;
+
+pub type Grid = [[u8; 4]; 4];
+
+pub const PUZZLE: Grid = [[1, 0, 0, 0], [0, 0, 1, 0], [0, 1, 0, 0], [0, 0, 0, 1]];
+pub const SOLUTION: Grid = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+
+pub const PUZZLE_9: [[u8; 9]; 9] = [
+    [0, 0, 0, 8, 6, 0, 2, 3, 0],
+    [7, 0, 5, 0, 0, 0, 9, 0, 8],
+    [0, 6, 0, 3, 0, 7, 0, 4, 0],
+    [0, 2, 0, 7, 0, 8, 0, 5, 0],
+    [0, 7, 8, 5, 0, 0, 0, 0, 0],
+    [4, 0, 0, 9, 0, 6, 0, 7, 0],
+    [3, 0, 9, 0, 5, 0, 7, 0, 2],
+    [0, 4, 0, 1, 0, 9, 0, 8, 0],
+    [5, 0, 7, 0, 8, 0, 0, 9, 4],
+];
+pub const SOLUTION_9: [[u8; 9]; 9] = [
+    [1, 9, 4, 8, 6, 5, 2, 3, 7],
+    [7, 3, 5, 4, 1, 2, 9, 6, 8],
+    [8, 6, 2, 3, 9, 7, 1, 4, 5],
+    [9, 2, 1, 7, 4, 8, 3, 5, 6],
+    [6, 7, 8, 5, 3, 1, 4, 2, 9],
+    [4, 5, 3, 9, 2, 6, 8, 7, 1],
+    [3, 8, 9, 6, 5, 4, 7, 1, 2],
+    [2, 4, 6, 1, 7, 9, 5, 8, 3],
+    [5, 1, 7, 2, 8, 3, 6, 9, 4],
+];
+
+/// Groth16 keys over BLS12-381 for circuits shaped like `blank`.
+pub fn setup<C: ConstraintSynthesizer<Fr>>(
+    blank: C,
+) -> (ProvingKey<Bls12_381>, VerifyingKey<Bls12_381>) {
+    Groth16::<Bls12_381>::circuit_specific_setup(blank, &mut thread_rng()).unwrap()
+}
+
+pub fn prove<C: ConstraintSynthesizer<Fr>>(
+    pk: &ProvingKey<Bls12_381>,
+    circuit: C,
+) -> Proof<Bls12_381> {
+    Groth16::<Bls12_381>::prove(pk, circuit, &mut thread_rng()).unwrap()
+}
+
+pub fn verifies(vk: &VerifyingKey<Bls12_381>, inputs: &[Fr], proof: &Proof<Bls12_381>) -> bool {
+    Groth16::<Bls12_381>::verify(vk, inputs, proof).unwrap()
+}
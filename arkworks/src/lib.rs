@@ -0,0 +1,26 @@
+pub mod aggregation;
+mod alloc;
+pub mod batch;
+pub mod ceremony;
+pub mod circuit;
+pub mod cmp;
+pub mod commitment;
+pub mod context;
+pub mod disclosure;
+pub mod encryption;
+pub mod error;
+pub mod evm;
+#[cfg(test)]
+mod fixtures;
+pub mod membership;
+pub mod multi;
+pub mod noir;
+pub mod nova;
+pub mod nullifier;
+pub mod poseidon;
+pub mod ptau;
+pub mod recursion;
+pub mod session;
+pub mod snark;
+mod transcript;
+pub mod validate;
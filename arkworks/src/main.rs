@@ -1,150 +1,263 @@
-mod alloc;
-mod circuit;
-//use circuit::*;
-mod cmp;
+use std::{fs, path::Path, path::PathBuf, process::ExitCode};
 
-fn main() {}
-//
-//#[derive(Debug)]
-//enum MainError {
-//    ProcessingError(String),
-//    ShouldHavePassed,
-//    ShouldHaveFailed,
-//}
-//
-//impl std::fmt::Display for MainError {
-//    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//        match self {
-//            MainError::ProcessingError(s) => write!(f, "Processing error: {}", s),
-//            MainError::ShouldHavePassed => write!(f, "Should have passed but failed"),
-//            MainError::ShouldHaveFailed => write!(f, "Should have failed but passed"),
-//        }
-//    }
-//}
-//
-//impl Error for MainError {}
-//
-//fn main() -> Result<(), MainError> {
-//
-//    // Check that it rejects a solution with a repeated number in a row.
-//    let solution = [
-//        [1, 1, 4, 8, 6, 5, 2, 3, 7],
-//        [7, 3, 5, 4, 1, 2, 9, 6, 8],
-//        [8, 6, 2, 3, 9, 7, 1, 4, 5],
-//        [9, 2, 1, 7, 4, 8, 3, 5, 6],
-//        [6, 7, 8, 5, 3, 1, 4, 2, 9],
-//        [4, 5, 3, 9, 2, 6, 8, 7, 1],
-//        [3, 8, 9, 6, 5, 4, 7, 1, 2],
-//        [2, 4, 6, 1, 7, 9, 5, 8, 3],
-//        [5, 1, 7, 2, 8, 3, 6, 9, 4],
-//    ];
-//
-//    let Ok(proof) = Groth16::<Bls12_381>::prove(
-//        &pk,
-//        SudokuCircuit {
-//            puzzle,
-//            solution: Some(solution),
-//        },
-//        rng,
-//    ) else {
-//        return Err(MainError::ProcessingError(
-//            "Failed to generate proof".to_string(),
-//        ));
-//    };
-//
-//    let mut serialized = vec![0; proof.serialized_size(ark_serialize::Compress::No)];
-//    let Ok(_) = proof.serialize_uncompressed(&mut serialized[..]) else {
-//        return Err(MainError::ProcessingError(
-//            "Failed to serialize proof".to_string(),
-//        ));
-//    };
-//
-//    // println!("proof: {:?}", proof.serialized_size());
-//    // println!("proof: {:?}", serialized);
-//
-//    let Ok(pr) =
-//        <Groth16<Bls12_381> as SNARK<BlsFr>>::Proof::deserialize_uncompressed(&serialized[..])
-//    else {
-//        return Err(MainError::ProcessingError(
-//            "Failed to deserialize proof".to_string(),
-//        ));
-//    };
-//    assert_eq!(proof, pr);
-//
-//    let mut serialized = vec![0; pk.serialized_size(ark_serialize::Compress::No)];
-//    let Ok(_) = pk.serialize_uncompressed(&mut serialized[..]) else {
-//        return Err(MainError::ProcessingError(
-//            "Failed to serialize proving key".to_string(),
-//        ));
-//    };
-//
-//    // println!("pk-size: {:?}", pk.serialized_size());
-//    // println!("pk: {:?}", serialized);
-//    let Ok(p) =
-//        <Groth16<Bls12_381> as SNARK<BlsFr>>::ProvingKey::deserialize_uncompressed(&serialized[..])
-//    else {
-//        return Err(MainError::ProcessingError(
-//            "Failed to deserialize proving key".to_string(),
-//        ));
-//    };
-//    assert_eq!(pk, p);
-//
-//    let mut serialized = vec![0; vk.serialized_size(ark_serialize::Compress::No)];
-//    let Ok(_) = vk.serialize_uncompressed(&mut serialized[..]) else {
-//        return Err(MainError::ProcessingError(
-//            "Failed to serialize verifying key".to_string(),
-//        ));
-//    };
-//
-//    // println!("vk-size: {:?}", vk.serialized_size());
-//    // println!("vk: {:?}", serialized);
-//
-//    let Ok(v) = <Groth16<Bls12_381> as SNARK<BlsFr>>::VerifyingKey::deserialize_uncompressed(
-//        &serialized[..],
-//    ) else {
-//        return Err(MainError::ProcessingError(
-//            "Failed to deserialize verifying key".to_string(),
-//        ));
-//    };
-//    assert_eq!(vk, v);
-//
-//    match Groth16::<Bls12_381>::verify(
-//        &vk,
-//        puzzle
-//            .as_flattened()
-//            .iter()
-//            .map(|cell| BlsFr::from(*cell))
-//            .collect::<Vec<_>>()
-//            .as_slice(),
-//        &proof,
-//    ) {
-//        Ok(false) => {}
-//        Ok(true) => return Err(MainError::ShouldHaveFailed),
-//        Err(_) => {
-//            return Err(MainError::ProcessingError(
-//                "Failed to verify proof".to_string(),
-//            ))
-//        }
-//    };
-//
-//    match Groth16::<Bls12_381>::verify(
-//        &v,
-//        puzzle
-//            .as_flattened()
-//            .iter()
-//            .map(|cell| BlsFr::from(*cell))
-//            .collect::<Vec<_>>()
-//            .as_slice(),
-//        &pr,
-//    ) {
-//        Ok(false) => {}
-//        Ok(true) => return Err(MainError::ShouldHaveFailed),
-//        Err(_) => {
-//            return Err(MainError::ProcessingError(
-//                "Failed to verify proof".to_string(),
-//            ))
-//        }
-//    }
-//
-//    Ok(())
-//}
+use clap::{Parser, Subcommand};
+use rand::thread_rng;
+use sudoku_snark::{
+    context::{self, Context},
+    error::{self, PipelineError},
+    noir,
+    ptau::PowersOfTau,
+    snark,
+    snark::{Backend, SetupMode},
+};
+
+/// Runs `$body` with `$n` a `const` equal to `$size`, for the board sizes
+/// the CLI is built for.
+macro_rules! with_board_size {
+    ($size:expr, $n:ident => $body:expr) => {
+        with_board_size!(@ $size, $n, $body, 1 2 3 4 5 6 7 8 9)
+    };
+    (@ $size:expr, $n:ident, $body:expr, $($k:literal)*) => {
+        match $size {
+            $($k => {
+                const $n: usize = $k;
+                $body
+            })*
+            size => Err(PipelineError::UnsupportedSize(size)),
+        }
+    };
+}
+
+/// Prove and verify Sudoku solutions, of the size of the Noir inputs.
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a proving key and a verifying key.
+    Setup {
+        /// Defaults to `groth16-bn254` with `--ptau`, and to
+        /// `groth16-bls12-381` otherwise.
+        #[arg(long, value_enum)]
+        backend: Option<Backend>,
+        #[arg(long)]
+        pk: PathBuf,
+        #[arg(long)]
+        vk: PathBuf,
+        /// The board is `size` x `size`.
+        #[arg(long, default_value_t = 9)]
+        size: usize,
+        /// `test` derives the keys from `--seed`, so anyone with the seed
+        /// can forge proofs.
+        #[arg(long, value_enum, default_value_t = SetupMode::Production)]
+        mode: SetupMode,
+        /// Seed for a reproducible `--mode test` setup.
+        #[arg(long)]
+        seed: Option<u64>,
+        /// A snarkjs `.ptau` file to take phase 1 from instead of fresh
+        /// randomness (`groth16-bn254` only).
+        #[arg(long)]
+        ptau: Option<PathBuf>,
+        /// Generate keys for proofs bound to a `--context`.
+        #[arg(long, conflicts_with = "ptau")]
+        context: bool,
+    },
+    /// Prove the solution in a Noir `Prover.toml`.
+    Prove {
+        #[arg(long)]
+        pk: PathBuf,
+        /// `Prover.toml` holding the flat `solution` and `puzzle` arrays.
+        #[arg(long)]
+        inputs: PathBuf,
+        #[arg(long)]
+        proof: PathBuf,
+        /// Hex of up to 32 bytes (a payee address, a nonce, a challenge) to
+        /// bind the proof to. Needs keys from `setup --context`.
+        #[arg(long, value_parser = context::parse)]
+        context: Option<Context>,
+    },
+    /// Verify a proof against the puzzle in a Noir `Verifier.toml`.
+    Verify {
+        #[arg(long)]
+        vk: PathBuf,
+        /// The `--vk` file was written by `prepare-vk`.
+        #[arg(long)]
+        prepared: bool,
+        /// `Verifier.toml` holding the public `puzzle`.
+        #[arg(long)]
+        inputs: PathBuf,
+        #[arg(long)]
+        proof: PathBuf,
+        /// The context the proof has to be bound to.
+        #[arg(long, value_parser = context::parse, conflicts_with = "prepared")]
+        context: Option<Context>,
+    },
+    /// Write an unlinkable copy of a proof that verifies the same way.
+    Rerandomize {
+        #[arg(long)]
+        vk: PathBuf,
+        #[arg(long)]
+        proof: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Write a prepared verifying key, which is faster to verify with.
+    PrepareVk {
+        #[arg(long)]
+        vk: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Write the `Verifier.toml` matching a `Prover.toml`.
+    ExportPublic {
+        #[arg(long)]
+        inputs: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, PipelineError> {
+    fs::read(path).map_err(|source| PipelineError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn write_file(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), PipelineError> {
+    fs::write(path, contents).map_err(|source| PipelineError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn read_toml(path: &Path) -> Result<String, PipelineError> {
+    fs::read_to_string(path).map_err(|source| PipelineError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", error::report(&err));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(command: Command) -> Result<(), PipelineError> {
+    let rng = &mut thread_rng();
+    match command {
+        Command::Setup {
+            backend,
+            pk,
+            vk,
+            size,
+            mode,
+            seed,
+            ptau,
+            context,
+        } => {
+            let backend = backend.unwrap_or(match ptau {
+                Some(_) => Backend::Groth16Bn254,
+                None => Backend::Groth16Bls12_381,
+            });
+            let srs = match ptau {
+                None => None,
+                Some(ptau) => Some(PowersOfTau::read(&read_file(&ptau)?)?),
+            };
+            let (proving_key, verifying_key) = with_board_size!(size, N => match &srs {
+                None if context => mode
+                    .rng(seed, rng)
+                    .and_then(|mut rng| backend.setup_with_context::<N, _>(&mut rng)),
+                None => backend.setup_in_mode::<N, _>(mode, seed, rng),
+                Some(srs) => mode.rng(seed, rng).and_then(|mut rng| {
+                    backend.setup_from_powers_of_tau::<N, _>(srs, &mut rng)
+                }),
+            }
+            .map_err(PipelineError::Setup))?;
+            write_file(&pk, proving_key)?;
+            write_file(&vk, verifying_key)?;
+        }
+        Command::Prove {
+            pk,
+            inputs,
+            proof,
+            context,
+        } => {
+            let inputs = read_toml(&inputs)?;
+            let proving_key = read_file(&pk)?;
+            let generated = with_board_size!(noir::board_size(&inputs)?, N => {
+                let inputs = noir::read_prover_toml::<N>(&inputs)?;
+                let (puzzle, solution) = (inputs.puzzle, inputs.solution);
+                match context {
+                    None => snark::prove_tagged(&proving_key, puzzle, solution, rng),
+                    Some(context) => snark::prove_with_context_tagged(
+                        &proving_key,
+                        puzzle,
+                        solution,
+                        &context,
+                        rng,
+                    ),
+                }
+                .map_err(PipelineError::Prove)
+            })?;
+            write_file(&proof, generated)?;
+        }
+        Command::Verify {
+            vk,
+            prepared,
+            inputs,
+            proof,
+            context,
+        } => {
+            let inputs = read_toml(&inputs)?;
+            let verifying_key = read_file(&vk)?;
+            let proof = read_file(&proof)?;
+            let accepted = with_board_size!(noir::board_size(&inputs)?, N => {
+                let puzzle = noir::read_verifier_toml::<N>(&inputs)?;
+                match context {
+                    Some(context) => {
+                        snark::verify_with_context_tagged(&verifying_key, &puzzle, &context, &proof)
+                    }
+                    None if prepared => {
+                        snark::verify_many_tagged(&verifying_key, &[(puzzle, proof)])
+                            .map(|accepted| accepted[0])
+                    }
+                    None => snark::verify_tagged(&verifying_key, &puzzle, &proof),
+                }
+                .map_err(PipelineError::Verify)
+            })?;
+            if !accepted {
+                return Err(PipelineError::Rejected);
+            }
+            println!("Proof accepted");
+        }
+        Command::Rerandomize { vk, proof, out } => {
+            let rerandomized =
+                snark::rerandomize_tagged(&read_file(&vk)?, &read_file(&proof)?, rng)
+                    .map_err(PipelineError::Rerandomize)?;
+            write_file(&out, rerandomized)?;
+        }
+        Command::PrepareVk { vk, out } => {
+            let prepared =
+                snark::prepare_tagged(&read_file(&vk)?).map_err(PipelineError::PrepareVk)?;
+            write_file(&out, prepared)?;
+        }
+        Command::ExportPublic { inputs, out } => {
+            let inputs = read_toml(&inputs)?;
+            let verifier_toml = with_board_size!(noir::board_size(&inputs)?, N => {
+                let inputs = noir::read_prover_toml::<N>(&inputs)?;
+                noir::write_verifier_toml(&inputs.puzzle).map_err(PipelineError::Input)
+            })?;
+            write_file(&out, verifier_toml)?;
+        }
+    }
+    Ok(())
+}
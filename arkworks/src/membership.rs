@@ -0,0 +1,237 @@
+//! Proofs that a private puzzle is from a published puzzle book.
+//!
+//! A puzzle book is published as the root of a Poseidon Merkle tree over the
+//! [`board_hash`]es of its puzzles, built natively with [`MerkleTree`].
+//! [`MembershipCircuit`] proves the [`SudokuCircuit`] statement for a private
+//! puzzle and that the puzzle is a leaf under the public root, given its
+//! [`MerklePath`]. The verifier learns that a puzzle of the book was solved,
+//! not which one.
+
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::{AllocVar, AllocationMode, Boolean, EqGadget};
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::circuit::SudokuCircuit;
+use crate::poseidon::{self, board_hash, board_hash_var};
+
+/// A Poseidon Merkle tree, padded with zero leaves to a power of two.
+pub struct MerkleTree<F> {
+    // From the leaves up to the root
+    levels: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField + Absorb> MerkleTree<F> {
+    /// The tree over `leaves`, which must not be empty.
+    pub fn new(config: &PoseidonConfig<F>, mut leaves: Vec<F>) -> Self {
+        assert!(!leaves.is_empty(), "a Merkle tree needs a leaf");
+        leaves.resize(leaves.len().next_power_of_two(), F::zero());
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let parents = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| poseidon::hash(config, pair))
+                .collect();
+            levels.push(parents);
+        }
+        MerkleTree { levels }
+    }
+
+    /// The tree over the [`board_hash`]es of `puzzles`, in order.
+    pub fn from_puzzles<const N: usize>(
+        config: &PoseidonConfig<F>,
+        puzzles: &[[[u8; N]; N]],
+    ) -> Self {
+        let leaves = puzzles
+            .iter()
+            .map(|puzzle| board_hash(config, puzzle))
+            .collect();
+        Self::new(config, leaves)
+    }
+
+    pub fn root(&self) -> F {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// The number of hashes from a leaf to the root.
+    pub fn depth(&self) -> usize {
+        self.levels.len() - 1
+    }
+
+    /// The path from leaf `index` to the root, if there is such a leaf.
+    pub fn path(&self, index: usize) -> Option<MerklePath<F>> {
+        if index >= self.levels[0].len() {
+            return None;
+        }
+        let siblings = self.levels[..self.depth()]
+            .iter()
+            .enumerate()
+            .map(|(height, level)| level[(index >> height) ^ 1])
+            .collect();
+        Some(MerklePath { index, siblings })
+    }
+}
+
+/// The siblings of a leaf and its ancestors, from the leaf up.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath<F> {
+    pub index: usize,
+    pub siblings: Vec<F>,
+}
+
+impl<F: PrimeField + Absorb> MerklePath<F> {
+    /// The root of the tree that has `leaf` at this path.
+    pub fn root(&self, config: &PoseidonConfig<F>, leaf: F) -> F {
+        self.siblings
+            .iter()
+            .enumerate()
+            .fold(leaf, |node, (height, &sibling)| {
+                if self.index >> height & 1 == 0 {
+                    poseidon::hash(config, &[node, sibling])
+                } else {
+                    poseidon::hash(config, &[sibling, node])
+                }
+            })
+    }
+}
+
+pub struct MembershipCircuit<const N: usize, F: PrimeField> {
+    pub config: PoseidonConfig<F>,
+    /// The depth of the trees whose roots the keys accept.
+    pub depth: usize,
+    // The puzzle is private, and so is its path
+    pub puzzle: Option<[[u8; N]; N]>,
+    pub path: Option<MerklePath<F>>,
+    // The solution is private
+    pub solution: Option<[[u8; N]; N]>,
+}
+
+impl<const N: usize, F: PrimeField + Absorb> MembershipCircuit<N, F> {
+    /// The public inputs a verifier passes alongside a proof for the book
+    /// with `root`.
+    pub fn public_inputs(root: F) -> Vec<F> {
+        vec![root]
+    }
+}
+
+impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for MembershipCircuit<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> ark_relations::r1cs::Result<()> {
+        let root = FpVar::new_input(ns!(cs, "root"), || match (&self.puzzle, &self.path) {
+            (Some(puzzle), Some(path)) => {
+                Ok(path.root(&self.config, board_hash(&self.config, puzzle)))
+            }
+            _ => Err(SynthesisError::AssignmentMissing),
+        })?;
+        let (puzzle, _) = SudokuCircuit::new_solved(
+            cs.clone(),
+            self.puzzle,
+            AllocationMode::Witness,
+            self.solution,
+        )?;
+
+        let leaf = board_hash_var(cs.clone(), &self.config, &puzzle.0)?;
+        root_var(cs, &self.config, self.depth, leaf, self.path.as_ref())?.enforce_equal(&root)
+    }
+}
+
+/// [`MerklePath::root`] in the circuit, for a private path of `depth`
+/// siblings.
+pub(crate) fn root_var<F: PrimeField + Absorb>(
+    cs: ConstraintSystemRef<F>,
+    config: &PoseidonConfig<F>,
+    depth: usize,
+    leaf: FpVar<F>,
+    path: Option<&MerklePath<F>>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let path = path.ok_or(SynthesisError::AssignmentMissing);
+    let mut node = leaf;
+    for height in 0..depth {
+        let is_right = Boolean::new_witness(ns!(cs, "is_right"), || {
+            path.map(|path| path.index >> height & 1 == 1)
+        })?;
+        let sibling = FpVar::new_witness(ns!(cs, "sibling"), || {
+            path.and_then(|path| {
+                path.siblings
+                    .get(height)
+                    .copied()
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })
+        })?;
+        let left = is_right.select(&sibling, &node)?;
+        let right = is_right.select(&node, &sibling)?;
+        node = poseidon::hash_var(cs.clone(), config, &[left, right])?;
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{prove, setup, verifies, Grid, SOLUTION};
+    use ark_bls12_381::Fr;
+
+    /// A book of 5 puzzles, with cell `i` blanked in puzzle `i`.
+    fn book() -> Vec<Grid> {
+        (0..5)
+            .map(|i| {
+                let mut puzzle = SOLUTION;
+                puzzle[i / 4][i % 4] = 0;
+                puzzle
+            })
+            .collect()
+    }
+
+    #[test]
+    fn paths_lead_to_root() {
+        let config = poseidon::config::<Fr>();
+        let book = book();
+        let tree = MerkleTree::from_puzzles(&config, &book);
+        assert_eq!(tree.depth(), 3);
+        for (index, puzzle) in book.iter().enumerate() {
+            let path = tree.path(index).unwrap();
+            assert_eq!(path.root(&config, board_hash(&config, puzzle)), tree.root());
+            assert_ne!(
+                path.root(&config, board_hash(&config, &SOLUTION)),
+                tree.root()
+            );
+        }
+        assert!(tree.path(8).is_none());
+        assert_eq!(
+            MerkleTree::new(&config, vec![Fr::from(7)]).root(),
+            Fr::from(7)
+        );
+    }
+
+    #[test]
+    fn proves_membership_privately() {
+        let config = poseidon::config::<Fr>();
+        let book = book();
+        let tree = MerkleTree::from_puzzles(&config, &book);
+        let circuit = |puzzle, path, solution| MembershipCircuit::<4, Fr> {
+            config: config.clone(),
+            depth: tree.depth(),
+            puzzle,
+            path,
+            solution,
+        };
+
+        let (pk, vk) = setup(circuit(None, None, None));
+        let proof = prove(&pk, circuit(Some(book[3]), tree.path(3), Some(SOLUTION)));
+        let inputs = MembershipCircuit::<4, Fr>::public_inputs(tree.root());
+        assert!(verifies(&vk, &inputs, &proof));
+        let other = MerkleTree::from_puzzles(&config, &book[..4]);
+        let inputs = MembershipCircuit::<4, Fr>::public_inputs(other.root());
+        assert!(!verifies(&vk, &inputs, &proof));
+
+        // A puzzle outside the book doesn't lead to the root, even with the
+        // path of one inside it.
+        let mut outside = SOLUTION;
+        outside[3][3] = 0;
+        let forged = prove(&pk, circuit(Some(outside), tree.path(3), Some(SOLUTION)));
+        let inputs = MembershipCircuit::<4, Fr>::public_inputs(tree.root());
+        assert!(!verifies(&vk, &inputs, &forged));
+    }
+}
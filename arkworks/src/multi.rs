@@ -0,0 +1,130 @@
+//! One Groth16 proof for a pack of `M` puzzles.
+//!
+//! [`MultiSudokuCircuit`] enforces the [`SudokuCircuit`] constraints for
+//! each of `M` puzzle/solution pairs. Each puzzle takes as many packed inputs
+//! as in [`SudokuCircuit::public_inputs`] (3 for a 9x9 puzzle over a 255-bit
+//! field), so the verifier's input work grows by a few elements per puzzle.
+
+use ark_ff::PrimeField;
+use ark_r1cs_std::prelude::AllocationMode;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef};
+
+use crate::circuit::SudokuCircuit;
+
+#[derive(Default)]
+pub struct MultiSudokuCircuit<const N: usize, const M: usize> {
+    // The puzzles are public
+    pub puzzles: Option<[[[u8; N]; N]; M]>,
+    // The solutions are private
+    pub solutions: Option<[[[u8; N]; N]; M]>,
+}
+
+impl<const N: usize, const M: usize> MultiSudokuCircuit<N, M> {
+    /// The public inputs a verifier passes alongside a proof for `puzzles`:
+    /// those of [`SudokuCircuit::public_inputs`] for each puzzle in turn.
+    pub fn public_inputs<ConstraintF: PrimeField>(puzzles: &[[[u8; N]; N]; M]) -> Vec<ConstraintF> {
+        puzzles
+            .iter()
+            .flat_map(SudokuCircuit::public_inputs)
+            .collect()
+    }
+}
+
+impl<const N: usize, const M: usize, ConstraintF: PrimeField> ConstraintSynthesizer<ConstraintF>
+    for MultiSudokuCircuit<N, M>
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<ConstraintF>,
+    ) -> ark_relations::r1cs::Result<()> {
+        for m in 0..M {
+            SudokuCircuit::new_solved(
+                cs.clone(),
+                self.puzzles.map(|puzzles| puzzles[m]),
+                AllocationMode::Input,
+                self.solutions.map(|solutions| solutions[m]),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{prove, setup, verifies, Grid, SOLUTION};
+    use ark_bls12_381::Fr;
+    use ark_relations::r1cs::{ConstraintSystem, SynthesisMode};
+
+    /// The puzzle left by blanking cell `blank`.
+    fn puzzle(blank: usize) -> Grid {
+        let mut puzzle = SOLUTION;
+        puzzle[blank / 4][blank % 4] = 0;
+        puzzle
+    }
+
+    fn check_pack<const M: usize>() {
+        let puzzles: [Grid; M] = std::array::from_fn(puzzle);
+        let solutions = [SOLUTION; M];
+
+        let (pk, vk) = setup(MultiSudokuCircuit::<4, M>::default());
+        let proof = prove(
+            &pk,
+            MultiSudokuCircuit {
+                puzzles: Some(puzzles),
+                solutions: Some(solutions),
+            },
+        );
+
+        let inputs = MultiSudokuCircuit::public_inputs::<Fr>(&puzzles);
+        assert_eq!(inputs.len(), M);
+        assert!(verifies(&vk, &inputs, &proof));
+
+        let mut wrong_puzzles = puzzles;
+        wrong_puzzles[M - 1] = puzzle(15);
+        let inputs = MultiSudokuCircuit::public_inputs::<Fr>(&wrong_puzzles);
+        assert!(!verifies(&vk, &inputs, &proof));
+    }
+
+    #[test]
+    fn proves_pack_of_1() {
+        check_pack::<1>();
+    }
+
+    #[test]
+    fn proves_pack_of_2() {
+        check_pack::<2>();
+    }
+
+    #[test]
+    fn proves_pack_of_8() {
+        check_pack::<8>();
+    }
+
+    #[test]
+    fn invalid_solution_in_pack_rejected() {
+        let mut solutions = [SOLUTION; 2];
+        solutions[1][0].swap(0, 1);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        MultiSudokuCircuit {
+            puzzles: Some([[[0; 4]; 4]; 2]),
+            solutions: Some(solutions),
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn constraints_scale_linearly() {
+        fn count<const M: usize>() -> usize {
+            let cs = ConstraintSystem::<Fr>::new_ref();
+            cs.set_mode(SynthesisMode::Setup);
+            MultiSudokuCircuit::<9, M>::default()
+                .generate_constraints(cs.clone())
+                .unwrap();
+            cs.num_constraints()
+        }
+        assert_eq!(count::<4>(), 4 * count::<1>());
+    }
+}
@@ -0,0 +1,306 @@
+//! Compatibility with the input files of the Noir implementation (`../noir`).
+//!
+//! Noir's `Prover.toml` stores `solution` and `puzzle` as flat arrays of
+//! `SIZE * SIZE` cells, and `Verifier.toml` stores the public `puzzle` as
+//! 32-byte hex field elements. Both are mapped to and from the `[[u8; N]; N]`
+//! grids used by [`SudokuCircuit`](crate::circuit::SudokuCircuit).
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+pub enum NoirTomlError {
+    Parse(toml::de::Error),
+    Write(toml::ser::Error),
+    WrongLength {
+        field: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// The `puzzle` does not have `N * N` cells for any `N`.
+    NotSquare {
+        found: usize,
+    },
+    InvalidCell {
+        field: &'static str,
+        index: usize,
+        value: String,
+    },
+}
+
+impl std::fmt::Display for NoirTomlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NoirTomlError::Parse(err) => write!(f, "Failed to parse TOML: {err}"),
+            NoirTomlError::Write(err) => write!(f, "Failed to write TOML: {err}"),
+            NoirTomlError::WrongLength {
+                field,
+                expected,
+                found,
+            } => write!(f, "`{field}` has {found} cells, expected {expected}"),
+            NoirTomlError::NotSquare { found } => {
+                write!(f, "`puzzle` has {found} cells, which is not a square board")
+            }
+            NoirTomlError::InvalidCell {
+                field,
+                index,
+                value,
+            } => write!(f, "`{field}[{index}]` is not a valid cell: {value}"),
+        }
+    }
+}
+
+impl std::error::Error for NoirTomlError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NoirTomlError::Parse(err) => Some(err),
+            NoirTomlError::Write(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// A cell as Noir writes it: either a plain integer or a field element
+/// string (`"0x..."` hex or decimal).
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Cell {
+    Int(i64),
+    Field(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProverToml {
+    solution: Vec<Cell>,
+    puzzle: Vec<Cell>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct VerifierToml {
+    puzzle: Vec<Cell>,
+}
+
+/// The contents of a Noir `Prover.toml`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProverInputs<const N: usize> {
+    pub puzzle: [[u8; N]; N],
+    pub solution: [[u8; N]; N],
+}
+
+fn parse_cell(field: &'static str, index: usize, cell: &Cell) -> Result<u8, NoirTomlError> {
+    let invalid = |value: String| NoirTomlError::InvalidCell {
+        field,
+        index,
+        value,
+    };
+    match cell {
+        Cell::Int(value) => u8::try_from(*value).map_err(|_| invalid(value.to_string())),
+        Cell::Field(value) => {
+            // `from_str_radix` takes a leading `+`, which Noir never writes.
+            let (digits, radix) = match value.strip_prefix("0x") {
+                Some(hex) => (hex, 16),
+                None => (value.as_str(), 10),
+            };
+            if digits.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+                return Err(invalid(value.clone()));
+            }
+            let digits = digits.trim_start_matches('0');
+            if digits.is_empty() {
+                Ok(0)
+            } else {
+                u8::from_str_radix(digits, radix).map_err(|_| invalid(value.clone()))
+            }
+        }
+    }
+}
+
+fn to_grid<const N: usize>(
+    field: &'static str,
+    cells: &[Cell],
+) -> Result<[[u8; N]; N], NoirTomlError> {
+    if cells.len() != N * N {
+        return Err(NoirTomlError::WrongLength {
+            field,
+            expected: N * N,
+            found: cells.len(),
+        });
+    }
+    let mut grid = [[0; N]; N];
+    for (index, cell) in cells.iter().enumerate() {
+        grid[index / N][index % N] = parse_cell(field, index, cell)?;
+    }
+    Ok(grid)
+}
+
+fn to_field_string(cell: u8) -> String {
+    format!("0x{cell:064x}")
+}
+
+/// The size `N` of the board in a Noir `Prover.toml` or `Verifier.toml`,
+/// from the number of cells of its `puzzle`.
+pub fn board_size(input: &str) -> Result<usize, NoirTomlError> {
+    let toml: VerifierToml = toml::from_str(input).map_err(NoirTomlError::Parse)?;
+    let found = toml.puzzle.len();
+    let size = found.isqrt();
+    if size == 0 || size * size != found {
+        return Err(NoirTomlError::NotSquare { found });
+    }
+    Ok(size)
+}
+
+/// Parses a Noir `Prover.toml`.
+pub fn read_prover_toml<const N: usize>(input: &str) -> Result<ProverInputs<N>, NoirTomlError> {
+    let toml: ProverToml = toml::from_str(input).map_err(NoirTomlError::Parse)?;
+    Ok(ProverInputs {
+        puzzle: to_grid("puzzle", &toml.puzzle)?,
+        solution: to_grid("solution", &toml.solution)?,
+    })
+}
+
+/// Writes a Noir `Prover.toml`, with cells as plain integers.
+pub fn write_prover_toml<const N: usize>(
+    inputs: &ProverInputs<N>,
+) -> Result<String, NoirTomlError> {
+    let flatten = |grid: &[[u8; N]; N]| {
+        grid.iter()
+            .flatten()
+            .map(|&cell| Cell::Int(cell.into()))
+            .collect()
+    };
+    toml::to_string(&ProverToml {
+        solution: flatten(&inputs.solution),
+        puzzle: flatten(&inputs.puzzle),
+    })
+    .map_err(NoirTomlError::Write)
+}
+
+/// Parses the public puzzle out of a Noir `Verifier.toml`.
+pub fn read_verifier_toml<const N: usize>(input: &str) -> Result<[[u8; N]; N], NoirTomlError> {
+    let toml: VerifierToml = toml::from_str(input).map_err(NoirTomlError::Parse)?;
+    to_grid("puzzle", &toml.puzzle)
+}
+
+/// Writes a Noir `Verifier.toml`, with cells as 32-byte hex field elements.
+pub fn write_verifier_toml<const N: usize>(puzzle: &[[u8; N]; N]) -> Result<String, NoirTomlError> {
+    toml::to_string(&VerifierToml {
+        puzzle: puzzle
+            .iter()
+            .flatten()
+            .map(|&cell| Cell::Field(to_field_string(cell)))
+            .collect(),
+    })
+    .map_err(NoirTomlError::Write)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{cells_per_input, SudokuCircuit};
+    use ark_bls12_381::Fr;
+    use ark_ff::AdditiveGroup;
+
+    // The inputs checked in under `../noir`.
+    const PROVER_TOML: &str = include_str!("../../noir/Prover.toml");
+    const VERIFIER_TOML: &str = include_str!("../../noir/Verifier.toml");
+
+    #[test]
+    fn reads_noir_inputs() {
+        let inputs = read_prover_toml::<2>(PROVER_TOML).unwrap();
+        assert_eq!(inputs.solution, [[1, 2], [1, 2]]);
+        assert_eq!(inputs.puzzle, [[1, 0], [0, 2]]);
+
+        let puzzle = read_verifier_toml::<2>(VERIFIER_TOML).unwrap();
+        assert_eq!(puzzle, inputs.puzzle);
+
+        assert_eq!(board_size(PROVER_TOML).unwrap(), 2);
+        assert_eq!(board_size(VERIFIER_TOML).unwrap(), 2);
+    }
+
+    #[test]
+    fn noir_inputs_pack_into_public_inputs() {
+        // Noir has one public input per cell, the field elements of
+        // `Verifier.toml`.
+        let toml: VerifierToml = toml::from_str(VERIFIER_TOML).unwrap();
+        let noir_inputs: Vec<Fr> = toml
+            .puzzle
+            .iter()
+            .map(|cell| match cell {
+                Cell::Field(hex) => Fr::from(u64::from_str_radix(&hex[2..], 16).unwrap()),
+                Cell::Int(_) => panic!("`Verifier.toml` holds field elements"),
+            })
+            .collect();
+
+        // `SudokuCircuit` packs the same cells as the bytes of fewer inputs.
+        let puzzle = read_verifier_toml::<2>(VERIFIER_TOML).unwrap();
+        let packed: Vec<Fr> = noir_inputs
+            .chunks(cells_per_input::<Fr>())
+            .map(|cells| {
+                cells
+                    .iter()
+                    .rev()
+                    .fold(Fr::ZERO, |packed, cell| packed * Fr::from(256) + cell)
+            })
+            .collect();
+        assert_eq!(noir_inputs.len(), 4);
+        assert_eq!(packed, SudokuCircuit::<2>::public_inputs::<Fr>(&puzzle));
+        assert_eq!(packed, [Fr::from(1 + (2 << 24))]);
+    }
+
+    #[test]
+    fn round_trips() {
+        let inputs = read_prover_toml::<2>(PROVER_TOML).unwrap();
+        let written = write_prover_toml(&inputs).unwrap();
+        assert_eq!(read_prover_toml::<2>(&written).unwrap(), inputs);
+
+        let written = write_verifier_toml(&inputs.puzzle).unwrap();
+        assert_eq!(read_verifier_toml::<2>(&written).unwrap(), inputs.puzzle);
+        assert_eq!(
+            toml::from_str::<toml::Table>(&written).unwrap(),
+            toml::from_str::<toml::Table>(VERIFIER_TOML).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_inputs() {
+        assert!(matches!(
+            read_prover_toml::<9>(PROVER_TOML),
+            Err(NoirTomlError::WrongLength {
+                expected: 81,
+                found: 4,
+                ..
+            })
+        ));
+        assert!(matches!(
+            read_verifier_toml::<1>(r#"puzzle = ["0x100"]"#),
+            Err(NoirTomlError::InvalidCell { index: 0, .. })
+        ));
+        assert!(matches!(
+            read_verifier_toml::<1>("puzzle = [-1]"),
+            Err(NoirTomlError::InvalidCell { index: 0, .. })
+        ));
+        for cell in ["+1", "0x", "0x+1", "", " 1"] {
+            assert!(
+                matches!(
+                    read_verifier_toml::<1>(&format!("puzzle = [{cell:?}]")),
+                    Err(NoirTomlError::InvalidCell { index: 0, .. })
+                ),
+                "{cell:?} accepted"
+            );
+        }
+        assert_eq!(
+            read_verifier_toml::<1>(r#"puzzle = ["0x09"]"#).unwrap(),
+            [[9]]
+        );
+        assert_eq!(
+            read_verifier_toml::<1>(r#"puzzle = ["007"]"#).unwrap(),
+            [[7]]
+        );
+        assert!(matches!(
+            board_size("puzzle = [1, 0, 0]"),
+            Err(NoirTomlError::NotSquare { found: 3 })
+        ));
+        assert!(matches!(
+            board_size("puzzle = []"),
+            Err(NoirTomlError::NotSquare { found: 0 })
+        ));
+    }
+}
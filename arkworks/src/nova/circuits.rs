@@ -0,0 +1,561 @@
+//! The augmented circuit each step is proven in, and the CycleFold circuit
+//! that does its BN254 arithmetic.
+//!
+//! Everything the augmented circuit hashes is encoded as in [`Encode`]:
+//! scalars of BN254 natively, elements of its base field as the limbs of
+//! their emulation, and points as affine coordinates with `(0, 0)` for the
+//! identity, which is on neither curve.
+
+use ark_bn254::{Fq, Fr};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ec::short_weierstrass::Projective;
+use ark_ec::CurveGroup;
+use ark_ff::{BigInteger, PrimeField};
+use ark_grumpkin::constraints::GVar;
+use ark_grumpkin::Projective as GrumpkinProjective;
+use ark_r1cs_std::convert::ToConstraintFieldGadget;
+use ark_r1cs_std::fields::emulated_fp::params::OptimizationType;
+use ark_r1cs_std::fields::emulated_fp::{AllocatedEmulatedFpVar, EmulatedFpVar};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::curves::short_weierstrass::ProjectiveVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use super::r1cs::Instance;
+use super::StepCircuit;
+use crate::poseidon;
+
+// Named through the curve's own config, as the coherence checker can't see
+// past `ark_bn254::G1Projective`'s projection to tell it from Grumpkin
+type G1Projective = Projective<ark_bn254::g1::Config>;
+type EmVar = EmulatedFpVar<Fq, Fr>;
+type G1Var = ProjectiveVar<ark_bn254::g1::Config, FpVar<Fq>>;
+
+/// Bits of the folding challenges.
+pub(crate) const CHALLENGE_BITS: usize = 128;
+
+/// The number of public inputs of [`CycleFoldCircuit`].
+pub(crate) const CYCLEFOLD_INPUTS: usize = 13;
+
+/// How the augmented circuit hashes a value.
+pub(crate) trait Encode {
+    fn encode(&self) -> Vec<Fr>;
+}
+
+impl Encode for Fr {
+    fn encode(&self) -> Vec<Fr> {
+        vec![*self]
+    }
+}
+
+impl Encode for Fq {
+    fn encode(&self) -> Vec<Fr> {
+        AllocatedEmulatedFpVar::<Fq, Fr>::get_limbs_representations(self, OptimizationType::Weight)
+            .expect("the limbs of a field element fit")
+    }
+}
+
+impl Encode for G1Projective {
+    fn encode(&self) -> Vec<Fr> {
+        let point = self.into_affine();
+        [point.x.encode(), point.y.encode()].concat()
+    }
+}
+
+impl Encode for GrumpkinProjective {
+    fn encode(&self) -> Vec<Fr> {
+        let point = self.into_affine();
+        vec![point.x, point.y]
+    }
+}
+
+impl<C: CurveGroup + Encode> Encode for Instance<C>
+where
+    C::ScalarField: Encode,
+{
+    fn encode(&self) -> Vec<Fr> {
+        let mut encoding = self.cm_e.encode();
+        encoding.extend(self.u.encode());
+        encoding.extend(self.cm_w.encode());
+        encoding.extend(self.x.iter().flat_map(Encode::encode));
+        encoding
+    }
+}
+
+/// The folding challenge for `values`, as the integer of its bits.
+pub(crate) fn challenge(config: &PoseidonConfig<Fr>, pp_hash: Fr, values: &[&dyn Encode]) -> u128 {
+    let mut inputs = vec![pp_hash];
+    inputs.extend(values.iter().flat_map(|value| value.encode()));
+    let bits = poseidon::hash(config, &inputs).into_bigint().to_bits_le();
+    bits[..CHALLENGE_BITS]
+        .iter()
+        .rev()
+        .fold(0, |acc, &bit| (acc << 1) | bit as u128)
+}
+
+/// The in-circuit counterpart of [`challenge`], as little-endian bits.
+fn challenge_var(
+    cs: ConstraintSystemRef<Fr>,
+    config: &PoseidonConfig<Fr>,
+    inputs: &[FpVar<Fr>],
+) -> Result<Vec<Boolean<Fr>>, SynthesisError> {
+    let mut bits = poseidon::hash_var(cs, config, inputs)?.to_bits_le()?;
+    bits.truncate(CHALLENGE_BITS);
+    Ok(bits)
+}
+
+/// `bits` as an emulated element of `Fq`.
+fn emulated_from_bits(
+    cs: ConstraintSystemRef<Fr>,
+    bits: &[Boolean<Fr>],
+) -> Result<EmVar, SynthesisError> {
+    let value = EmVar::new_witness(ns!(cs, "from_bits"), || {
+        let bits = bits
+            .iter()
+            .map(|bit| bit.value())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Fq::from(
+            bits.iter()
+                .rev()
+                .fold(0u128, |acc, &bit| (acc << 1) | bit as u128),
+        ))
+    })?;
+    let unset = Boolean::FALSE;
+    for (i, bit) in value.to_bits_le()?.iter().enumerate() {
+        bit.enforce_equal(bits.get(i).unwrap_or(&unset))?;
+    }
+    Ok(value)
+}
+
+/// A BN254 point in the augmented circuit, as emulated affine coordinates.
+/// It is only hashed and handed to the CycleFold circuit, which checks that
+/// it is on the curve.
+#[derive(Clone)]
+struct G1Coords {
+    x: EmVar,
+    y: EmVar,
+}
+
+impl G1Coords {
+    fn new_witness(
+        cs: ConstraintSystemRef<Fr>,
+        point: &G1Projective,
+    ) -> Result<Self, SynthesisError> {
+        let point = point.into_affine();
+        Ok(G1Coords {
+            x: EmVar::new_witness(ns!(cs, "x"), || Ok(point.x))?,
+            y: EmVar::new_witness(ns!(cs, "y"), || Ok(point.y))?,
+        })
+    }
+
+    fn zero() -> Self {
+        G1Coords {
+            x: EmVar::zero(),
+            y: EmVar::zero(),
+        }
+    }
+
+    fn encode(&self) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        Ok([self.x.to_constraint_field()?, self.y.to_constraint_field()?].concat())
+    }
+
+    fn select(cond: &Boolean<Fr>, a: &Self, b: &Self) -> Result<Self, SynthesisError> {
+        Ok(G1Coords {
+            x: cond.select(&a.x, &b.x)?,
+            y: cond.select(&a.y, &b.y)?,
+        })
+    }
+}
+
+fn encode_grumpkin(point: &GVar) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+    let point = point.to_affine()?;
+    Ok(vec![point.x, point.y])
+}
+
+/// An [`Instance`] of the augmented circuit.
+#[derive(Clone)]
+struct InstanceVar {
+    cm_e: G1Coords,
+    u: FpVar<Fr>,
+    cm_w: G1Coords,
+    x: Vec<FpVar<Fr>>,
+}
+
+impl InstanceVar {
+    fn new_witness(
+        cs: ConstraintSystemRef<Fr>,
+        instance: &Instance<G1Projective>,
+    ) -> Result<Self, SynthesisError> {
+        Ok(InstanceVar {
+            cm_e: G1Coords::new_witness(ns!(cs, "cm_e").cs(), &instance.cm_e)?,
+            u: FpVar::new_witness(ns!(cs, "u"), || Ok(instance.u))?,
+            cm_w: G1Coords::new_witness(ns!(cs, "cm_w").cs(), &instance.cm_w)?,
+            x: Vec::new_witness(ns!(cs, "x"), || Ok(instance.x.clone()))?,
+        })
+    }
+
+    /// A plain instance, of the previous step, with `u = 1` and `E = 0`.
+    fn new_fresh_witness(
+        cs: ConstraintSystemRef<Fr>,
+        instance: &Instance<G1Projective>,
+    ) -> Result<Self, SynthesisError> {
+        Ok(InstanceVar {
+            cm_e: G1Coords::zero(),
+            u: FpVar::one(),
+            cm_w: G1Coords::new_witness(ns!(cs, "cm_w").cs(), &instance.cm_w)?,
+            x: Vec::new_witness(ns!(cs, "x"), || Ok(instance.x.clone()))?,
+        })
+    }
+
+    fn dummy() -> Self {
+        InstanceVar {
+            cm_e: G1Coords::zero(),
+            u: FpVar::zero(),
+            cm_w: G1Coords::zero(),
+            x: vec![FpVar::zero()],
+        }
+    }
+
+    fn encode(&self) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        let mut encoding = self.cm_e.encode()?;
+        encoding.push(self.u.clone());
+        encoding.extend(self.cm_w.encode()?);
+        encoding.extend(self.x.iter().cloned());
+        Ok(encoding)
+    }
+
+    fn select(cond: &Boolean<Fr>, a: &Self, b: &Self) -> Result<Self, SynthesisError> {
+        Ok(InstanceVar {
+            cm_e: G1Coords::select(cond, &a.cm_e, &b.cm_e)?,
+            u: cond.select(&a.u, &b.u)?,
+            cm_w: G1Coords::select(cond, &a.cm_w, &b.cm_w)?,
+            x: a.x
+                .iter()
+                .zip(&b.x)
+                .map(|(a, b)| cond.select(a, b))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// An [`Instance`] of the CycleFold circuit, inside the augmented circuit.
+#[derive(Clone)]
+struct CycleFoldInstanceVar {
+    cm_e: GVar,
+    u: EmVar,
+    cm_w: GVar,
+    x: Vec<EmVar>,
+}
+
+impl CycleFoldInstanceVar {
+    fn new_witness(
+        cs: ConstraintSystemRef<Fr>,
+        instance: &Instance<GrumpkinProjective>,
+    ) -> Result<Self, SynthesisError> {
+        Ok(CycleFoldInstanceVar {
+            cm_e: GVar::new_witness(ns!(cs, "cm_e"), || Ok(instance.cm_e))?,
+            u: EmVar::new_witness(ns!(cs, "u"), || Ok(instance.u))?,
+            cm_w: GVar::new_witness(ns!(cs, "cm_w"), || Ok(instance.cm_w))?,
+            x: Vec::new_witness(ns!(cs, "x"), || Ok(instance.x.clone()))?,
+        })
+    }
+
+    fn dummy() -> Self {
+        CycleFoldInstanceVar {
+            cm_e: GVar::zero(),
+            u: EmVar::zero(),
+            cm_w: GVar::zero(),
+            x: vec![EmVar::zero(); CYCLEFOLD_INPUTS],
+        }
+    }
+
+    fn encode(&self) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        let mut encoding = encode_grumpkin(&self.cm_e)?;
+        encoding.extend(self.u.to_constraint_field()?);
+        encoding.extend(encode_grumpkin(&self.cm_w)?);
+        for x in &self.x {
+            encoding.extend(x.to_constraint_field()?);
+        }
+        Ok(encoding)
+    }
+
+    fn select(cond: &Boolean<Fr>, a: &Self, b: &Self) -> Result<Self, SynthesisError> {
+        Ok(CycleFoldInstanceVar {
+            cm_e: cond.select(&a.cm_e, &b.cm_e)?,
+            u: cond.select(&a.u, &b.u)?,
+            cm_w: cond.select(&a.cm_w, &b.cm_w)?,
+            x: a.x
+                .iter()
+                .zip(&b.x)
+                .map(|(a, b)| cond.select(a, b))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Proves `W + r·W_i` and `E + r·T`, the commitments of a Nova fold, over
+/// BN254's base field where their arithmetic is native.
+///
+/// The public inputs are `r` and then the coordinates of `W`, `W_i`,
+/// `W + r·W_i`, `E`, `T` and `E + r·T`.
+#[derive(Default)]
+pub(crate) struct CycleFoldCircuit {
+    pub(crate) r: u128,
+    // `W`, `W_i`, `E` and `T`
+    pub(crate) points: [G1Projective; 4],
+}
+
+impl CycleFoldCircuit {
+    pub(crate) fn public_inputs(&self) -> Vec<Fq> {
+        let [w, w_i, e, t] = self.points;
+        let mut inputs = vec![Fq::from(self.r)];
+        for point in [
+            w,
+            w_i,
+            w + w_i * Fr::from(self.r),
+            e,
+            t,
+            e + t * Fr::from(self.r),
+        ] {
+            let point = point.into_affine();
+            inputs.extend([point.x, point.y]);
+        }
+        inputs
+    }
+}
+
+impl ConstraintSynthesizer<Fq> for CycleFoldCircuit {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fq>) -> ark_relations::r1cs::Result<()> {
+        let inputs = Vec::<FpVar<Fq>>::new_input(ns!(cs, "inputs"), || Ok(self.public_inputs()))?;
+        let r = Vec::<Boolean<Fq>>::new_witness(ns!(cs, "r"), || {
+            Ok((0..CHALLENGE_BITS)
+                .map(|i| (self.r >> i) & 1 == 1)
+                .collect::<Vec<_>>())
+        })?;
+        let points = self
+            .points
+            .iter()
+            .map(|point| G1Var::new_witness(ns!(cs, "point"), || Ok(*point)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let [w, w_i, e, t] = &points[..] else {
+            unreachable!("there are four points")
+        };
+
+        Boolean::le_bits_to_fp(&r)?.enforce_equal(&inputs[0])?;
+        let w_next = w + w_i.scalar_mul_le(r.iter())?;
+        let e_next = e + t.scalar_mul_le(r.iter())?;
+        for (point, coords) in [w, w_i, &w_next, e, t, &e_next]
+            .into_iter()
+            .zip(inputs[1..].chunks(2))
+        {
+            let point = point.to_affine()?;
+            point.x.enforce_equal(&coords[0])?;
+            point.y.enforce_equal(&coords[1])?;
+        }
+        Ok(())
+    }
+}
+
+/// Step `i` of an IVC: checks that the incoming instance, of step `i - 1`,
+/// is for the state and running instances it is given, folds it in, and
+/// applies the step function.
+///
+/// Its only public input hashes `i + 1`, `z_0`, `z_{i+1}` and the folded
+/// running instances, which is what the next step checks.
+pub(crate) struct AugmentedCircuit<'a, S> {
+    pub(crate) config: &'a PoseidonConfig<Fr>,
+    pub(crate) pp_hash: Fr,
+    pub(crate) i: u64,
+    pub(crate) z_0: Vec<Fr>,
+    pub(crate) z_i: Vec<Fr>,
+    pub(crate) running: Instance<G1Projective>,
+    pub(crate) incoming: Instance<G1Projective>,
+    pub(crate) cm_t: G1Projective,
+    // `W` and `E` of `running` folded with `incoming`
+    pub(crate) folded: [G1Projective; 2],
+    pub(crate) cf_running: Instance<GrumpkinProjective>,
+    // The commitments of the CycleFold instance and its cross term
+    pub(crate) cf_cm_w: GrumpkinProjective,
+    pub(crate) cf_cm_t: GrumpkinProjective,
+    pub(crate) step: &'a S,
+}
+
+impl<'a, S: StepCircuit<Fr>> AugmentedCircuit<'a, S> {
+    /// The circuit of the first step from `z_0`, which folds nothing.
+    pub(crate) fn first(
+        config: &'a PoseidonConfig<Fr>,
+        pp_hash: Fr,
+        z_0: Vec<Fr>,
+        step: &'a S,
+    ) -> Self {
+        AugmentedCircuit {
+            config,
+            pp_hash,
+            i: 0,
+            z_i: z_0.clone(),
+            z_0,
+            running: Instance::dummy(1),
+            incoming: Instance::dummy(1),
+            cm_t: G1Projective::default(),
+            folded: Default::default(),
+            cf_running: Instance::dummy(CYCLEFOLD_INPUTS),
+            cf_cm_w: GrumpkinProjective::default(),
+            cf_cm_t: GrumpkinProjective::default(),
+            step,
+        }
+    }
+
+    /// Synthesizes the circuit and returns `z_{i+1}`.
+    pub(crate) fn synthesize(
+        self,
+        cs: ConstraintSystemRef<Fr>,
+    ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+        let pp_hash = FpVar::new_witness(ns!(cs, "pp_hash"), || Ok(self.pp_hash))?;
+        let i = FpVar::new_witness(ns!(cs, "i"), || Ok(Fr::from(self.i)))?;
+        let z_0 = Vec::new_witness(ns!(cs, "z_0"), || Ok(self.z_0.clone()))?;
+        let z_i = Vec::new_witness(ns!(cs, "z_i"), || Ok(self.z_i.clone()))?;
+        let running = InstanceVar::new_witness(ns!(cs, "running").cs(), &self.running)?;
+        let incoming = InstanceVar::new_fresh_witness(ns!(cs, "incoming").cs(), &self.incoming)?;
+        let cm_t = G1Coords::new_witness(ns!(cs, "cm_t").cs(), &self.cm_t)?;
+        let folded_cm_w = G1Coords::new_witness(ns!(cs, "folded_cm_w").cs(), &self.folded[0])?;
+        let folded_cm_e = G1Coords::new_witness(ns!(cs, "folded_cm_e").cs(), &self.folded[1])?;
+        let cf_running =
+            CycleFoldInstanceVar::new_witness(ns!(cs, "cf_running").cs(), &self.cf_running)?;
+        let cf_cm_w = GVar::new_witness(ns!(cs, "cf_cm_w"), || Ok(self.cf_cm_w))?;
+        let cf_cm_t = GVar::new_witness(ns!(cs, "cf_cm_t"), || Ok(self.cf_cm_t))?;
+
+        let is_first = i.is_zero()?;
+        let state = |i: &FpVar<Fr>,
+                     z: &[FpVar<Fr>],
+                     running: &InstanceVar,
+                     cf_running: &CycleFoldInstanceVar| {
+            let mut inputs = vec![pp_hash.clone(), i.clone()];
+            inputs.extend(z_0.iter().cloned());
+            inputs.extend(z.iter().cloned());
+            inputs.extend(running.encode()?);
+            inputs.extend(cf_running.encode()?);
+            poseidon::hash_var(cs.clone(), self.config, &inputs)
+        };
+        // The previous step's public input commits to where it left off.
+        state(&i, &z_i, &running, &cf_running)?
+            .conditional_enforce_equal(&incoming.x[0], &!&is_first)?;
+        z_i.conditional_enforce_equal(&z_0, &is_first)?;
+
+        // Nova's folding, but for the commitments.
+        let r_bits = challenge_var(
+            cs.clone(),
+            self.config,
+            &[
+                vec![pp_hash.clone()],
+                running.encode()?,
+                incoming.encode()?,
+                cm_t.encode()?,
+            ]
+            .concat(),
+        )?;
+        let r = Boolean::le_bits_to_fp(&r_bits)?;
+        let folded = InstanceVar {
+            cm_e: folded_cm_e,
+            u: &running.u + &r,
+            cm_w: folded_cm_w,
+            x: vec![&running.x[0] + &r * &incoming.x[0]],
+        };
+
+        // The CycleFold instance for the commitments, folded into its own
+        // running instance.
+        let mut cf_x = vec![emulated_from_bits(cs.clone(), &r_bits)?];
+        for point in [
+            &running.cm_w,
+            &incoming.cm_w,
+            &folded.cm_w,
+            &running.cm_e,
+            &cm_t,
+            &folded.cm_e,
+        ] {
+            cf_x.extend([point.x.clone(), point.y.clone()]);
+        }
+        let cf_incoming = CycleFoldInstanceVar {
+            cm_e: GVar::zero(),
+            u: EmVar::one(),
+            cm_w: cf_cm_w,
+            x: cf_x,
+        };
+        let cf_r_bits = challenge_var(
+            cs.clone(),
+            self.config,
+            &[
+                vec![pp_hash.clone()],
+                cf_running.encode()?,
+                cf_incoming.encode()?,
+                encode_grumpkin(&cf_cm_t)?,
+            ]
+            .concat(),
+        )?;
+        let cf_r = emulated_from_bits(cs.clone(), &cf_r_bits)?;
+        let cf_folded = CycleFoldInstanceVar {
+            cm_e: &cf_running.cm_e + cf_cm_t.scalar_mul_le(cf_r_bits.iter())?,
+            u: &cf_running.u + &cf_r,
+            cm_w: &cf_running.cm_w + cf_incoming.cm_w.scalar_mul_le(cf_r_bits.iter())?,
+            x: cf_running
+                .x
+                .iter()
+                .zip(&cf_incoming.x)
+                .map(|(a, b)| a + &cf_r * b)
+                .collect(),
+        };
+
+        // The first step has nothing to fold, and starts both running
+        // instances afresh.
+        let running = InstanceVar::select(&is_first, &InstanceVar::dummy(), &folded)?;
+        let cf_running =
+            CycleFoldInstanceVar::select(&is_first, &CycleFoldInstanceVar::dummy(), &cf_folded)?;
+
+        let z_next = self.step.generate_step_constraints(cs.clone(), &z_i)?;
+        let next = state(&(&i + FpVar::one()), &z_next, &running, &cf_running)?;
+        let x = FpVar::new_input(ns!(cs, "x"), || next.value())?;
+        next.enforce_equal(&x)?;
+        Ok(z_next)
+    }
+}
+
+impl<S: StepCircuit<Fr>> ConstraintSynthesizer<Fr> for AugmentedCircuit<'_, S> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> ark_relations::r1cs::Result<()> {
+        self.synthesize(cs)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::UniformRand;
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::thread_rng;
+
+    #[test]
+    fn native_and_circuit_encodings_match() {
+        let rng = &mut thread_rng();
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        for value in [Fq::from(0), Fq::from(1), -Fq::from(1), Fq::rand(rng)] {
+            let var = EmVar::new_witness(cs.clone(), || Ok(value)).unwrap();
+            assert_eq!(
+                var.to_constraint_field().unwrap().value().unwrap(),
+                value.encode()
+            );
+            let constant = EmVar::constant(value);
+            assert_eq!(
+                constant.to_constraint_field().unwrap().value().unwrap(),
+                value.encode()
+            );
+        }
+        for point in [GrumpkinProjective::default(), GrumpkinProjective::rand(rng)] {
+            let var = GVar::new_witness(cs.clone(), || Ok(point)).unwrap();
+            assert_eq!(
+                encode_grumpkin(&var).unwrap().value().unwrap(),
+                point.encode()
+            );
+        }
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,467 @@
+//! Incrementally verifiable computation by Nova folding, with CycleFold.
+//!
+//! A [`StepCircuit`] maps a state `z_i` to `z_{i+1}`. Each [`Ivc::prove_step`]
+//! runs it in an augmented circuit that also folds the previous step's R1CS
+//! instance into a running relaxed instance, as in Nova
+//! (<https://eprint.iacr.org/2021/370>). However many steps are taken, an
+//! [`IvcProof`] is a few instances and one folded witness.
+//!
+//! The augmented circuit is over the BN254 scalar field and its witnesses are
+//! committed to on BN254, whose arithmetic is over the base field. Folding
+//! the commitments is left to a small CycleFold circuit over that field
+//! (<https://eprint.iacr.org/2023/1192>), committed to on Grumpkin, and the
+//! augmented circuit folds the CycleFold instances in turn, where Grumpkin
+//! arithmetic is native. This follows Sonobe's Nova, which is not released
+//! for arkworks 0.5.
+//!
+//! Proofs are zero-knowledge: before the folded witnesses are revealed, both
+//! running instances are folded with random satisfied instances, which
+//! leaves the witnesses uniformly random.
+
+mod circuits;
+mod r1cs;
+
+use ark_bn254::{Fq, Fr, G1Projective};
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_ec::CurveGroup;
+use ark_ff::{One, PrimeField, UniformRand, Zero};
+use ark_grumpkin::Projective as GrumpkinProjective;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::R1CSVar;
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::Rng;
+use sha3::{Digest, Keccak256};
+
+use crate::poseidon;
+use circuits::{challenge, AugmentedCircuit, CycleFoldCircuit, Encode, CYCLEFOLD_INPUTS};
+use r1cs::{assignment, CommitmentKey, R1cs};
+pub use r1cs::{Instance, Witness};
+
+/// A step function `z_{i+1} = F(z_i)` on states of [`StepCircuit::ARITY`]
+/// elements, which allocates its own witnesses.
+pub trait StepCircuit<F: PrimeField> {
+    const ARITY: usize;
+
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        z: &[FpVar<F>],
+    ) -> Result<Vec<FpVar<F>>, SynthesisError>;
+}
+
+/// The circuits of an IVC and the keys their witnesses are committed with.
+pub struct IvcParams {
+    config: PoseidonConfig<Fr>,
+    arity: usize,
+    primary: R1cs<Fr>,
+    cyclefold: R1cs<Fq>,
+    key: CommitmentKey<G1Projective>,
+    cf_key: CommitmentKey<GrumpkinProjective>,
+    // Binds the hashed states to the circuits
+    pp_hash: Fr,
+}
+
+impl IvcParams {
+    /// Parameters for IVCs of steps shaped like `blank`, which needs no
+    /// assignment. `config` is what the folding hashes with.
+    pub fn new<S: StepCircuit<Fr>>(
+        config: PoseidonConfig<Fr>,
+        blank: &S,
+    ) -> Result<Self, SynthesisError> {
+        let cyclefold = R1cs::of(CycleFoldCircuit::default())?;
+        let primary = R1cs::of(AugmentedCircuit::first(
+            &config,
+            Fr::zero(),
+            vec![Fr::zero(); S::ARITY],
+            blank,
+        ))?;
+        let key = CommitmentKey::new(
+            b"sudoku-snark nova bn254",
+            primary.num_constraints().max(primary.num_witnesses),
+        );
+        let cf_key = CommitmentKey::new(
+            b"sudoku-snark nova grumpkin",
+            cyclefold.num_constraints().max(cyclefold.num_witnesses),
+        );
+        let mut hasher = Keccak256::new();
+        hasher.update((S::ARITY as u64).to_le_bytes());
+        primary.digest(&mut hasher);
+        cyclefold.digest(&mut hasher);
+        let pp_hash = Fr::from_le_bytes_mod_order(&hasher.finalize());
+        Ok(IvcParams {
+            config,
+            arity: S::ARITY,
+            primary,
+            cyclefold,
+            key,
+            cf_key,
+            pp_hash,
+        })
+    }
+
+    /// The Poseidon parameters the folding hashes with.
+    pub fn config(&self) -> &PoseidonConfig<Fr> {
+        &self.config
+    }
+
+    /// The hash of step `i`'s state, which its instance has as input.
+    fn state_hash(
+        &self,
+        i: u64,
+        z_0: &[Fr],
+        z_i: &[Fr],
+        running: &Instance<G1Projective>,
+        cf_running: &Instance<GrumpkinProjective>,
+    ) -> Fr {
+        let mut inputs = vec![self.pp_hash, Fr::from(i)];
+        inputs.extend(z_0);
+        inputs.extend(z_i);
+        inputs.extend(running.encode());
+        inputs.extend(cf_running.encode());
+        poseidon::hash(&self.config, &inputs)
+    }
+
+    fn challenge<C: CurveGroup + Encode>(
+        &self,
+        running: &Instance<C>,
+        incoming: &Instance<C>,
+        cm_t: &C,
+    ) -> u128
+    where
+        C::ScalarField: Encode,
+    {
+        challenge(&self.config, self.pp_hash, &[running, incoming, cm_t])
+    }
+
+    /// Folds `incoming` into `running`, returning the folded instance and
+    /// witness and the commitment to the cross term.
+    fn fold<C: CurveGroup + Encode, R: Rng>(
+        &self,
+        r1cs: &R1cs<C::ScalarField>,
+        key: &CommitmentKey<C>,
+        running: (&Instance<C>, &Witness<C::ScalarField>),
+        incoming: (&Instance<C>, &Witness<C::ScalarField>),
+        rng: &mut R,
+    ) -> (Instance<C>, Witness<C::ScalarField>, C)
+    where
+        C::ScalarField: Encode,
+    {
+        let t = r1cs.cross_term(running, incoming);
+        let r_t = C::ScalarField::rand(rng);
+        let cm_t = key.commit(&t, r_t);
+        let r = self.challenge(running.0, incoming.0, &cm_t).into();
+        (
+            running.0.fold(incoming.0, &cm_t, r),
+            running.1.fold(incoming.1, &t, r_t, r),
+            cm_t,
+        )
+    }
+}
+
+/// An IVC in progress, with the instances and witnesses of its last step.
+pub struct Ivc<'a> {
+    params: &'a IvcParams,
+    i: u64,
+    z_0: Vec<Fr>,
+    z_i: Vec<Fr>,
+    running: (Instance<G1Projective>, Witness<Fr>),
+    incoming: (Instance<G1Projective>, Witness<Fr>),
+    cf_running: (Instance<GrumpkinProjective>, Witness<Fq>),
+}
+
+impl<'a> Ivc<'a> {
+    pub fn new(params: &'a IvcParams, z_0: Vec<Fr>) -> Self {
+        assert_eq!(z_0.len(), params.arity, "the state has the step's arity");
+        Ivc {
+            params,
+            i: 0,
+            z_i: z_0.clone(),
+            z_0,
+            running: (Instance::dummy(1), Witness::dummy(&params.primary)),
+            incoming: (Instance::dummy(1), Witness::dummy(&params.primary)),
+            cf_running: (
+                Instance::dummy(CYCLEFOLD_INPUTS),
+                Witness::dummy(&params.cyclefold),
+            ),
+        }
+    }
+
+    /// The current state `z_i`.
+    pub fn state(&self) -> &[Fr] {
+        &self.z_i
+    }
+
+    /// Applies `step` to the state, or returns `Unsatisfiable` if its
+    /// witnesses don't satisfy it.
+    pub fn prove_step<S: StepCircuit<Fr>, R: Rng>(
+        &mut self,
+        step: &S,
+        rng: &mut R,
+    ) -> Result<(), SynthesisError> {
+        let params = self.params;
+        let mut circuit =
+            AugmentedCircuit::first(&params.config, params.pp_hash, self.z_0.clone(), step);
+        let (running, cf_running) = if self.i == 0 {
+            (self.running.clone(), self.cf_running.clone())
+        } else {
+            let (folded, folded_witness, cm_t) = params.fold(
+                &params.primary,
+                &params.key,
+                (&self.running.0, &self.running.1),
+                (&self.incoming.0, &self.incoming.1),
+                rng,
+            );
+
+            // The CycleFold instance of the commitments' folding.
+            let cf_circuit = CycleFoldCircuit {
+                r: params.challenge(&self.running.0, &self.incoming.0, &cm_t),
+                points: [
+                    self.running.0.cm_w,
+                    self.incoming.0.cm_w,
+                    self.running.0.cm_e,
+                    cm_t,
+                ],
+            };
+            let cs = ConstraintSystem::new_ref();
+            cf_circuit.generate_constraints(cs.clone())?;
+            let (cf_instance, cf_witness) =
+                Witness::fresh(&params.cyclefold, &params.cf_key, assignment(cs)?, rng);
+            let (cf_folded, cf_folded_witness, cf_cm_t) = params.fold(
+                &params.cyclefold,
+                &params.cf_key,
+                (&self.cf_running.0, &self.cf_running.1),
+                (&cf_instance, &cf_witness),
+                rng,
+            );
+
+            circuit.i = self.i;
+            circuit.z_i = self.z_i.clone();
+            circuit.running = self.running.0.clone();
+            circuit.incoming = self.incoming.0.clone();
+            circuit.cm_t = cm_t;
+            circuit.folded = [folded.cm_w, folded.cm_e];
+            circuit.cf_running = self.cf_running.0.clone();
+            circuit.cf_cm_w = cf_instance.cm_w;
+            circuit.cf_cm_t = cf_cm_t;
+            ((folded, folded_witness), (cf_folded, cf_folded_witness))
+        };
+
+        let cs = ConstraintSystem::new_ref();
+        let z_next = circuit.synthesize(cs.clone())?.value()?;
+        self.incoming = Witness::fresh(&params.primary, &params.key, assignment(cs)?, rng);
+        self.running = running;
+        self.cf_running = cf_running;
+        self.z_i = z_next;
+        self.i += 1;
+        Ok(())
+    }
+
+    /// A proof of the steps so far, or `None` before the first.
+    pub fn proof<R: Rng>(&self, rng: &mut R) -> Option<IvcProof> {
+        if self.i == 0 {
+            return None;
+        }
+        let params = self.params;
+        // What the verifier folds, and then a random instance to hide it.
+        let (folded, folded_witness, cm_t) = params.fold(
+            &params.primary,
+            &params.key,
+            (&self.running.0, &self.running.1),
+            (&self.incoming.0, &self.incoming.1),
+            rng,
+        );
+        let random = params.primary.random_instance(&params.key, rng);
+        let (_, witness, random_cm_t) = params.fold(
+            &params.primary,
+            &params.key,
+            (&folded, &folded_witness),
+            (&random.0, &random.1),
+            rng,
+        );
+        let cf_random = params.cyclefold.random_instance(&params.cf_key, rng);
+        let (_, cf_witness, cf_cm_t) = params.fold(
+            &params.cyclefold,
+            &params.cf_key,
+            (&self.cf_running.0, &self.cf_running.1),
+            (&cf_random.0, &cf_random.1),
+            rng,
+        );
+        Some(IvcProof {
+            steps: self.i,
+            z_0: self.z_0.clone(),
+            z_i: self.z_i.clone(),
+            running: self.running.0.clone(),
+            incoming: self.incoming.0.clone(),
+            cm_t,
+            random: random.0,
+            random_cm_t,
+            witness,
+            cf_running: self.cf_running.0.clone(),
+            cf_random: cf_random.0,
+            cf_cm_t,
+            cf_witness,
+        })
+    }
+}
+
+/// A proof that `steps` applications of a step function take `z_0` to
+/// `z_i`.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct IvcProof {
+    pub steps: u64,
+    pub z_0: Vec<Fr>,
+    pub z_i: Vec<Fr>,
+    // The running instance and the last step's, and the cross term of
+    // folding them
+    running: Instance<G1Projective>,
+    incoming: Instance<G1Projective>,
+    cm_t: G1Projective,
+    // A random instance the folded one is hidden by, and the folded witness
+    random: Instance<G1Projective>,
+    random_cm_t: G1Projective,
+    witness: Witness<Fr>,
+    // Likewise for the CycleFold running instance
+    cf_running: Instance<GrumpkinProjective>,
+    cf_random: Instance<GrumpkinProjective>,
+    cf_cm_t: GrumpkinProjective,
+    cf_witness: Witness<Fq>,
+}
+
+impl IvcProof {
+    pub fn verify(&self, params: &IvcParams) -> bool {
+        let well_formed = self.steps > 0
+            && self.z_0.len() == params.arity
+            && self.z_i.len() == params.arity
+            && [&self.running, &self.incoming, &self.random]
+                .iter()
+                .all(|instance| instance.x.len() == params.primary.num_inputs)
+            && [&self.cf_running, &self.cf_random]
+                .iter()
+                .all(|instance| instance.x.len() == params.cyclefold.num_inputs);
+        if !well_formed {
+            return false;
+        }
+
+        // The last step continues from the running instances.
+        let state = params.state_hash(
+            self.steps,
+            &self.z_0,
+            &self.z_i,
+            &self.running,
+            &self.cf_running,
+        );
+        if self.incoming.u != Fr::one()
+            || !self.incoming.cm_e.is_zero()
+            || self.incoming.x != [state]
+        {
+            return false;
+        }
+
+        let r = params.challenge(&self.running, &self.incoming, &self.cm_t);
+        let folded = self.running.fold(&self.incoming, &self.cm_t, r.into());
+        let r = params.challenge(&folded, &self.random, &self.random_cm_t);
+        let hidden = folded.fold(&self.random, &self.random_cm_t, r.into());
+        let r = params.challenge(&self.cf_running, &self.cf_random, &self.cf_cm_t);
+        let cf_hidden = self
+            .cf_running
+            .fold(&self.cf_random, &self.cf_cm_t, r.into());
+        params
+            .primary
+            .is_satisfied(&params.key, &hidden, &self.witness)
+            && params
+                .cyclefold
+                .is_satisfied(&params.cf_key, &cf_hidden, &self.cf_witness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_r1cs_std::prelude::*;
+    use rand::thread_rng;
+
+    /// `z_{i+1} = z_i^3 + z_i + 5`, as in Nova's examples.
+    struct Cubic;
+
+    impl StepCircuit<Fr> for Cubic {
+        const ARITY: usize = 1;
+
+        fn generate_step_constraints(
+            &self,
+            _: ConstraintSystemRef<Fr>,
+            z: &[FpVar<Fr>],
+        ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+            Ok(vec![
+                &z[0] * &z[0] * &z[0] + &z[0] + FpVar::constant(Fr::from(5)),
+            ])
+        }
+    }
+
+    /// A step that needs a witness: `z_{i+1} = z_i + y` for `y * y = 4`.
+    struct PlusRoot(Fr);
+
+    impl StepCircuit<Fr> for PlusRoot {
+        const ARITY: usize = 1;
+
+        fn generate_step_constraints(
+            &self,
+            cs: ConstraintSystemRef<Fr>,
+            z: &[FpVar<Fr>],
+        ) -> Result<Vec<FpVar<Fr>>, SynthesisError> {
+            let y = FpVar::new_witness(cs, || Ok(self.0))?;
+            (&y * &y).enforce_equal(&FpVar::constant(Fr::from(4)))?;
+            Ok(vec![&z[0] + y])
+        }
+    }
+
+    #[test]
+    fn steps_fold_into_a_proof() {
+        let rng = &mut thread_rng();
+        let params = IvcParams::new(poseidon::config(), &Cubic).unwrap();
+        let mut ivc = Ivc::new(&params, vec![Fr::from(3)]);
+        assert_eq!(ivc.proof(rng), None);
+        let mut z = Fr::from(3);
+        for _ in 0..3 {
+            ivc.prove_step(&Cubic, rng).unwrap();
+            z = z * z * z + z + Fr::from(5);
+            assert_eq!(ivc.state(), [z]);
+            assert!(ivc.proof(rng).unwrap().verify(&params));
+        }
+
+        let proof = ivc.proof(rng).unwrap();
+        // Claims about other states or step counts fail.
+        let mut other = proof.clone();
+        other.z_i = vec![z + Fr::one()];
+        assert!(!other.verify(&params));
+        let mut other = proof.clone();
+        other.z_0 = vec![Fr::from(4)];
+        assert!(!other.verify(&params));
+        let mut other = proof.clone();
+        other.steps = 2;
+        assert!(!other.verify(&params));
+        let mut other = proof.clone();
+        other.witness.w[0] += Fr::one();
+        assert!(!other.verify(&params));
+        let mut other = proof;
+        other.cf_witness.w[0] += Fq::one();
+        assert!(!other.verify(&params));
+    }
+
+    #[test]
+    fn unsatisfied_steps_are_errors() {
+        let rng = &mut thread_rng();
+        let params = IvcParams::new(poseidon::config(), &PlusRoot(Fr::zero())).unwrap();
+        let mut ivc = Ivc::new(&params, vec![Fr::zero()]);
+        ivc.prove_step(&PlusRoot(Fr::from(2)), rng).unwrap();
+        ivc.prove_step(&PlusRoot(-Fr::from(2)), rng).unwrap();
+        assert!(matches!(
+            ivc.prove_step(&PlusRoot(Fr::from(3)), rng),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+        assert_eq!(ivc.state(), [Fr::zero()]);
+        assert!(ivc.proof(rng).unwrap().verify(&params));
+    }
+}
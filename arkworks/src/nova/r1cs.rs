@@ -0,0 +1,293 @@
+//! Relaxed R1CS over Pedersen commitments, and Nova's folding of it.
+//!
+//! A relaxed instance `(E, u, W, x)` is satisfied by a witness `(e, w)` when
+//! `AZ ∘ BZ = u·CZ + e` for `Z = (u, x, w)` and `E`, `W` commit to `e`, `w`.
+//! A plain R1CS instance is the case `u = 1`, `e = 0`. Folding two instances
+//! with a challenge `r` gives one that is satisfied exactly when both were,
+//! but for a negligible fraction of the `r`.
+
+use ark_ec::CurveGroup;
+use ark_ff::{PrimeField, Zero};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, SynthesisMode,
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha3::{Digest, Keccak256};
+
+type Matrix<F> = Vec<Vec<(F, usize)>>;
+
+/// The constraint matrices of a circuit.
+pub(crate) struct R1cs<F: PrimeField> {
+    a: Matrix<F>,
+    b: Matrix<F>,
+    c: Matrix<F>,
+    // Not counting the constant one
+    pub(crate) num_inputs: usize,
+    pub(crate) num_witnesses: usize,
+}
+
+impl<F: PrimeField> R1cs<F> {
+    /// The matrices of `circuit`, which may have no assignment.
+    pub(crate) fn of(circuit: impl ConstraintSynthesizer<F>) -> Result<Self, SynthesisError> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        circuit.generate_constraints(cs.clone())?;
+        cs.finalize();
+        let matrices = cs.to_matrices().expect("setup mode keeps the matrices");
+        Ok(R1cs {
+            a: matrices.a,
+            b: matrices.b,
+            c: matrices.c,
+            num_inputs: matrices.num_instance_variables - 1,
+            num_witnesses: matrices.num_witness_variables,
+        })
+    }
+
+    pub(crate) fn num_constraints(&self) -> usize {
+        self.a.len()
+    }
+
+    /// `AZ`, `BZ` and `CZ` for `Z = (u, x, w)`.
+    fn products(&self, u: F, x: &[F], w: &[F]) -> [Vec<F>; 3] {
+        let z = [&[u], x, w].concat();
+        let eval = |matrix: &Matrix<F>| -> Vec<F> {
+            matrix
+                .iter()
+                .map(|row| row.iter().map(|(coeff, i)| *coeff * z[*i]).sum())
+                .collect()
+        };
+        [eval(&self.a), eval(&self.b), eval(&self.c)]
+    }
+
+    /// The cross term of folding `(u2, w2)` into `(u1, w1)`.
+    pub(crate) fn cross_term<C: CurveGroup<ScalarField = F>>(
+        &self,
+        (u1, w1): (&Instance<C>, &Witness<F>),
+        (u2, w2): (&Instance<C>, &Witness<F>),
+    ) -> Vec<F> {
+        let [a1, b1, c1] = self.products(u1.u, &u1.x, &w1.w);
+        let [a2, b2, c2] = self.products(u2.u, &u2.x, &w2.w);
+        (0..self.num_constraints())
+            .map(|i| a1[i] * b2[i] + a2[i] * b1[i] - u1.u * c2[i] - u2.u * c1[i])
+            .collect()
+    }
+
+    /// Whether `witness` satisfies `instance`.
+    pub(crate) fn is_satisfied<C: CurveGroup<ScalarField = F>>(
+        &self,
+        key: &CommitmentKey<C>,
+        instance: &Instance<C>,
+        witness: &Witness<F>,
+    ) -> bool {
+        if instance.x.len() != self.num_inputs
+            || witness.w.len() != self.num_witnesses
+            || witness.e.len() != self.num_constraints()
+        {
+            return false;
+        }
+        let [az, bz, cz] = self.products(instance.u, &instance.x, &witness.w);
+        (0..self.num_constraints()).all(|i| az[i] * bz[i] == instance.u * cz[i] + witness.e[i])
+            && key.commit(&witness.w, witness.r_w) == instance.cm_w
+            && key.commit(&witness.e, witness.r_e) == instance.cm_e
+    }
+
+    /// A random satisfied instance, to fold a witness with before it is
+    /// revealed.
+    pub(crate) fn random_instance<C: CurveGroup<ScalarField = F>, R: Rng>(
+        &self,
+        key: &CommitmentKey<C>,
+        rng: &mut R,
+    ) -> (Instance<C>, Witness<F>) {
+        let u = F::rand(rng);
+        let x: Vec<F> = (0..self.num_inputs).map(|_| F::rand(rng)).collect();
+        let w: Vec<F> = (0..self.num_witnesses).map(|_| F::rand(rng)).collect();
+        let [az, bz, cz] = self.products(u, &x, &w);
+        let e = (0..self.num_constraints())
+            .map(|i| az[i] * bz[i] - u * cz[i])
+            .collect();
+        let witness = Witness {
+            e,
+            r_e: F::rand(rng),
+            w,
+            r_w: F::rand(rng),
+        };
+        let instance = Instance {
+            cm_e: key.commit(&witness.e, witness.r_e),
+            u,
+            cm_w: key.commit(&witness.w, witness.r_w),
+            x,
+        };
+        (instance, witness)
+    }
+
+    /// Feeds the matrices to `hasher`, so that instances hashed along with
+    /// the digest are bound to this circuit.
+    pub(crate) fn digest(&self, hasher: &mut Keccak256) {
+        hasher.update((self.num_inputs as u64).to_le_bytes());
+        hasher.update((self.num_witnesses as u64).to_le_bytes());
+        for matrix in [&self.a, &self.b, &self.c] {
+            hasher.update((matrix.len() as u64).to_le_bytes());
+            for row in matrix {
+                hasher.update((row.len() as u64).to_le_bytes());
+                for (coeff, i) in row {
+                    let mut bytes = Vec::new();
+                    coeff
+                        .serialize_uncompressed(&mut bytes)
+                        .expect("writing to a Vec cannot fail");
+                    hasher.update(bytes);
+                    hasher.update((*i as u64).to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// The inputs and witnesses assigned in `cs`, a circuit synthesized in
+/// proving mode, or `Unsatisfiable` if they don't satisfy it.
+pub(crate) fn assignment<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+) -> Result<(Vec<F>, Vec<F>), SynthesisError> {
+    cs.finalize();
+    if !cs.is_satisfied()? {
+        return Err(SynthesisError::Unsatisfiable);
+    }
+    let cs = cs.borrow().expect("the constraint system is not `None`");
+    Ok((
+        cs.instance_assignment[1..].to_vec(),
+        cs.witness_assignment.clone(),
+    ))
+}
+
+/// Pedersen vector commitments with a blinding term.
+pub(crate) struct CommitmentKey<C: CurveGroup> {
+    generators: Vec<C::Affine>,
+    blinding: C::Affine,
+}
+
+impl<C: CurveGroup> CommitmentKey<C> {
+    /// A key for vectors of up to `len` elements. The generators are drawn
+    /// by hashing to the curve from a seed derived from `label`, so nobody
+    /// knows their discrete logarithms.
+    pub(crate) fn new(label: &[u8], len: usize) -> Self {
+        let mut rng = ChaCha20Rng::from_seed(Keccak256::digest(label).into());
+        let points: Vec<C> = (0..=len).map(|_| C::rand(&mut rng)).collect();
+        let mut generators = C::normalize_batch(&points);
+        let blinding = generators.pop().expect("there is at least one point");
+        CommitmentKey {
+            generators,
+            blinding,
+        }
+    }
+
+    pub(crate) fn commit(&self, values: &[C::ScalarField], blinding: C::ScalarField) -> C {
+        C::msm_unchecked(&self.generators[..values.len()], values) + self.blinding * blinding
+    }
+}
+
+/// A committed relaxed R1CS instance.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Instance<C: CurveGroup> {
+    pub cm_e: C,
+    pub u: C::ScalarField,
+    pub cm_w: C,
+    pub x: Vec<C::ScalarField>,
+}
+
+impl<C: CurveGroup> Instance<C> {
+    /// The instance folding starts from, satisfied by the zero witness.
+    pub(crate) fn dummy(num_inputs: usize) -> Self {
+        Instance {
+            cm_e: C::zero(),
+            u: C::ScalarField::zero(),
+            cm_w: C::zero(),
+            x: vec![C::ScalarField::zero(); num_inputs],
+        }
+    }
+
+    /// `self` folded with `other`, given the commitment `cm_t` to their
+    /// cross term.
+    pub(crate) fn fold(&self, other: &Self, cm_t: &C, r: C::ScalarField) -> Self {
+        Instance {
+            cm_e: self.cm_e + *cm_t * r + other.cm_e * (r * r),
+            u: self.u + r * other.u,
+            cm_w: self.cm_w + other.cm_w * r,
+            x: self
+                .x
+                .iter()
+                .zip(&other.x)
+                .map(|(a, b)| *a + r * b)
+                .collect(),
+        }
+    }
+}
+
+/// A witness of a relaxed R1CS instance, with the blinding of its
+/// commitments.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Witness<F: PrimeField> {
+    pub e: Vec<F>,
+    pub r_e: F,
+    pub w: Vec<F>,
+    pub r_w: F,
+}
+
+impl<F: PrimeField> Witness<F> {
+    /// The witness of [`Instance::dummy`].
+    pub(crate) fn dummy(r1cs: &R1cs<F>) -> Self {
+        Witness {
+            e: vec![F::zero(); r1cs.num_constraints()],
+            r_e: F::zero(),
+            w: vec![F::zero(); r1cs.num_witnesses],
+            r_w: F::zero(),
+        }
+    }
+
+    /// The witness of a plain R1CS assignment with inputs `x` and witnesses
+    /// `w`, and its instance.
+    pub(crate) fn fresh<C: CurveGroup<ScalarField = F>, R: Rng>(
+        r1cs: &R1cs<F>,
+        key: &CommitmentKey<C>,
+        (x, w): (Vec<F>, Vec<F>),
+        rng: &mut R,
+    ) -> (Instance<C>, Self) {
+        let r_w = F::rand(rng);
+        let instance = Instance {
+            cm_e: C::zero(),
+            u: F::one(),
+            cm_w: key.commit(&w, r_w),
+            x,
+        };
+        let witness = Witness {
+            e: vec![F::zero(); r1cs.num_constraints()],
+            r_e: F::zero(),
+            w,
+            r_w,
+        };
+        (instance, witness)
+    }
+
+    /// `self` folded with `other`, given their cross term `t` and the
+    /// blinding `r_t` of its commitment.
+    pub(crate) fn fold(&self, other: &Self, t: &[F], r_t: F, r: F) -> Self {
+        let r2 = r * r;
+        Witness {
+            e: self
+                .e
+                .iter()
+                .zip(t)
+                .zip(&other.e)
+                .map(|((e1, t), e2)| *e1 + r * t + r2 * e2)
+                .collect(),
+            r_e: self.r_e + r * r_t + r2 * other.r_e,
+            w: self
+                .w
+                .iter()
+                .zip(&other.w)
+                .map(|(a, b)| *a + r * b)
+                .collect(),
+            r_w: self.r_w + r * other.r_w,
+        }
+    }
+}
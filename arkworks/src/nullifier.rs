@@ -0,0 +1,390 @@
+//! Anonymous, one-time claims of a puzzle bounty.
+//!
+//! Solvers register the [`identity`] `Poseidon(secret)` of a private secret,
+//! and the registered identities are published as the root of a
+//! [`MerkleTree`](crate::membership::MerkleTree). [`NullifierCircuit`]
+//! proves the [`SudokuCircuit`] statement for the public puzzle, that the
+//! secret's identity is a leaf under the public root, and outputs the public
+//! nullifier `Poseidon(secret, puzzle_id)`, where `puzzle_id` is the
+//! [`board_hash`] of the puzzle. A secret always gets the same nullifier for
+//! a puzzle, so a [`NullifierRegistry`] can turn away its second claim, while
+//! nullifiers of different puzzles can't be linked to each other, to the
+//! secret or to the identity. The registry only takes proofs under the
+//! roots it was created with, as anyone can prove membership in a tree of
+//! their own.
+//!
+//! A solver can't claim more than once by making up fresh secrets, as only
+//! registered identities have a path to the root. That only holds as far as
+//! the registrar hands out one identity per solver, which is outside of the
+//! circuit.
+//!
+//! The proof is not bound to who gets paid, so whoever first submits a copy
+//! of it claims the bounty; see [`crate::context`] for binding proofs to a
+//! payee.
+
+use std::collections::HashSet;
+use std::hash::Hash;
+
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::{AllocVar, AllocationMode, EqGadget};
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+
+use crate::circuit::SudokuCircuit;
+use crate::membership::{root_var, MerklePath};
+use crate::poseidon::{self, board_hash, board_hash_var};
+use crate::snark::{ProofOf, ProvingBackend, SnarkError, VerifyingKeyOf};
+
+/// The identifier of `puzzle` that nullifiers are derived from.
+pub fn puzzle_id<const N: usize, F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    puzzle: &[[u8; N]; N],
+) -> F {
+    board_hash(config, puzzle)
+}
+
+/// The identity a solver with `secret` registers, as a leaf of the
+/// [`MerkleTree`](crate::membership::MerkleTree) of identities.
+pub fn identity<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, secret: F) -> F {
+    poseidon::hash(config, &[secret])
+}
+
+/// The nullifier of the solver with `secret` for `puzzle`.
+pub fn nullifier<const N: usize, F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    secret: F,
+    puzzle: &[[u8; N]; N],
+) -> F {
+    poseidon::hash(config, &[secret, puzzle_id(config, puzzle)])
+}
+
+pub struct NullifierCircuit<const N: usize, F: PrimeField> {
+    pub config: PoseidonConfig<F>,
+    /// The depth of the identity trees whose roots the keys accept.
+    pub depth: usize,
+    // The puzzle is public
+    pub puzzle: Option<[[u8; N]; N]>,
+    // The solution is private
+    pub solution: Option<[[u8; N]; N]>,
+    // The solver's secret is private, and so is the path of its identity
+    pub secret: Option<F>,
+    pub path: Option<MerklePath<F>>,
+}
+
+impl<const N: usize, F: PrimeField + Absorb> NullifierCircuit<N, F> {
+    /// The public inputs a verifier passes alongside a proof for `puzzle`
+    /// by a solver registered under `identities`: those of
+    /// [`SudokuCircuit::public_inputs`], then the root and the nullifier.
+    pub fn public_inputs(puzzle: &[[u8; N]; N], identities: F, nullifier: F) -> Vec<F> {
+        let mut inputs = SudokuCircuit::public_inputs(puzzle);
+        inputs.extend([identities, nullifier]);
+        inputs
+    }
+}
+
+impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for NullifierCircuit<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> ark_relations::r1cs::Result<()> {
+        let (puzzle, _) = SudokuCircuit::new_solved(
+            cs.clone(),
+            self.puzzle,
+            AllocationMode::Input,
+            self.solution,
+        )?;
+        let identities =
+            FpVar::new_input(ns!(cs, "identities"), || match (self.secret, &self.path) {
+                (Some(secret), Some(path)) => {
+                    Ok(path.root(&self.config, identity(&self.config, secret)))
+                }
+                _ => Err(SynthesisError::AssignmentMissing),
+            })?;
+        let expected =
+            FpVar::new_input(ns!(cs, "nullifier"), || match (self.puzzle, self.secret) {
+                (Some(puzzle), Some(secret)) => Ok(nullifier(&self.config, secret, &puzzle)),
+                _ => Err(SynthesisError::AssignmentMissing),
+            })?;
+        let secret = FpVar::new_witness(ns!(cs, "secret"), || {
+            self.secret.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        let leaf = poseidon::hash_var(cs.clone(), &self.config, std::slice::from_ref(&secret))?;
+        root_var(
+            cs.clone(),
+            &self.config,
+            self.depth,
+            leaf,
+            self.path.as_ref(),
+        )?
+        .enforce_equal(&identities)?;
+
+        let puzzle_id = board_hash_var(cs.clone(), &self.config, &puzzle.0)?;
+        poseidon::hash_var(cs, &self.config, &[secret, puzzle_id])?.enforce_equal(&expected)
+    }
+}
+
+#[derive(Debug)]
+pub enum NullifierError {
+    Snark(SnarkError),
+    /// The proof is for a root of identities the registry doesn't accept.
+    UnknownRoot,
+    /// The proof does not verify for the puzzle and nullifier.
+    Rejected,
+    /// The nullifier has claimed the puzzle before.
+    AlreadyClaimed,
+}
+
+impl std::fmt::Display for NullifierError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NullifierError::Snark(err) => write!(f, "SNARK error: {err}"),
+            NullifierError::UnknownRoot => write!(f, "Unknown root of identities"),
+            NullifierError::Rejected => write!(f, "Proof rejected"),
+            NullifierError::AlreadyClaimed => write!(f, "Nullifier already claimed"),
+        }
+    }
+}
+
+impl std::error::Error for NullifierError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NullifierError::Snark(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<SnarkError> for NullifierError {
+    fn from(err: SnarkError) -> Self {
+        NullifierError::Snark(err)
+    }
+}
+
+/// The nullifiers that have claimed a bounty so far, and the roots of the
+/// identity trees claims may be made under.
+pub struct NullifierRegistry<F> {
+    roots: HashSet<F>,
+    claimed: HashSet<F>,
+}
+
+impl<F: Hash + Eq> NullifierRegistry<F> {
+    /// A registry with no claims that accepts proofs under `roots`, the
+    /// published roots of registered identities.
+    pub fn new(roots: impl IntoIterator<Item = F>) -> Self {
+        NullifierRegistry {
+            roots: roots.into_iter().collect(),
+            claimed: HashSet::new(),
+        }
+    }
+
+    pub fn accepts_root(&self, root: &F) -> bool {
+        self.roots.contains(root)
+    }
+
+    pub fn is_claimed(&self, nullifier: &F) -> bool {
+        self.claimed.contains(nullifier)
+    }
+
+    /// Records `nullifier`, unless it was recorded before.
+    pub fn claim(&mut self, nullifier: F) -> Result<(), NullifierError> {
+        if !self.claimed.insert(nullifier) {
+            return Err(NullifierError::AlreadyClaimed);
+        }
+        Ok(())
+    }
+}
+
+impl<F: PrimeField + Absorb> NullifierRegistry<F> {
+    /// Checks the [`NullifierCircuit`] proof for `puzzle`, the root of the
+    /// registered `identities` and `nullifier` with `vk`, and
+    /// [`claim`](NullifierRegistry::claim)s the nullifier if it verifies
+    /// under an accepted root.
+    pub fn verify_and_claim<const N: usize, B: ProvingBackend<Field = F>>(
+        &mut self,
+        vk: &VerifyingKeyOf<B>,
+        puzzle: &[[u8; N]; N],
+        identities: F,
+        nullifier: F,
+        proof: &ProofOf<B>,
+    ) -> Result<(), NullifierError> {
+        if !self.accepts_root(&identities) {
+            return Err(NullifierError::UnknownRoot);
+        }
+        if self.is_claimed(&nullifier) {
+            return Err(NullifierError::AlreadyClaimed);
+        }
+        let inputs = NullifierCircuit::<N, F>::public_inputs(puzzle, identities, nullifier);
+        if !B::Snark::verify(vk, &inputs, proof).map_err(Into::into)? {
+            return Err(NullifierError::Rejected);
+        }
+        self.claim(nullifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{prove, setup, PUZZLE, SOLUTION};
+    use crate::membership::MerkleTree;
+    use crate::snark::{Gm17Bls12_381, Groth16Bls12_381};
+    use ark_bls12_381::Fr;
+    use ark_ff::UniformRand;
+    use rand::prelude::*;
+
+    #[test]
+    fn each_solver_claims_once() {
+        let rng = &mut thread_rng();
+        let config = poseidon::config::<Fr>();
+        let alice = Fr::rand(rng);
+        let bob = Fr::rand(rng);
+        let mallory = Fr::rand(rng);
+        let identities = MerkleTree::new(
+            &config,
+            vec![identity(&config, alice), identity(&config, bob)],
+        );
+        let circuit = |puzzle, solution, secret, path| NullifierCircuit::<4, Fr> {
+            config: config.clone(),
+            depth: identities.depth(),
+            puzzle,
+            solution,
+            secret,
+            path,
+        };
+        let (pk, vk) = setup(circuit(None, None, None, None));
+        let prove_as = |secret, index| {
+            prove(
+                &pk,
+                circuit(
+                    Some(PUZZLE),
+                    Some(SOLUTION),
+                    Some(secret),
+                    identities.path(index),
+                ),
+            )
+        };
+        let root = identities.root();
+
+        let mut registry = NullifierRegistry::new([root]);
+        let alice_nullifier = nullifier(&config, alice, &PUZZLE);
+        let proof = prove_as(alice, 0);
+        registry
+            .verify_and_claim::<4, Groth16Bls12_381>(&vk, &PUZZLE, root, alice_nullifier, &proof)
+            .unwrap();
+        // A fresh proof from the same secret has the same nullifier.
+        let again = prove_as(alice, 0);
+        assert!(matches!(
+            registry.verify_and_claim::<4, Groth16Bls12_381>(
+                &vk,
+                &PUZZLE,
+                root,
+                alice_nullifier,
+                &again
+            ),
+            Err(NullifierError::AlreadyClaimed)
+        ));
+        // Nor can a proof claim someone else's nullifier.
+        let bob_nullifier = nullifier(&config, bob, &PUZZLE);
+        assert!(matches!(
+            registry.verify_and_claim::<4, Groth16Bls12_381>(
+                &vk,
+                &PUZZLE,
+                root,
+                bob_nullifier,
+                &again
+            ),
+            Err(NullifierError::Rejected)
+        ));
+        registry
+            .verify_and_claim::<4, Groth16Bls12_381>(
+                &vk,
+                &PUZZLE,
+                root,
+                bob_nullifier,
+                &prove_as(bob, 1),
+            )
+            .unwrap();
+        assert!(registry.is_claimed(&bob_nullifier));
+
+        // An unregistered secret has no path to the root, even with the path
+        // of a registered identity.
+        let forged = prove_as(mallory, 1);
+        let mallory_nullifier = nullifier(&config, mallory, &PUZZLE);
+        assert!(matches!(
+            registry.verify_and_claim::<4, Groth16Bls12_381>(
+                &vk,
+                &PUZZLE,
+                root,
+                mallory_nullifier,
+                &forged
+            ),
+            Err(NullifierError::Rejected)
+        ));
+
+        let mut other_puzzle = PUZZLE;
+        other_puzzle[0][0] = 0;
+        assert_ne!(nullifier(&config, alice, &other_puzzle), alice_nullifier);
+    }
+
+    #[test]
+    fn self_made_identity_trees_rejected() {
+        type Snark = <Gm17Bls12_381 as ProvingBackend>::Snark;
+        let rng = &mut thread_rng();
+        let config = poseidon::config::<Fr>();
+        let registered = MerkleTree::new(
+            &config,
+            vec![
+                identity(&config, Fr::rand(rng)),
+                identity(&config, Fr::rand(rng)),
+            ],
+        );
+        // Mallory registers nowhere, but builds a tree of identities of the
+        // same depth, with a valid path for the secret.
+        let mallory = Fr::rand(rng);
+        let own = MerkleTree::new(
+            &config,
+            vec![identity(&config, mallory), identity(&config, Fr::rand(rng))],
+        );
+        let circuit = |puzzle, solution, secret, path| NullifierCircuit::<4, Fr> {
+            config: config.clone(),
+            depth: registered.depth(),
+            puzzle,
+            solution,
+            secret,
+            path,
+        };
+        let (pk, vk) = Snark::circuit_specific_setup(circuit(None, None, None, None), rng).unwrap();
+        let proof = Snark::prove(
+            &pk,
+            circuit(Some(PUZZLE), Some(SOLUTION), Some(mallory), own.path(0)),
+            rng,
+        )
+        .unwrap();
+        let mallory_nullifier = nullifier(&config, mallory, &PUZZLE);
+
+        let mut registry = NullifierRegistry::new([registered.root()]);
+        assert!(matches!(
+            registry.verify_and_claim::<4, Gm17Bls12_381>(
+                &vk,
+                &PUZZLE,
+                own.root(),
+                mallory_nullifier,
+                &proof
+            ),
+            Err(NullifierError::UnknownRoot)
+        ));
+        assert!(!registry.is_claimed(&mallory_nullifier));
+
+        // The proof itself is sound, it is only the root that isn't accepted.
+        NullifierRegistry::new([own.root()])
+            .verify_and_claim::<4, Gm17Bls12_381>(
+                &vk,
+                &PUZZLE,
+                own.root(),
+                mallory_nullifier,
+                &proof,
+            )
+            .unwrap();
+    }
+}
@@ -0,0 +1,130 @@
+//! Poseidon hashing with matching native and in-circuit implementations.
+//!
+//! The parameters are arkworks' rate-2 defaults optimized for constraints
+//! (`α = 17`, 8 full and 31 partial rounds), with the round constants and
+//! MDS matrix drawn from the Grain LFSR of the Poseidon paper. Deriving them
+//! takes a while, so callers build a [`PoseidonConfig`] once with [`config`]
+//! and pass it to every hash.
+
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::{
+    find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge,
+};
+use ark_crypto_primitives::sponge::{Absorb, CryptographicSponge};
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+
+const RATE: usize = 2;
+const ALPHA: u64 = 17;
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 31;
+
+pub fn config<F: PrimeField>() -> PoseidonConfig<F> {
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE.into(),
+        RATE,
+        FULL_ROUNDS as u64,
+        PARTIAL_ROUNDS as u64,
+        0,
+    );
+    PoseidonConfig::new(FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA, mds, ark, RATE, 1)
+}
+
+/// Absorbs `inputs` into a fresh sponge and squeezes one element.
+pub fn hash<F: PrimeField + Absorb>(config: &PoseidonConfig<F>, inputs: &[F]) -> F {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&inputs);
+    sponge.squeeze_field_elements(1)[0]
+}
+
+/// The in-circuit counterpart of [`hash`].
+pub fn hash_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    config: &PoseidonConfig<F>,
+    inputs: &[FpVar<F>],
+) -> Result<FpVar<F>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(cs, config);
+    sponge.absorb(&inputs)?;
+    Ok(sponge.squeeze_field_elements(1)?.remove(0))
+}
+
+/// Absorbs `inputs` into a fresh sponge and squeezes `count` elements. The
+/// sponge doesn't pad, so the first one equals [`hash`] of `inputs` with any
+/// trailing zeros.
+pub fn squeeze<F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    inputs: &[F],
+    count: usize,
+) -> Vec<F> {
+    let mut sponge = PoseidonSponge::new(config);
+    sponge.absorb(&inputs);
+    sponge.squeeze_field_elements(count)
+}
+
+/// The in-circuit counterpart of [`squeeze`].
+pub fn squeeze_var<F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    config: &PoseidonConfig<F>,
+    inputs: &[FpVar<F>],
+    count: usize,
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    let mut sponge = PoseidonSpongeVar::new(cs, config);
+    sponge.absorb(&inputs)?;
+    sponge.squeeze_field_elements(count)
+}
+
+/// The Poseidon hash of the cells of `board`. It hides nothing, so it only
+/// commits to boards that are public or drawn from a public set.
+pub fn board_hash<const N: usize, F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    board: &[[u8; N]; N],
+) -> F {
+    let cells: Vec<F> = board.iter().flatten().map(|&cell| F::from(cell)).collect();
+    hash(config, &cells)
+}
+
+/// The in-circuit counterpart of [`board_hash`].
+pub(crate) fn board_hash_var<const N: usize, F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    config: &PoseidonConfig<F>,
+    board: &[[UInt8<F>; N]; N],
+) -> Result<FpVar<F>, SynthesisError> {
+    hash_var(cs, config, &cells_var(board)?)
+}
+
+/// The cells of `board` as field elements, in row-major order.
+pub(crate) fn cells_var<const N: usize, F: PrimeField>(
+    board: &[[UInt8<F>; N]; N],
+) -> Result<Vec<FpVar<F>>, SynthesisError> {
+    board.iter().flatten().map(|cell| cell.to_fp()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_381::Fr;
+    use ark_r1cs_std::prelude::{AllocVar, EqGadget};
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+
+    #[test]
+    fn native_and_circuit_hashes_match() {
+        let config = config::<Fr>();
+        let inputs: Vec<_> = (0..5u64).map(Fr::from).collect();
+        let cs = ConstraintSystem::new_ref();
+        let input_vars = Vec::new_witness(cs.clone(), || Ok(inputs.clone())).unwrap();
+        let digest = hash_var(cs.clone(), &config, &input_vars).unwrap();
+        assert_eq!(digest.value().unwrap(), hash(&config, &inputs));
+        assert_ne!(hash(&config, &inputs[1..]), hash(&config, &inputs));
+        let squeezed = squeeze_var(cs.clone(), &config, &input_vars, 3).unwrap();
+        assert_eq!(squeezed.value().unwrap(), squeeze(&config, &inputs, 3),);
+        assert_eq!(squeeze(&config, &inputs, 3)[0], hash(&config, &inputs));
+
+        let expected = FpVar::new_input(cs.clone(), || Ok(hash(&config, &inputs))).unwrap();
+        digest.enforce_equal(&expected).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,545 @@
+//! Powers of tau from snarkjs `.ptau` files as the phase-1 SRS of a Groth16
+//! setup over BN254, e.g. from the Perpetual Powers of Tau ceremony or
+//! `snarkjs powersoftau` as in `../circom/Makefile`.
+//!
+//! A `.ptau` file is a list of sections, each a `u32` id and a `u64` length
+//! after the `ptau` magic, a `u32` version and a `u32` section count. The
+//! header section holds the field size, the base field modulus and the power
+//! `p`; the next ones hold `[τ^i]₁` for `i < 2^{p+1} - 1`, `[τ^i]₂`,
+//! `[α·τ^i]₁` and `[β·τ^i]₁` for `i < 2^p`, and `[β]₂`. Coordinates are
+//! little-endian in Montgomery form, the same as arkworks keeps them. The
+//! Lagrange sections added by `snarkjs powersoftau prepare phase2` are
+//! ignored, as the keys are built over arkworks' own evaluation domain.
+//!
+//! [`PowersOfTau::read`] checks that the points are consistent powers, so
+//! they can be turned into keys with `γ = δ = 1`. Anyone can forge proofs
+//! against those, so they only ever leave this crate as the initial
+//! [`Parameters`] of a phase-2 ceremony; [`setup`] runs that ceremony with a
+//! single local contribution.
+
+use std::ops::{AddAssign, Mul};
+
+use ark_bn254::{Bn254, Fq, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, VariableBaseMSM};
+use ark_ff::{BigInt, PrimeField, UniformRand, Zero};
+use ark_groth16::{ProvingKey, VerifyingKey};
+use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, OptimizationGoal, SynthesisError, SynthesisMode,
+};
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha3::{Digest, Keccak256};
+
+use crate::ceremony::Parameters;
+use crate::circuit::SudokuCircuit;
+
+const MAGIC: &[u8; 4] = b"ptau";
+const N8: usize = 32;
+
+const HEADER: u32 = 1;
+const TAU_G1: u32 = 2;
+const TAU_G2: u32 = 3;
+const ALPHA_TAU_G1: u32 = 4;
+const BETA_TAU_G1: u32 = 5;
+const BETA_G2: u32 = 6;
+
+/// The phase-1 SRS read from a `.ptau` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PowersOfTau {
+    power: u32,
+    tau_g1: Vec<G1Affine>,
+    tau_g2: Vec<G2Affine>,
+    alpha_tau_g1: Vec<G1Affine>,
+    beta_tau_g1: Vec<G1Affine>,
+    beta_g2: G2Affine,
+}
+
+#[derive(Debug)]
+pub enum PtauError {
+    /// The file ended before a section or value it announced.
+    Truncated,
+    BadMagic,
+    MissingSection(u32),
+    /// A section is not as long as the header says it should be.
+    SectionLength(u32),
+    /// The file is for another curve than BN254.
+    UnsupportedCurve,
+    InvalidPoint,
+    /// The points are not powers of one `τ`, or `α` and `β` do not match.
+    InconsistentPowers,
+    /// `τ = 1`, as in a file from `snarkjs powersoftau new` nobody has
+    /// contributed to.
+    NoContributions,
+    /// The circuit needs more powers than the file has.
+    TooSmall {
+        power: u32,
+        needed: u32,
+    },
+    Synthesis(SynthesisError),
+}
+
+impl std::fmt::Display for PtauError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PtauError::Truncated => write!(f, "Powers of tau file is truncated"),
+            PtauError::BadMagic => write!(f, "Not a powers of tau file"),
+            PtauError::MissingSection(id) => write!(f, "Missing section {id}"),
+            PtauError::SectionLength(id) => write!(f, "Section {id} has the wrong length"),
+            PtauError::UnsupportedCurve => write!(f, "Powers of tau are not over BN254"),
+            PtauError::InvalidPoint => write!(f, "Invalid curve point"),
+            PtauError::InconsistentPowers => write!(f, "Points are not consistent powers of tau"),
+            PtauError::NoContributions => write!(f, "Powers of tau have no contributions"),
+            PtauError::TooSmall { power, needed } => {
+                write!(f, "Circuit needs 2^{needed} powers of tau, got 2^{power}")
+            }
+            PtauError::Synthesis(err) => write!(f, "Synthesis error: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for PtauError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PtauError::Synthesis(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<SynthesisError> for PtauError {
+    fn from(err: SynthesisError) -> Self {
+        PtauError::Synthesis(err)
+    }
+}
+
+impl PowersOfTau {
+    /// Parses and checks the contents of a `.ptau` file.
+    pub fn read(bytes: &[u8]) -> Result<Self, PtauError> {
+        let mut reader = Reader(bytes);
+        if reader.take(4)? != MAGIC {
+            return Err(PtauError::BadMagic);
+        }
+        let _version = reader.u32()?;
+        let num_sections = reader.u32()?;
+        let mut sections = Vec::new();
+        for _ in 0..num_sections {
+            let id = reader.u32()?;
+            let len = usize::try_from(reader.u64()?).map_err(|_| PtauError::Truncated)?;
+            sections.push((id, reader.take(len)?));
+        }
+        let section = |id| {
+            sections
+                .iter()
+                .find(|(found, _)| *found == id)
+                .map(|(_, contents)| Reader(contents))
+                .ok_or(PtauError::MissingSection(id))
+        };
+
+        let mut header = section(HEADER)?;
+        if header.u32()? as usize != N8 || read_biguint(header.take(N8)?) != Fq::MODULUS {
+            return Err(PtauError::UnsupportedCurve);
+        }
+        let power = header.u32()?;
+        if power == 0 || power >= 32 {
+            return Err(PtauError::SectionLength(HEADER));
+        }
+        let powers = 1 << power;
+
+        let srs = PowersOfTau {
+            power,
+            tau_g1: section(TAU_G1)?.points(TAU_G1, 2 * powers - 1, read_g1)?,
+            tau_g2: section(TAU_G2)?.points(TAU_G2, powers, read_g2)?,
+            alpha_tau_g1: section(ALPHA_TAU_G1)?.points(ALPHA_TAU_G1, powers, read_g1)?,
+            beta_tau_g1: section(BETA_TAU_G1)?.points(BETA_TAU_G1, powers, read_g1)?,
+            beta_g2: section(BETA_G2)?.points(BETA_G2, 1, read_g2)?[0],
+        };
+        srs.check(Keccak256::digest(bytes).into())?;
+        Ok(srs)
+    }
+
+    /// The file holds `2^power` powers of tau in G2.
+    pub fn power(&self) -> u32 {
+        self.power
+    }
+
+    /// Checks random combinations of the points, weighted from `seed`.
+    fn check(&self, seed: [u8; 32]) -> Result<(), PtauError> {
+        let (g1, g2) = (self.tau_g1[0], self.tau_g2[0]);
+        if g1 != G1Affine::generator() || g2 != G2Affine::generator() {
+            return Err(PtauError::InconsistentPowers);
+        }
+        if self.tau_g1[1] == g1 {
+            return Err(PtauError::NoContributions);
+        }
+
+        let mut rng = ChaCha20Rng::from_seed(seed);
+        // `∑ ρ_i·P_i` and `∑ ρ_i·P_{i+1}`, which differ by a factor of `τ`
+        // if `P` are consecutive powers.
+        fn shifted<A: AffineRepr>(points: &[A], rng: &mut ChaCha20Rng) -> (A, A) {
+            let weights: Vec<A::ScalarField> = (1..points.len())
+                .map(|_| A::ScalarField::rand(rng))
+                .collect();
+            let low = A::Group::msm_unchecked(&points[..points.len() - 1], &weights);
+            let high = A::Group::msm_unchecked(&points[1..], &weights);
+            (low.into_affine(), high.into_affine())
+        }
+        let (tau_g1, tau_g2) = (self.tau_g1[1], self.tau_g2[1]);
+        let same_ratio = |a: (G1Affine, G1Affine), b: (G2Affine, G2Affine)| {
+            Bn254::pairing(a.0, b.1) == Bn254::pairing(a.1, b.0)
+        };
+
+        let consistent = same_ratio(shifted(&self.tau_g1, &mut rng), (g2, tau_g2))
+            && same_ratio((g1, tau_g1), shifted(&self.tau_g2, &mut rng))
+            && same_ratio(shifted(&self.alpha_tau_g1, &mut rng), (g2, tau_g2))
+            && same_ratio(shifted(&self.beta_tau_g1, &mut rng), (g2, tau_g2))
+            && same_ratio((g1, self.beta_tau_g1[0]), (g2, self.beta_g2));
+        if !consistent {
+            return Err(PtauError::InconsistentPowers);
+        }
+        Ok(())
+    }
+
+    /// Groth16 keys for `N`x`N` boards with `γ = δ = 1`, built like
+    /// `ark_groth16`'s generator but from the powers instead of a known `τ`.
+    pub(crate) fn proving_key<const N: usize>(&self) -> Result<ProvingKey<Bn254>, PtauError> {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        cs.set_optimization_goal(OptimizationGoal::Constraints);
+        cs.set_mode(SynthesisMode::Setup);
+        SudokuCircuit::<N>::default().generate_constraints(cs.clone())?;
+        cs.finalize();
+        let matrices = cs.to_matrices().expect("setup mode keeps the matrices");
+
+        let num_instance = cs.num_instance_variables();
+        let num_variables = num_instance + cs.num_witness_variables();
+        let num_constraints = cs.num_constraints();
+        let domain = GeneralEvaluationDomain::<Fr>::new(num_constraints + num_instance)
+            .ok_or(SynthesisError::PolynomialDegreeTooLarge)?;
+        let n = domain.size();
+        if n > self.tau_g2.len() {
+            return Err(PtauError::TooSmall {
+                power: self.power,
+                needed: n.ilog2(),
+            });
+        }
+
+        // `[L_j(τ)]` for the Lagrange polynomials `L_j` of the domain.
+        let lagrange_g1 = lagrange(&domain, &self.tau_g1[..n]);
+        let lagrange_g2 = lagrange(&domain, &self.tau_g2[..n]);
+        let alpha_lagrange = lagrange(&domain, &self.alpha_tau_g1[..n]);
+        let beta_lagrange = lagrange(&domain, &self.beta_tau_g1[..n]);
+
+        // `[u_k(τ)]₁`, `[v_k(τ)]₁`, `[v_k(τ)]₂` and `[β·u_k(τ) + α·v_k(τ) +
+        // w_k(τ)]₁` for every variable `k`.
+        let mut a = vec![G1Projective::zero(); num_variables];
+        let mut b_g1 = vec![G1Projective::zero(); num_variables];
+        let mut b_g2 = vec![G2Projective::zero(); num_variables];
+        let mut combined = vec![G1Projective::zero(); num_variables];
+        // The reduction appends a row `x_k · 0 = 0` per instance variable.
+        for k in 0..num_instance {
+            a[k] += lagrange_g1[num_constraints + k];
+            combined[k] += beta_lagrange[num_constraints + k];
+        }
+        for j in 0..num_constraints {
+            for (coeff, k) in &matrices.a[j] {
+                add_scaled(&mut a[*k], lagrange_g1[j], coeff);
+                add_scaled(&mut combined[*k], beta_lagrange[j], coeff);
+            }
+            for (coeff, k) in &matrices.b[j] {
+                add_scaled(&mut b_g1[*k], lagrange_g1[j], coeff);
+                add_scaled(&mut b_g2[*k], lagrange_g2[j], coeff);
+                add_scaled(&mut combined[*k], alpha_lagrange[j], coeff);
+            }
+            for (coeff, k) in &matrices.c[j] {
+                add_scaled(&mut combined[*k], lagrange_g1[j], coeff);
+            }
+        }
+
+        // `[τ^i·t(τ)]₁` for the vanishing polynomial `t(X) = X^n - 1`.
+        let h: Vec<_> = (0..n - 1)
+            .map(|i| self.tau_g1[i + n].into_group() - self.tau_g1[i])
+            .collect();
+
+        let combined = G1Projective::normalize_batch(&combined);
+        Ok(ProvingKey {
+            vk: VerifyingKey {
+                alpha_g1: self.alpha_tau_g1[0],
+                beta_g2: self.beta_g2,
+                gamma_g2: self.tau_g2[0],
+                delta_g2: self.tau_g2[0],
+                gamma_abc_g1: combined[..num_instance].to_vec(),
+            },
+            beta_g1: self.beta_tau_g1[0],
+            delta_g1: self.tau_g1[0],
+            a_query: G1Projective::normalize_batch(&a),
+            b_g1_query: G1Projective::normalize_batch(&b_g1),
+            b_g2_query: G2Projective::normalize_batch(&b_g2),
+            h_query: G1Projective::normalize_batch(&h),
+            l_query: combined[num_instance..].to_vec(),
+        })
+    }
+}
+
+/// Groth16 keys for `N`x`N` boards from `srs`, after one phase-2
+/// contribution drawn from `rng`.
+pub fn setup<const N: usize, R: RngCore + CryptoRng>(
+    srs: &PowersOfTau,
+    rng: &mut R,
+) -> Result<(ProvingKey<Bn254>, VerifyingKey<Bn254>), PtauError> {
+    let mut parameters = Parameters::from_powers_of_tau::<N>(srs)?;
+    parameters.contribute(rng);
+    Ok(parameters.into_keys())
+}
+
+/// The inverse FFT of `[τ^i]`, which is `[L_j(τ)]`.
+fn lagrange<G: CurveGroup<ScalarField = Fr>>(
+    domain: &GeneralEvaluationDomain<Fr>,
+    powers: &[G::Affine],
+) -> Vec<G> {
+    let mut points: Vec<G> = powers.iter().map(|p| p.into_group()).collect();
+    domain.ifft_in_place(&mut points);
+    points
+}
+
+fn add_scaled<G: AddAssign + Mul<Fr, Output = G> + Copy>(acc: &mut G, point: G, coeff: &Fr) {
+    *acc += point * *coeff;
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PtauError> {
+        if self.0.len() < len {
+            return Err(PtauError::Truncated);
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, PtauError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, PtauError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    /// Reads the `count` points section `id` consists of.
+    fn points<P>(
+        mut self,
+        id: u32,
+        count: usize,
+        read: fn(&mut Self) -> Result<P, PtauError>,
+    ) -> Result<Vec<P>, PtauError> {
+        let points = (0..count)
+            .map(|_| read(&mut self))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| match err {
+                PtauError::Truncated => PtauError::SectionLength(id),
+                err => err,
+            })?;
+        if !self.0.is_empty() {
+            return Err(PtauError::SectionLength(id));
+        }
+        Ok(points)
+    }
+
+    fn fq(&mut self) -> Result<Fq, PtauError> {
+        let repr = read_biguint(self.take(N8)?);
+        if repr >= Fq::MODULUS {
+            return Err(PtauError::InvalidPoint);
+        }
+        // The file is in Montgomery form already.
+        Ok(Fq::new_unchecked(repr))
+    }
+}
+
+fn read_biguint(bytes: &[u8]) -> BigInt<4> {
+    let mut limbs = [0; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    BigInt(limbs)
+}
+
+fn read_g1(reader: &mut Reader) -> Result<G1Affine, PtauError> {
+    let (x, y) = (reader.fq()?, reader.fq()?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G1Affine::zero());
+    }
+    let point = G1Affine::new_unchecked(x, y);
+    if !point.is_on_curve() {
+        return Err(PtauError::InvalidPoint);
+    }
+    Ok(point)
+}
+
+fn read_g2(reader: &mut Reader) -> Result<G2Affine, PtauError> {
+    let x = ark_bn254::Fq2::new(reader.fq()?, reader.fq()?);
+    let y = ark_bn254::Fq2::new(reader.fq()?, reader.fq()?);
+    if x.is_zero() && y.is_zero() {
+        return Ok(G2Affine::zero());
+    }
+    let point = G2Affine::new_unchecked(x, y);
+    if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+        return Err(PtauError::InvalidPoint);
+    }
+    Ok(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ec::scalar_mul::ScalarMul;
+    use ark_ff::Field;
+    use ark_groth16::Groth16;
+    use ark_snark::SNARK;
+    use rand::prelude::*;
+
+    /// A `.ptau` file with `2^power` powers of a known `τ`, laid out as
+    /// `snarkjs powersoftau` writes it, without the phase-2 sections.
+    fn ptau_file(power: u32, tau: Fr, alpha: Fr, beta: Fr) -> Vec<u8> {
+        let powers = 1 << power;
+        let tau_powers: Vec<Fr> = std::iter::successors(Some(Fr::from(1)), |x| Some(*x * tau))
+            .take(2 * powers - 1)
+            .collect();
+        let scaled = |by: Fr| -> Vec<Fr> { tau_powers[..powers].iter().map(|x| by * x).collect() };
+        let (g1, g2) = (
+            G1Affine::generator().into_group(),
+            G2Affine::generator().into_group(),
+        );
+
+        let fq = |out: &mut Vec<u8>, x: Fq| {
+            // Montgomery form, as `read_biguint` expects.
+            for limb in x.0 .0 {
+                out.extend(limb.to_le_bytes());
+            }
+        };
+        let g1_section = |points: Vec<G1Affine>| {
+            let mut out = Vec::new();
+            for point in points {
+                fq(&mut out, point.x);
+                fq(&mut out, point.y);
+            }
+            out
+        };
+        let g2_section = |points: Vec<G2Affine>| {
+            let mut out = Vec::new();
+            for point in points {
+                for x in [point.x.c0, point.x.c1, point.y.c0, point.y.c1] {
+                    fq(&mut out, x);
+                }
+            }
+            out
+        };
+
+        let mut header = (N8 as u32).to_le_bytes().to_vec();
+        for limb in Fq::MODULUS.0 {
+            header.extend(limb.to_le_bytes());
+        }
+        header.extend(power.to_le_bytes());
+        // The power of the ceremony the file was cut from.
+        header.extend(power.to_le_bytes());
+        let sections = [
+            (HEADER, header),
+            (TAU_G1, g1_section(g1.batch_mul(&tau_powers))),
+            (TAU_G2, g2_section(g2.batch_mul(&tau_powers[..powers]))),
+            (ALPHA_TAU_G1, g1_section(g1.batch_mul(&scaled(alpha)))),
+            (BETA_TAU_G1, g1_section(g1.batch_mul(&scaled(beta)))),
+            (BETA_G2, g2_section(vec![(g2 * beta).into_affine()])),
+        ];
+
+        let mut file = MAGIC.to_vec();
+        file.extend(1u32.to_le_bytes());
+        file.extend((sections.len() as u32).to_le_bytes());
+        for (id, contents) in sections {
+            file.extend(id.to_le_bytes());
+            file.extend((contents.len() as u64).to_le_bytes());
+            file.extend(contents);
+        }
+        file
+    }
+
+    /// The fewest powers a 4x4 board's keys fit in, `2^11`.
+    const POWER_4X4: u32 = 11;
+
+    fn random_ptau_file(power: u32) -> Vec<u8> {
+        let rng = &mut thread_rng();
+        ptau_file(power, Fr::rand(rng), Fr::rand(rng), Fr::rand(rng))
+    }
+
+    #[test]
+    fn setup_from_ptau_proves() {
+        let rng = &mut thread_rng();
+        let srs = PowersOfTau::read(&random_ptau_file(POWER_4X4)).unwrap();
+        assert_eq!(srs.power(), POWER_4X4);
+        let (pk, vk) = setup::<4, _>(&srs, rng).unwrap();
+        assert_eq!(vk.alpha_g1, srs.alpha_tau_g1[0]);
+        assert_ne!(vk.delta_g2, G2Affine::generator());
+
+        let puzzle = [[1, 0, 3, 4], [3, 4, 0, 2], [0, 1, 4, 3], [4, 3, 2, 0]];
+        let solution = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [4, 3, 2, 1]];
+        let circuit = SudokuCircuit {
+            puzzle: Some(puzzle),
+            solution: Some(solution),
+        };
+        let proof = Groth16::<Bn254>::prove(&pk, circuit, rng).unwrap();
+        let inputs = SudokuCircuit::public_inputs::<Fr>(&puzzle);
+        assert!(Groth16::<Bn254>::verify(&vk, &inputs, &proof).unwrap());
+        let inputs = SudokuCircuit::public_inputs::<Fr>(&solution);
+        assert!(!Groth16::<Bn254>::verify(&vk, &inputs, &proof).unwrap());
+    }
+
+    #[test]
+    fn bad_files_rejected() {
+        let ptau = random_ptau_file(3);
+
+        assert!(matches!(
+            PowersOfTau::read(b"zkey"),
+            Err(PtauError::BadMagic)
+        ));
+        assert!(matches!(
+            PowersOfTau::read(&ptau[..ptau.len() - 1]),
+            Err(PtauError::Truncated)
+        ));
+
+        let mut other_curve = ptau.clone();
+        other_curve[24] ^= 1;
+        assert!(matches!(
+            PowersOfTau::read(&other_curve),
+            Err(PtauError::UnsupportedCurve)
+        ));
+
+        let fresh = ptau_file(3, Fr::ONE, Fr::ONE, Fr::ONE);
+        assert!(matches!(
+            PowersOfTau::read(&fresh),
+            Err(PtauError::NoContributions)
+        ));
+        // The third power of tau in G1, swapped for the second.
+        let tau_g1 = 12 + 12 + (4 + 32 + 4 + 4) + 12;
+        let mut swapped = ptau.clone();
+        swapped.copy_within(tau_g1 + 64..tau_g1 + 128, tau_g1 + 128);
+        assert!(matches!(
+            PowersOfTau::read(&swapped),
+            Err(PtauError::InconsistentPowers)
+        ));
+
+        let mut off_curve = ptau.clone();
+        off_curve[tau_g1 + 64] ^= 1;
+        assert!(matches!(
+            PowersOfTau::read(&off_curve),
+            Err(PtauError::InvalidPoint)
+        ));
+
+        let srs = PowersOfTau::read(&ptau).unwrap();
+        assert!(matches!(
+            srs.proving_key::<4>(),
+            Err(PtauError::TooSmall {
+                power: 3,
+                needed: POWER_4X4
+            })
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+//! A circuit that verifies a Groth16 proof of
+//! [`SudokuCircuit`](crate::circuit::SudokuCircuit), so that a
+//! second proof can attest "I verified a Sudoku proof" without revealing
+//! the puzzle.
+//!
+//! The inner proof is over a pairing `E` and the outer circuit over `E`'s
+//! base field, where the inner curve arithmetic is native. Proving the outer
+//! circuit needs a pairing-friendly curve whose scalar field is that base
+//! field, such as BW6-761 for an inner BLS12-377 proof.
+
+use std::marker::PhantomData;
+
+use ark_crypto_primitives::snark::{BooleanInputVar, SNARKGadget};
+use ark_ec::pairing::Pairing;
+use ark_groth16::constraints::{Groth16VerifierGadget, ProofVar, VerifyingKeyVar};
+use ark_groth16::{Proof, VerifyingKey};
+use ark_r1cs_std::pairing::PairingVar;
+use ark_r1cs_std::prelude::{AllocVar, Boolean, EqGadget, ToBitsGadget};
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+
+use crate::circuit::{cells_per_input, Puzzle};
+
+type BasePrimeField<E> = <<E as Pairing>::BaseField as ark_ff::Field>::BasePrimeField;
+
+/// Proves knowledge of a puzzle and a Groth16 proof that it was solved,
+/// under the fixed verifying key `vk` of the Sudoku circuit.
+pub struct RecursiveCircuit<const N: usize, E: Pairing, P: PairingVar<E>> {
+    pub vk: VerifyingKey<E>,
+    pub puzzle: Option<[[u8; N]; N]>,
+    pub proof: Option<Proof<E>>,
+    _pairing: PhantomData<P>,
+}
+
+impl<const N: usize, E: Pairing, P: PairingVar<E>> RecursiveCircuit<N, E, P> {
+    pub fn new(vk: VerifyingKey<E>, puzzle: Option<[[u8; N]; N]>, proof: Option<Proof<E>>) -> Self {
+        RecursiveCircuit {
+            vk,
+            puzzle,
+            proof,
+            _pairing: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize, E: Pairing, P: PairingVar<E>> ConstraintSynthesizer<BasePrimeField<E>>
+    for RecursiveCircuit<N, E, P>
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<BasePrimeField<E>>,
+    ) -> ark_relations::r1cs::Result<()> {
+        let vk = VerifyingKeyVar::<E, P>::new_constant(ns!(cs, "vk"), &self.vk)?;
+        let proof = ProofVar::<E, P>::new_witness(ns!(cs, "proof"), || match self.proof {
+            Some(proof) => Ok(proof),
+            // The setup only needs the shape of a proof, not its points.
+            None if cs.is_in_setup_mode() => Ok(Proof::default()),
+            None => Err(SynthesisError::AssignmentMissing),
+        })?;
+        let puzzle = Puzzle::<N, _>::new_witness(ns!(cs, "puzzle"), || {
+            self.puzzle.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+
+        // The inner public inputs are the cells packed as little-endian bytes
+        // (see `SudokuCircuit::public_inputs`), so each scalar's bits are
+        // those of its cells.
+        let inputs = puzzle
+            .0
+            .as_flattened()
+            .chunks(cells_per_input::<E::ScalarField>())
+            .map(|chunk| chunk.to_bits_le())
+            .collect::<Result<Vec<_>, _>>()?;
+        let inputs = BooleanInputVar::<E::ScalarField, _>::new(inputs);
+
+        Groth16VerifierGadget::<E, P>::verify(&vk, &inputs, &proof)?.enforce_equal(&Boolean::TRUE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_377::{constraints::PairingVar, Bls12_377};
+    use ark_bw6_761::BW6_761;
+    use ark_groth16::Groth16;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_snark::SNARK;
+    use rand::prelude::*;
+
+    use crate::circuit::SudokuCircuit;
+    use crate::fixtures::SOLUTION;
+
+    type Recursive = RecursiveCircuit<4, Bls12_377, PairingVar>;
+
+    const PUZZLE: [[u8; 4]; 4] = [[1, 0, 0, 4], [0, 4, 0, 0], [0, 0, 4, 0], [4, 0, 0, 1]];
+
+    #[test]
+    fn verifies_inner_proof() {
+        let rng = &mut thread_rng();
+        let (pk, vk) =
+            Groth16::<Bls12_377>::circuit_specific_setup(SudokuCircuit::<4>::default(), rng)
+                .unwrap();
+        let circuit = SudokuCircuit {
+            puzzle: Some(PUZZLE),
+            solution: Some(SOLUTION),
+        };
+        let proof = Groth16::<Bls12_377>::prove(&pk, circuit, rng).unwrap();
+
+        let (outer_pk, outer_vk) =
+            Groth16::<BW6_761>::circuit_specific_setup(Recursive::new(vk.clone(), None, None), rng)
+                .unwrap();
+        let outer_proof = Groth16::<BW6_761>::prove(
+            &outer_pk,
+            Recursive::new(vk.clone(), Some(PUZZLE), Some(proof.clone())),
+            rng,
+        )
+        .unwrap();
+        // The outer proof has no public inputs: the puzzle stays hidden.
+        assert!(Groth16::<BW6_761>::verify(&outer_vk, &[], &outer_proof).unwrap());
+
+        // An inner proof of another puzzle leaves the outer circuit
+        // unsatisfied, so there is no outer proof to make.
+        let mut wrong_puzzle = PUZZLE;
+        wrong_puzzle[0][1] = 2;
+        let cs = ConstraintSystem::new_ref();
+        Recursive::new(vk, Some(wrong_puzzle), Some(proof))
+            .generate_constraints(cs.clone())
+            .unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,450 @@
+//! Incremental proofs of a solving session, one cell placement per step.
+//!
+//! The session state is `z_i = [H(board_i, r)]`, a Poseidon commitment to
+//! the board after `i` placements, starting from the puzzle. The blinding
+//! `r` is drawn by the solver once per session and never revealed, so the
+//! states can't be opened by guessing the few boards one placement away.
+//! [`StartCircuit`] proves that `z_0` commits to the public puzzle.
+//! [`PlacementStep`] is the step function `z_{i+1} = F(z_i)`: it takes the
+//! board, `r` and a [`Placement`] as witnesses, checks that the board
+//! matches `z_i` and that the cell is empty, and outputs the commitment to
+//! the new board once no digit repeats in a row, column or box of it.
+//! [`CompletionCircuit`] then proves that the final board is a solution.
+//!
+//! The steps are folded with [Nova](crate::nova) over BN254, so
+//! [`prove_session`] gives one [`IvcProof`] for the whole session, however
+//! many placements it took.
+
+use ark_bn254::Fr;
+use ark_crypto_primitives::sponge::poseidon::PoseidonConfig;
+use ark_crypto_primitives::sponge::Absorb;
+use ark_ff::PrimeField;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::{AllocVar, Boolean, EqGadget};
+use ark_r1cs_std::uint8::UInt8;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use rand::Rng;
+
+use crate::circuit::{unmarked, Puzzle, Solution, SudokuCircuit};
+use crate::cmp::CmpGadget;
+use crate::nova::{Ivc, IvcParams, IvcProof, StepCircuit};
+use crate::poseidon::{self, cells_var};
+
+/// Writing `digit` into the cell at `row`, `col`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    pub row: u8,
+    pub col: u8,
+    pub digit: u8,
+}
+
+impl Placement {
+    /// `board` with the placement made, if its cell is on the board.
+    pub fn apply<const N: usize>(&self, board: &[[u8; N]; N]) -> Option<[[u8; N]; N]> {
+        let mut board = *board;
+        *board
+            .get_mut(self.row as usize)?
+            .get_mut(self.col as usize)? = self.digit;
+        Some(board)
+    }
+}
+
+/// The session state for `board`, blinded by the session's `blinding`.
+pub fn session_state<const N: usize, F: PrimeField + Absorb>(
+    config: &PoseidonConfig<F>,
+    board: &[[u8; N]; N],
+    blinding: F,
+) -> F {
+    let mut inputs: Vec<F> = board.iter().flatten().map(|&cell| F::from(cell)).collect();
+    inputs.push(blinding);
+    poseidon::hash(config, &inputs)
+}
+
+fn session_state_var<const N: usize, F: PrimeField>(
+    cs: ConstraintSystemRef<F>,
+    config: &PoseidonConfig<F>,
+    board: &[[UInt8<F>; N]; N],
+    blinding: &FpVar<F>,
+) -> Result<FpVar<F>, SynthesisError> {
+    let mut inputs = cells_var(board)?;
+    inputs.push(blinding.clone());
+    poseidon::hash_var(cs, config, &inputs)
+}
+
+/// One placement on the board committed to by the incoming state.
+pub struct PlacementStep<const N: usize, F: PrimeField> {
+    pub config: PoseidonConfig<F>,
+    pub board: Option<[[u8; N]; N]>,
+    pub blinding: Option<F>,
+    pub placement: Option<Placement>,
+}
+
+impl<const N: usize, F: PrimeField> StepCircuit<F> for PlacementStep<N, F> {
+    const ARITY: usize = 1;
+
+    /// Maps `z = [H(board, r)]` to `[H(board', r)]`, where `board'` is
+    /// `board` with the placement applied.
+    fn generate_step_constraints(
+        &self,
+        cs: ConstraintSystemRef<F>,
+        z: &[FpVar<F>],
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let board = Puzzle::<N, F>::new_witness(ns!(cs, "board"), || {
+            self.board.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let blinding = FpVar::new_witness(ns!(cs, "blinding"), || {
+            self.blinding.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        session_state_var(cs.clone(), &self.config, &board.0, &blinding)?.enforce_equal(&z[0])?;
+
+        let placement = |field: fn(&Placement) -> u8| {
+            self.placement
+                .as_ref()
+                .map(field)
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+        let row = UInt8::new_witness(ns!(cs, "row"), || placement(|p| p.row))?;
+        let col = UInt8::new_witness(ns!(cs, "col"), || placement(|p| p.col))?;
+        let digit = UInt8::new_witness(ns!(cs, "digit"), || placement(|p| p.digit))?;
+        (digit.is_leq(&UInt8::constant(N as u8))? & (&digit.is_geq(&UInt8::constant(1))?))
+            .enforce_equal(&Boolean::TRUE)?;
+
+        let in_row = (0..N)
+            .map(|r| row.is_eq(&UInt8::constant(r as u8)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let in_col = (0..N)
+            .map(|c| col.is_eq(&UInt8::constant(c as u8)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let is_target: Vec<Vec<_>> = in_row
+            .iter()
+            .map(|r| in_col.iter().map(|c| r & c).collect())
+            .collect();
+        // The placement has to hit a cell of the board.
+        Boolean::kary_or(&is_target.concat())?.enforce_equal(&Boolean::TRUE)?;
+
+        let mut next = board.0.clone();
+        for (r, row) in board.0.iter().enumerate() {
+            for (c, cell) in row.iter().enumerate() {
+                cell.conditional_enforce_equal(&UInt8::constant(0), &is_target[r][c])?;
+                next[r][c] = is_target[r][c].select(&digit, cell)?;
+            }
+        }
+        // Checking the whole new board for duplicates covers the digit's
+        // row, column and box.
+        let next = Puzzle(next);
+        next.check_consistent(&mut unmarked)?;
+        Ok(vec![session_state_var(
+            cs,
+            &self.config,
+            &next.0,
+            &blinding,
+        )?])
+    }
+}
+
+/// The parameters of [`prove_session`] and [`verify_session`] for `N`x`N`
+/// boards, with `config` for the states and the folding.
+pub fn session_params<const N: usize>(
+    config: PoseidonConfig<Fr>,
+) -> Result<IvcParams, SynthesisError> {
+    let blank = PlacementStep::<N, Fr> {
+        config: config.clone(),
+        board: None,
+        blinding: None,
+        placement: None,
+    };
+    IvcParams::new(config, &blank)
+}
+
+/// Folds the `placements` on `puzzle` in the session blinded by `blinding`
+/// into one proof, or `None` if there are none. A placement that isn't
+/// allowed is `Unsatisfiable`.
+pub fn prove_session<const N: usize, R: Rng>(
+    params: &IvcParams,
+    puzzle: &[[u8; N]; N],
+    blinding: Fr,
+    placements: &[Placement],
+    rng: &mut R,
+) -> Result<Option<IvcProof>, SynthesisError> {
+    let config = params.config();
+    let mut ivc = Ivc::new(params, vec![session_state(config, puzzle, blinding)]);
+    let mut board = *puzzle;
+    for placement in placements {
+        let step = PlacementStep {
+            config: config.clone(),
+            board: Some(board),
+            blinding: Some(blinding),
+            placement: Some(*placement),
+        };
+        ivc.prove_step(&step, rng)?;
+        board = placement
+            .apply(&board)
+            .expect("the step only hits cells of the board");
+    }
+    Ok(ivc.proof(rng))
+}
+
+/// Whether `proof` folds a session from the state `start` to `end`.
+pub fn verify_session(params: &IvcParams, start: Fr, end: Fr, proof: &IvcProof) -> bool {
+    proof.z_0 == [start] && proof.z_i == [end] && proof.verify(params)
+}
+
+/// Proves that the public state `z_0` commits to the public puzzle.
+pub struct StartCircuit<const N: usize, F: PrimeField> {
+    pub config: PoseidonConfig<F>,
+    // The puzzle is public
+    pub puzzle: Option<[[u8; N]; N]>,
+    // The blinding is private
+    pub blinding: Option<F>,
+}
+
+impl<const N: usize, F: PrimeField + Absorb> StartCircuit<N, F> {
+    /// The public inputs for a session on `puzzle` starting from `state`.
+    pub fn public_inputs(puzzle: &[[u8; N]; N], state: F) -> Vec<F> {
+        let mut inputs = SudokuCircuit::public_inputs(puzzle);
+        inputs.push(state);
+        inputs
+    }
+}
+
+impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for StartCircuit<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> ark_relations::r1cs::Result<()> {
+        let puzzle = Puzzle::<N, F>::new_input(ns!(cs, "puzzle"), || {
+            self.puzzle.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        puzzle.check_range(&mut unmarked)?;
+        let state = FpVar::new_input(ns!(cs, "state"), || match (&self.puzzle, self.blinding) {
+            (Some(puzzle), Some(blinding)) => Ok(session_state(&self.config, puzzle, blinding)),
+            _ => Err(SynthesisError::AssignmentMissing),
+        })?;
+        let blinding = FpVar::new_witness(ns!(cs, "blinding"), || {
+            self.blinding.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        session_state_var(cs, &self.config, &puzzle.0, &blinding)?.enforce_equal(&state)
+    }
+}
+
+/// Proves that the board committed to by the public state is solved.
+pub struct CompletionCircuit<const N: usize, F: PrimeField> {
+    pub config: PoseidonConfig<F>,
+    pub board: Option<[[u8; N]; N]>,
+    pub blinding: Option<F>,
+}
+
+impl<const N: usize, F: PrimeField + Absorb> ConstraintSynthesizer<F> for CompletionCircuit<N, F> {
+    fn generate_constraints(self, cs: ConstraintSystemRef<F>) -> ark_relations::r1cs::Result<()> {
+        let state = FpVar::new_input(ns!(cs, "state"), || match (&self.board, self.blinding) {
+            (Some(board), Some(blinding)) => Ok(session_state(&self.config, board, blinding)),
+            _ => Err(SynthesisError::AssignmentMissing),
+        })?;
+        let board = Solution::<N, F>::new_witness(ns!(cs, "board"), || {
+            self.board.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        let blinding = FpVar::new_witness(ns!(cs, "blinding"), || {
+            self.blinding.ok_or(SynthesisError::AssignmentMissing)
+        })?;
+        session_state_var(cs, &self.config, &board.0, &blinding)?.enforce_equal(&state)?;
+        board.check_range(&mut unmarked)?;
+        board.check_rows(&mut unmarked)?;
+        board.check_cols(&mut unmarked)?;
+        board.check_subgrids(&mut unmarked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon::board_hash;
+    use ark_ff::UniformRand;
+    use ark_r1cs_std::R1CSVar;
+    use ark_relations::r1cs::ConstraintSystem;
+    use rand::prelude::*;
+
+    const PUZZLE: [[u8; 4]; 4] = [[1, 0, 3, 4], [3, 4, 0, 2], [0, 1, 4, 3], [4, 3, 2, 0]];
+    const SESSION: [Placement; 4] = [
+        Placement {
+            row: 0,
+            col: 1,
+            digit: 2,
+        },
+        Placement {
+            row: 1,
+            col: 2,
+            digit: 1,
+        },
+        Placement {
+            row: 2,
+            col: 0,
+            digit: 2,
+        },
+        Placement {
+            row: 3,
+            col: 3,
+            digit: 1,
+        },
+    ];
+
+    /// Whether the step maps the state of `board` to that of the board with
+    /// `placement` made.
+    fn step_satisfied(board: [[u8; 4]; 4], placement: Placement) -> bool {
+        let config = poseidon::config::<Fr>();
+        let blinding = Fr::from(7);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        let state =
+            FpVar::new_input(cs.clone(), || Ok(session_state(&config, &board, blinding))).unwrap();
+        let next = PlacementStep {
+            config: config.clone(),
+            board: Some(board),
+            blinding: Some(blinding),
+            placement: Some(placement),
+        }
+        .generate_step_constraints(cs.clone(), &[state])
+        .unwrap();
+        let expected = placement
+            .apply(&board)
+            .map(|board| session_state(&config, &board, blinding));
+        cs.is_satisfied().unwrap() && next[0].value().ok() == expected
+    }
+
+    fn start_satisfied(puzzle: [[u8; 4]; 4], state: Fr, blinding: Fr) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        StartCircuit {
+            config: poseidon::config(),
+            puzzle: Some(puzzle),
+            blinding: Some(blinding),
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        // The state is an input, so a forged one replaces the honest value.
+        let inputs = StartCircuit::<4, Fr>::public_inputs(&puzzle, state);
+        cs.borrow_mut().unwrap().instance_assignment[1..].copy_from_slice(&inputs);
+        cs.is_satisfied().unwrap()
+    }
+
+    fn completion_satisfied(board: [[u8; 4]; 4]) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        CompletionCircuit {
+            config: poseidon::config(),
+            board: Some(board),
+            blinding: Some(Fr::from(7)),
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn session_folds_into_one_proof() {
+        let rng = &mut thread_rng();
+        let config = poseidon::config::<Fr>();
+        let params = session_params::<4>(config.clone()).unwrap();
+        let blinding = Fr::rand(rng);
+        let start = session_state(&config, &PUZZLE, blinding);
+        assert!(start_satisfied(PUZZLE, start, blinding));
+
+        let proof = prove_session(&params, &PUZZLE, blinding, &SESSION, rng)
+            .unwrap()
+            .unwrap();
+        let board = SESSION
+            .iter()
+            .try_fold(PUZZLE, |board, placement| placement.apply(&board))
+            .unwrap();
+        let end = session_state(&config, &board, blinding);
+        assert_eq!(proof.steps, 4);
+        assert!(verify_session(&params, start, end, &proof));
+        assert!(completion_satisfied(board));
+
+        // The proof is for this session's start and end only.
+        let halfway = SESSION[..2]
+            .iter()
+            .try_fold(PUZZLE, |board, placement| placement.apply(&board))
+            .unwrap();
+        assert!(!verify_session(
+            &params,
+            start,
+            session_state(&config, &halfway, blinding),
+            &proof
+        ));
+        assert!(!verify_session(&params, end, end, &proof));
+
+        // Placements that aren't allowed have no proof.
+        assert_eq!(
+            prove_session(&params, &PUZZLE, blinding, &[], rng).unwrap(),
+            None
+        );
+        for placement in [
+            SESSION[0],
+            Placement {
+                row: 4,
+                col: 1,
+                digit: 2,
+            },
+        ] {
+            assert!(matches!(
+                prove_session(&params, &PUZZLE, blinding, &[SESSION[0], placement], rng),
+                Err(SynthesisError::Unsatisfiable)
+            ));
+        }
+    }
+
+    #[test]
+    fn session_steps_chain() {
+        let mut board = PUZZLE;
+        for placement in SESSION {
+            assert!(step_satisfied(board, placement));
+            board = placement.apply(&board).unwrap();
+        }
+        assert!(completion_satisfied(board));
+        assert!(!completion_satisfied(SESSION[3].apply(&PUZZLE).unwrap()));
+    }
+
+    #[test]
+    fn states_are_blinded() {
+        let config = poseidon::config::<Fr>();
+        let blinding = Fr::rand(&mut thread_rng());
+        let state = session_state(&config, &PUZZLE, blinding);
+        // Without the blinding, the next state can't be matched against the
+        // boards one placement away.
+        let next = session_state(&config, &SESSION[0].apply(&PUZZLE).unwrap(), blinding);
+        assert_ne!(
+            next,
+            board_hash(&config, &SESSION[0].apply(&PUZZLE).unwrap())
+        );
+        assert_ne!(
+            state,
+            session_state(&config, &PUZZLE, blinding + Fr::from(1))
+        );
+
+        // The start proof ties the state to the puzzle, not another board.
+        assert!(start_satisfied(PUZZLE, state, blinding));
+        assert!(!start_satisfied(PUZZLE, next, blinding));
+    }
+
+    #[test]
+    fn start_puzzle_range_checked() {
+        let blinding = Fr::from(7);
+        let mut puzzle = PUZZLE;
+        puzzle[0][1] = 5;
+        let state = session_state(&poseidon::config(), &puzzle, blinding);
+        assert!(!start_satisfied(puzzle, state, blinding));
+    }
+
+    #[test]
+    fn conflicting_placements_rejected() {
+        // The digit is already in the row, the column and the box respectively.
+        for (row, col, digit) in [(0, 1, 3), (1, 2, 4), (2, 0, 1)] {
+            assert!(!step_satisfied(PUZZLE, Placement { row, col, digit }));
+        }
+        // Given cells and digits out of range can't be placed.
+        for (row, col, digit) in [(0, 0, 2), (0, 1, 0), (0, 1, 5)] {
+            assert!(!step_satisfied(PUZZLE, Placement { row, col, digit }));
+        }
+        // Neither can cells off the board.
+        for (row, col) in [(4, 1), (1, 4), (255, 255)] {
+            let placement = Placement { row, col, digit: 2 };
+            assert_eq!(placement.apply(&PUZZLE), None);
+            assert!(!step_satisfied(PUZZLE, placement));
+        }
+    }
+}
@@ -0,0 +1,1264 @@
+//! Setup, proving and verification of [`SudokuCircuit`] behind a common
+//! interface over [`SNARK`]s.
+//!
+//! Each [`ProvingBackend`] fixes a proof system and a curve; the circuit is
+//! the same for all of them. [`Backend`] names them at runtime, and keys and
+//! proofs are serialized with a leading [`Backend`] tag so they can be loaded
+//! without knowing the backend up front.
+//!
+//! Groth16 and GM17 ([`Gm17Bls12_381`], from the `ark-gm17` port in this
+//! workspace) need a separate trusted setup for every board size. Marlin
+//! ([`MarlinBls12_381`], from the `ark-marlin` port in this workspace) has a
+//! universal SRS instead: one [`MarlinBls12_381::universal_setup`] serves every
+//! circuit up to its bound, and the keys for each `N` are derived from it
+//! without further trust by [`MarlinBls12_381::index`].
+//!
+//! A setup in [`SetupMode::Test`] derives its randomness from a seed with
+//! ChaCha20, so the same seed gives the same keys on every machine. That is
+//! what CI and golden keys need, but whoever knows the seed knows the
+//! trapdoor and can forge proofs, so [`SetupMode::Production`] refuses seeds.
+
+use ark_bls12_381::Bls12_381;
+use ark_bn254::Bn254;
+use ark_ff::PrimeField;
+use ark_gm17::GM17;
+use ark_groth16::Groth16;
+use ark_marlin::{AHPForR1CS, ComputationBound, Marlin, UniversalSRS};
+use ark_poly::univariate::DensePolynomial;
+use ark_poly_commit::marlin_pc::MarlinKZG10;
+use ark_relations::r1cs::{ConstraintSynthesizer, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_snark::SNARK;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use sha3::Sha3_256;
+
+use crate::circuit::SudokuCircuit;
+use crate::context::{Context, ContextCircuit};
+use crate::ptau::{self, PowersOfTau, PtauError};
+use crate::validate::{validate, validate_puzzle, ValidationError};
+
+pub type ProvingKeyOf<B> =
+    <<B as ProvingBackend>::Snark as SNARK<<B as ProvingBackend>::Field>>::ProvingKey;
+pub type VerifyingKeyOf<B> =
+    <<B as ProvingBackend>::Snark as SNARK<<B as ProvingBackend>::Field>>::VerifyingKey;
+pub type PreparedVerifyingKeyOf<B> =
+    <<B as ProvingBackend>::Snark as SNARK<<B as ProvingBackend>::Field>>::ProcessedVerifyingKey;
+pub type ProofOf<B> = <<B as ProvingBackend>::Snark as SNARK<<B as ProvingBackend>::Field>>::Proof;
+
+/// A proof system and curve to prove [`SudokuCircuit`] with.
+pub trait ProvingBackend {
+    const BACKEND: Backend;
+    type Field: PrimeField;
+    type Snark: SNARK<Self::Field, Error: Into<SnarkError>>;
+
+    /// Generates the proving and verifying keys for `N`x`N` boards.
+    #[allow(clippy::type_complexity)]
+    fn setup<const N: usize, R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> Result<(ProvingKeyOf<Self>, VerifyingKeyOf<Self>), SnarkError>;
+
+    /// Proves that `solution` solves `puzzle`.
+    fn prove<const N: usize, R: RngCore + CryptoRng>(
+        pk: &ProvingKeyOf<Self>,
+        puzzle: [[u8; N]; N],
+        solution: [[u8; N]; N],
+        rng: &mut R,
+    ) -> Result<ProofOf<Self>, SnarkError> {
+        let circuit = SudokuCircuit {
+            puzzle: Some(puzzle),
+            solution: Some(solution),
+        };
+        Self::Snark::prove(pk, circuit, rng).map_err(Into::into)
+    }
+
+    /// Checks `proof` against the public `puzzle`.
+    fn verify<const N: usize>(
+        vk: &VerifyingKeyOf<Self>,
+        puzzle: &[[u8; N]; N],
+        proof: &ProofOf<Self>,
+    ) -> Result<bool, SnarkError> {
+        Self::Snark::verify(
+            vk,
+            &SudokuCircuit::public_inputs::<Self::Field>(puzzle),
+            proof,
+        )
+        .map_err(Into::into)
+    }
+
+    /// Generates keys for `N`x`N` boards whose proofs are bound to a
+    /// [`Context`].
+    #[allow(clippy::type_complexity)]
+    fn setup_with_context<const N: usize, R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> Result<(ProvingKeyOf<Self>, VerifyingKeyOf<Self>), SnarkError> {
+        Self::Snark::circuit_specific_setup(ContextCircuit::<N>::default(), rng).map_err(Into::into)
+    }
+
+    /// Proves that `solution` solves `puzzle`, bound to `context`.
+    fn prove_with_context<const N: usize, R: RngCore + CryptoRng>(
+        pk: &ProvingKeyOf<Self>,
+        puzzle: [[u8; N]; N],
+        solution: [[u8; N]; N],
+        context: &Context,
+        rng: &mut R,
+    ) -> Result<ProofOf<Self>, SnarkError> {
+        let circuit = ContextCircuit {
+            puzzle: Some(puzzle),
+            solution: Some(solution),
+            context: Some(*context),
+        };
+        Self::Snark::prove(pk, circuit, rng).map_err(Into::into)
+    }
+
+    /// Checks `proof` against the public `puzzle` and `context`.
+    fn verify_with_context<const N: usize>(
+        vk: &VerifyingKeyOf<Self>,
+        puzzle: &[[u8; N]; N],
+        context: &Context,
+        proof: &ProofOf<Self>,
+    ) -> Result<bool, SnarkError> {
+        Self::Snark::verify(
+            vk,
+            &ContextCircuit::public_inputs::<Self::Field>(puzzle, context),
+            proof,
+        )
+        .map_err(Into::into)
+    }
+
+    /// A fresh proof of the same statement as `proof`, which nobody can link
+    /// to it without the witness, if the proof system has one.
+    fn rerandomize<R: RngCore + CryptoRng>(
+        vk: &VerifyingKeyOf<Self>,
+        proof: &ProofOf<Self>,
+        rng: &mut R,
+    ) -> Result<ProofOf<Self>, SnarkError>;
+
+    /// Does the work [`ProvingBackend::verify`] repeats on every call up
+    /// front, such as the pairing of `α` and `β`.
+    fn prepare(vk: &VerifyingKeyOf<Self>) -> Result<PreparedVerifyingKeyOf<Self>, SnarkError> {
+        Self::Snark::process_vk(vk).map_err(Into::into)
+    }
+
+    /// Checks `proof` against the public `puzzle` with a prepared key.
+    fn verify_prepared<const N: usize>(
+        pvk: &PreparedVerifyingKeyOf<Self>,
+        puzzle: &[[u8; N]; N],
+        proof: &ProofOf<Self>,
+    ) -> Result<bool, SnarkError> {
+        Self::Snark::verify_with_processed_vk(
+            pvk,
+            &SudokuCircuit::public_inputs::<Self::Field>(puzzle),
+            proof,
+        )
+        .map_err(Into::into)
+    }
+}
+
+/// Groth16 over BLS12-381.
+pub struct Groth16Bls12_381;
+
+impl ProvingBackend for Groth16Bls12_381 {
+    const BACKEND: Backend = Backend::Groth16Bls12_381;
+    type Field = ark_bls12_381::Fr;
+    type Snark = Groth16<Bls12_381>;
+
+    fn setup<const N: usize, R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> Result<(ProvingKeyOf<Self>, VerifyingKeyOf<Self>), SnarkError> {
+        Ok(Self::Snark::circuit_specific_setup(
+            SudokuCircuit::<N>::default(),
+            rng,
+        )?)
+    }
+
+    fn rerandomize<R: RngCore + CryptoRng>(
+        vk: &VerifyingKeyOf<Self>,
+        proof: &ProofOf<Self>,
+        rng: &mut R,
+    ) -> Result<ProofOf<Self>, SnarkError> {
+        Ok(Self::Snark::rerandomize_proof(vk, proof, rng))
+    }
+}
+
+/// Groth16 over BN254, which the EVM has precompiles for (see [`crate::evm`]).
+pub struct Groth16Bn254;
+
+impl ProvingBackend for Groth16Bn254 {
+    const BACKEND: Backend = Backend::Groth16Bn254;
+    type Field = ark_bn254::Fr;
+    type Snark = Groth16<Bn254>;
+
+    fn setup<const N: usize, R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> Result<(ProvingKeyOf<Self>, VerifyingKeyOf<Self>), SnarkError> {
+        Ok(Self::Snark::circuit_specific_setup(
+            SudokuCircuit::<N>::default(),
+            rng,
+        )?)
+    }
+
+    fn rerandomize<R: RngCore + CryptoRng>(
+        vk: &VerifyingKeyOf<Self>,
+        proof: &ProofOf<Self>,
+        rng: &mut R,
+    ) -> Result<ProofOf<Self>, SnarkError> {
+        Ok(Self::Snark::rerandomize_proof(vk, proof, rng))
+    }
+}
+
+/// GM17 over BLS12-381. Its proofs are simulation-extractable, so unlike
+/// Groth16 they cannot be rerandomized.
+pub struct Gm17Bls12_381;
+
+impl ProvingBackend for Gm17Bls12_381 {
+    const BACKEND: Backend = Backend::Gm17Bls12_381;
+    type Field = ark_bls12_381::Fr;
+    type Snark = GM17<Bls12_381>;
+
+    fn setup<const N: usize, R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> Result<(ProvingKeyOf<Self>, VerifyingKeyOf<Self>), SnarkError> {
+        Ok(Self::Snark::circuit_specific_setup(
+            SudokuCircuit::<N>::default(),
+            rng,
+        )?)
+    }
+
+    fn rerandomize<R: RngCore + CryptoRng>(
+        _: &VerifyingKeyOf<Self>,
+        _: &ProofOf<Self>,
+        _: &mut R,
+    ) -> Result<ProofOf<Self>, SnarkError> {
+        Err(SnarkError::NotRerandomizable(Self::BACKEND))
+    }
+}
+
+/// The polynomial commitment [`MarlinBls12_381`] compiles Marlin with.
+type MarlinPc = MarlinKZG10<Bls12_381, DensePolynomial<ark_bls12_381::Fr>>;
+type MarlinSnark = Marlin<ark_bls12_381::Fr, MarlinPc, Sha3_256>;
+
+/// Marlin over BLS12-381, with KZG commitments from a universal SRS.
+pub struct MarlinBls12_381;
+
+impl MarlinBls12_381 {
+    /// The bound a universal SRS needs to index `circuit`.
+    pub fn bound<C: ConstraintSynthesizer<ark_bls12_381::Fr>>(
+        circuit: C,
+    ) -> Result<ComputationBound, SnarkError> {
+        let info = AHPForR1CS::index(circuit)
+            .map_err(|err| SnarkError::Marlin(err.into()))?
+            .index_info;
+        Ok(ComputationBound {
+            num_constraints: info.num_constraints,
+            num_variables: info.num_variables,
+            num_non_zero: info.num_non_zero,
+        })
+    }
+
+    /// A universal SRS for every circuit within `bound`, such as the
+    /// [`MarlinBls12_381::bound`] of the largest board it is to serve.
+    pub fn universal_setup<R: RngCore + CryptoRng>(
+        bound: &ComputationBound,
+        rng: &mut R,
+    ) -> Result<UniversalSRS<ark_bls12_381::Fr, MarlinPc>, SnarkError> {
+        Ok(MarlinSnark::universal_setup(
+            bound.num_constraints,
+            bound.num_variables,
+            bound.num_non_zero,
+            rng,
+        )?)
+    }
+
+    /// The keys for `N`x`N` boards, derived from `srs` without randomness.
+    #[allow(clippy::type_complexity)]
+    pub fn index<const N: usize>(
+        srs: &UniversalSRS<ark_bls12_381::Fr, MarlinPc>,
+    ) -> Result<(ProvingKeyOf<Self>, VerifyingKeyOf<Self>), SnarkError> {
+        Ok(MarlinSnark::index(srs, SudokuCircuit::<N>::default())?)
+    }
+
+    /// The keys for `N`x`N` boards bound to a [`Context`], derived from `srs`.
+    #[allow(clippy::type_complexity)]
+    pub fn index_with_context<const N: usize>(
+        srs: &UniversalSRS<ark_bls12_381::Fr, MarlinPc>,
+    ) -> Result<(ProvingKeyOf<Self>, VerifyingKeyOf<Self>), SnarkError> {
+        Ok(MarlinSnark::index(srs, ContextCircuit::<N>::default())?)
+    }
+}
+
+impl ProvingBackend for MarlinBls12_381 {
+    const BACKEND: Backend = Backend::MarlinBls12_381;
+    type Field = ark_bls12_381::Fr;
+    type Snark = MarlinSnark;
+
+    /// Runs a universal setup just large enough for `N`x`N` boards and
+    /// indexes them with it. Use [`MarlinBls12_381::index`] to share an SRS.
+    fn setup<const N: usize, R: RngCore + CryptoRng>(
+        rng: &mut R,
+    ) -> Result<(ProvingKeyOf<Self>, VerifyingKeyOf<Self>), SnarkError> {
+        Ok(Self::Snark::circuit_specific_setup(
+            SudokuCircuit::<N>::default(),
+            rng,
+        )?)
+    }
+
+    fn rerandomize<R: RngCore + CryptoRng>(
+        _: &VerifyingKeyOf<Self>,
+        _: &ProofOf<Self>,
+        _: &mut R,
+    ) -> Result<ProofOf<Self>, SnarkError> {
+        Err(SnarkError::NotRerandomizable(Self::BACKEND))
+    }
+}
+
+#[derive(Debug)]
+pub enum SnarkError {
+    Synthesis(SynthesisError),
+    Marlin(ark_marlin::Error<ark_poly_commit::Error>),
+    Serialization(SerializationError),
+    PowersOfTau(PtauError),
+    InvalidSolution(ValidationError),
+    InvalidPuzzle(ValidationError),
+    UnknownBackend(u8),
+    BackendMismatch {
+        expected: Backend,
+        found: Backend,
+    },
+    UnknownKind(u8),
+    KindMismatch {
+        expected: Kind,
+        found: Kind,
+    },
+    TrailingBytes(usize),
+    /// The verifying key is for a circuit with `found` public inputs.
+    InputCountMismatch {
+        expected: usize,
+        found: usize,
+    },
+    NotRerandomizable(Backend),
+    SeededProductionSetup,
+    UnseededTestSetup,
+}
+
+impl std::fmt::Display for SnarkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnarkError::Synthesis(err) => write!(f, "Synthesis error: {err}"),
+            SnarkError::Marlin(err) => write!(f, "Marlin error: {err}"),
+            SnarkError::Serialization(err) => write!(f, "Serialization error: {err}"),
+            SnarkError::PowersOfTau(err) => write!(f, "Powers of tau error: {err}"),
+            SnarkError::InvalidSolution(err) => write!(f, "Invalid solution: {err}"),
+            SnarkError::InvalidPuzzle(err) => write!(f, "Invalid puzzle: {err}"),
+            SnarkError::UnknownBackend(tag) => write!(f, "Unknown backend tag {tag}"),
+            SnarkError::BackendMismatch { expected, found } => {
+                write!(f, "Expected data for {expected}, found {found}")
+            }
+            SnarkError::UnknownKind(tag) => write!(f, "Unknown kind tag {tag}"),
+            SnarkError::KindMismatch { expected, found } => {
+                write!(f, "Expected a {expected}, found a {found}")
+            }
+            SnarkError::TrailingBytes(len) => write!(f, "{len} trailing bytes"),
+            SnarkError::InputCountMismatch { expected, found } => write!(
+                f,
+                "The verifying key takes {found} public inputs, expected {expected}"
+            ),
+            SnarkError::NotRerandomizable(backend) => {
+                write!(f, "{backend} proofs cannot be rerandomized")
+            }
+            SnarkError::SeededProductionSetup => {
+                write!(f, "Production setups must not be seeded")
+            }
+            SnarkError::UnseededTestSetup => write!(f, "Test setups need a seed"),
+        }
+    }
+}
+
+impl std::error::Error for SnarkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnarkError::Synthesis(err) => Some(err),
+            SnarkError::Marlin(err) => Some(err),
+            SnarkError::Serialization(err) => Some(err),
+            SnarkError::PowersOfTau(err) => Some(err),
+            SnarkError::InvalidSolution(err) | SnarkError::InvalidPuzzle(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<SynthesisError> for SnarkError {
+    fn from(err: SynthesisError) -> Self {
+        SnarkError::Synthesis(err)
+    }
+}
+
+impl From<ark_marlin::Error<ark_poly_commit::Error>> for SnarkError {
+    fn from(err: ark_marlin::Error<ark_poly_commit::Error>) -> Self {
+        SnarkError::Marlin(err)
+    }
+}
+
+impl From<SerializationError> for SnarkError {
+    fn from(err: SerializationError) -> Self {
+        SnarkError::Serialization(err)
+    }
+}
+
+impl From<PtauError> for SnarkError {
+    fn from(err: PtauError) -> Self {
+        SnarkError::PowersOfTau(err)
+    }
+}
+
+impl From<ValidationError> for SnarkError {
+    fn from(err: ValidationError) -> Self {
+        SnarkError::InvalidSolution(err)
+    }
+}
+
+/// Where the randomness of a setup comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum SetupMode {
+    /// Randomness from the caller's RNG, never to be reproduced.
+    Production,
+    /// Randomness from [`seeded_rng`], for reproducible keys in tests.
+    Test,
+}
+
+impl SetupMode {
+    /// The RNG a setup in this mode draws from: `rng` in production, which
+    /// refuses a `seed`, or [`seeded_rng`] in test mode, which needs one.
+    pub fn rng<R: RngCore + CryptoRng>(
+        self,
+        seed: Option<u64>,
+        rng: R,
+    ) -> Result<SetupRng<R>, SnarkError> {
+        match (self, seed) {
+            (SetupMode::Production, None) => Ok(SetupRng::Production(rng)),
+            (SetupMode::Production, Some(_)) => Err(SnarkError::SeededProductionSetup),
+            (SetupMode::Test, Some(seed)) => Ok(SetupRng::Test(Box::new(seeded_rng(seed)))),
+            (SetupMode::Test, None) => Err(SnarkError::UnseededTestSetup),
+        }
+    }
+}
+
+/// The RNG a setup draws from, see [`SetupMode::rng`].
+pub enum SetupRng<R> {
+    Production(R),
+    Test(Box<ChaCha20Rng>),
+}
+
+impl<R: RngCore> RngCore for SetupRng<R> {
+    fn next_u32(&mut self) -> u32 {
+        match self {
+            SetupRng::Production(rng) => rng.next_u32(),
+            SetupRng::Test(rng) => rng.next_u32(),
+        }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        match self {
+            SetupRng::Production(rng) => rng.next_u64(),
+            SetupRng::Test(rng) => rng.next_u64(),
+        }
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            SetupRng::Production(rng) => rng.fill_bytes(dest),
+            SetupRng::Test(rng) => rng.fill_bytes(dest),
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        match self {
+            SetupRng::Production(rng) => rng.try_fill_bytes(dest),
+            SetupRng::Test(rng) => rng.try_fill_bytes(dest),
+        }
+    }
+}
+
+impl<R: CryptoRng> CryptoRng for SetupRng<R> {}
+
+/// The RNG a [`SetupMode::Test`] setup draws from.
+pub fn seeded_rng(seed: u64) -> ChaCha20Rng {
+    ChaCha20Rng::seed_from_u64(seed)
+}
+
+/// The [`ProvingBackend`]s, for choosing one at runtime.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum Backend {
+    #[cfg_attr(feature = "cli", value(name = "groth16-bls12-381"))]
+    Groth16Bls12_381,
+    #[cfg_attr(feature = "cli", value(name = "groth16-bn254"))]
+    Groth16Bn254,
+    #[cfg_attr(feature = "cli", value(name = "marlin-bls12-381"))]
+    MarlinBls12_381,
+    #[cfg_attr(feature = "cli", value(name = "gm17-bls12-381"))]
+    Gm17Bls12_381,
+}
+
+impl std::fmt::Display for Backend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Backend::Groth16Bls12_381 => write!(f, "Groth16 over BLS12-381"),
+            Backend::Groth16Bn254 => write!(f, "Groth16 over BN254"),
+            Backend::MarlinBls12_381 => write!(f, "Marlin over BLS12-381"),
+            Backend::Gm17Bls12_381 => write!(f, "GM17 over BLS12-381"),
+        }
+    }
+}
+
+impl Backend {
+    pub const ALL: [Backend; 4] = [
+        Backend::Groth16Bls12_381,
+        Backend::Groth16Bn254,
+        Backend::MarlinBls12_381,
+        Backend::Gm17Bls12_381,
+    ];
+
+    /// The byte in front of everything serialized for this backend.
+    pub fn tag(self) -> u8 {
+        match self {
+            Backend::Groth16Bls12_381 => 0,
+            Backend::Groth16Bn254 => 1,
+            Backend::MarlinBls12_381 => 2,
+            Backend::Gm17Bls12_381 => 3,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Result<Self, SnarkError> {
+        Self::ALL
+            .into_iter()
+            .find(|backend| backend.tag() == tag)
+            .ok_or(SnarkError::UnknownBackend(tag))
+    }
+
+    /// The backend `serialized` was written for.
+    pub fn of(serialized: &[u8]) -> Result<Self, SnarkError> {
+        let tag = serialized
+            .first()
+            .ok_or(SerializationError::NotEnoughSpace)?;
+        Self::from_tag(*tag)
+    }
+
+    /// Runs [`ProvingBackend::setup`], returning tagged keys.
+    pub fn setup<const N: usize, R: RngCore + CryptoRng>(
+        self,
+        rng: &mut R,
+    ) -> Result<(Vec<u8>, Vec<u8>), SnarkError> {
+        fn setup<B: ProvingBackend, const N: usize, R: RngCore + CryptoRng>(
+            rng: &mut R,
+        ) -> Result<(Vec<u8>, Vec<u8>), SnarkError> {
+            let (pk, vk) = B::setup::<N, R>(rng)?;
+            Ok((
+                serialize_tagged::<B, _>(Kind::ProvingKey, &pk)?,
+                serialize_tagged::<B, _>(Kind::VerifyingKey, &vk)?,
+            ))
+        }
+        match self {
+            Backend::Groth16Bls12_381 => setup::<Groth16Bls12_381, N, R>(rng),
+            Backend::Groth16Bn254 => setup::<Groth16Bn254, N, R>(rng),
+            Backend::MarlinBls12_381 => setup::<MarlinBls12_381, N, R>(rng),
+            Backend::Gm17Bls12_381 => setup::<Gm17Bls12_381, N, R>(rng),
+        }
+    }
+
+    /// Runs [`ProvingBackend::setup_with_context`], returning tagged keys.
+    pub fn setup_with_context<const N: usize, R: RngCore + CryptoRng>(
+        self,
+        rng: &mut R,
+    ) -> Result<(Vec<u8>, Vec<u8>), SnarkError> {
+        fn setup<B: ProvingBackend, const N: usize, R: RngCore + CryptoRng>(
+            rng: &mut R,
+        ) -> Result<(Vec<u8>, Vec<u8>), SnarkError> {
+            let (pk, vk) = B::setup_with_context::<N, R>(rng)?;
+            Ok((
+                serialize_tagged::<B, _>(Kind::ContextProvingKey, &pk)?,
+                serialize_tagged::<B, _>(Kind::ContextVerifyingKey, &vk)?,
+            ))
+        }
+        match self {
+            Backend::Groth16Bls12_381 => setup::<Groth16Bls12_381, N, R>(rng),
+            Backend::Groth16Bn254 => setup::<Groth16Bn254, N, R>(rng),
+            Backend::MarlinBls12_381 => setup::<MarlinBls12_381, N, R>(rng),
+            Backend::Gm17Bls12_381 => setup::<Gm17Bls12_381, N, R>(rng),
+        }
+    }
+
+    /// Runs [`Backend::setup`] in `mode`: from `rng` in production, which
+    /// refuses a `seed`, or from [`seeded_rng`] in test mode, which needs one.
+    pub fn setup_in_mode<const N: usize, R: RngCore + CryptoRng>(
+        self,
+        mode: SetupMode,
+        seed: Option<u64>,
+        rng: &mut R,
+    ) -> Result<(Vec<u8>, Vec<u8>), SnarkError> {
+        self.setup::<N, _>(&mut mode.rng(seed, rng)?)
+    }
+
+    /// Runs a setup from the phase-1 SRS `srs` with one phase-2 contribution
+    /// from `rng` (see [`ptau::setup`]), returning tagged keys. Only BN254
+    /// has `.ptau` files.
+    pub fn setup_from_powers_of_tau<const N: usize, R: RngCore + CryptoRng>(
+        self,
+        srs: &PowersOfTau,
+        rng: &mut R,
+    ) -> Result<(Vec<u8>, Vec<u8>), SnarkError> {
+        if self != Backend::Groth16Bn254 {
+            return Err(SnarkError::BackendMismatch {
+                expected: Backend::Groth16Bn254,
+                found: self,
+            });
+        }
+        let (pk, vk) = ptau::setup::<N, R>(srs, rng)?;
+        Ok((
+            serialize_tagged::<Groth16Bn254, _>(Kind::ProvingKey, &pk)?,
+            serialize_tagged::<Groth16Bn254, _>(Kind::VerifyingKey, &vk)?,
+        ))
+    }
+}
+
+/// What a tagged value is, so that one can't be loaded as another of the
+/// same backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    ProvingKey,
+    VerifyingKey,
+    PreparedVerifyingKey,
+    ContextProvingKey,
+    ContextVerifyingKey,
+    Proof,
+    ContextProof,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::ProvingKey => write!(f, "proving key"),
+            Kind::VerifyingKey => write!(f, "verifying key"),
+            Kind::PreparedVerifyingKey => write!(f, "prepared verifying key"),
+            Kind::ContextProvingKey => write!(f, "context proving key"),
+            Kind::ContextVerifyingKey => write!(f, "context verifying key"),
+            Kind::Proof => write!(f, "proof"),
+            Kind::ContextProof => write!(f, "context proof"),
+        }
+    }
+}
+
+impl Kind {
+    pub const ALL: [Kind; 7] = [
+        Kind::ProvingKey,
+        Kind::VerifyingKey,
+        Kind::PreparedVerifyingKey,
+        Kind::ContextProvingKey,
+        Kind::ContextVerifyingKey,
+        Kind::Proof,
+        Kind::ContextProof,
+    ];
+
+    /// The byte after the backend's tag.
+    pub fn tag(self) -> u8 {
+        match self {
+            Kind::ProvingKey => 0,
+            Kind::VerifyingKey => 1,
+            Kind::PreparedVerifyingKey => 2,
+            Kind::ContextProvingKey => 3,
+            Kind::ContextVerifyingKey => 4,
+            Kind::Proof => 5,
+            Kind::ContextProof => 6,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> Result<Self, SnarkError> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.tag() == tag)
+            .ok_or(SnarkError::UnknownKind(tag))
+    }
+
+    /// What `serialized` is.
+    pub fn of(serialized: &[u8]) -> Result<Self, SnarkError> {
+        let tag = serialized
+            .get(1)
+            .ok_or(SerializationError::NotEnoughSpace)?;
+        Self::from_tag(*tag)
+    }
+}
+
+/// Serializes `value` (uncompressed) behind the tags of backend `B` and
+/// `kind`.
+pub fn serialize_tagged<B: ProvingBackend, T: CanonicalSerialize>(
+    kind: Kind,
+    value: &T,
+) -> Result<Vec<u8>, SnarkError> {
+    let mut serialized = vec![0; 2 + value.serialized_size(ark_serialize::Compress::No)];
+    serialized[0] = B::BACKEND.tag();
+    serialized[1] = kind.tag();
+    value.serialize_uncompressed(&mut serialized[2..])?;
+    Ok(serialized)
+}
+
+/// Deserializes a value of `kind` written by [`serialize_tagged`] for
+/// backend `B`, which must fill all of `serialized`.
+pub fn deserialize_tagged<B: ProvingBackend, T: CanonicalDeserialize>(
+    kind: Kind,
+    serialized: &[u8],
+) -> Result<T, SnarkError> {
+    let found = Backend::of(serialized)?;
+    if found != B::BACKEND {
+        return Err(SnarkError::BackendMismatch {
+            expected: B::BACKEND,
+            found,
+        });
+    }
+    let found = Kind::of(serialized)?;
+    if found != kind {
+        return Err(SnarkError::KindMismatch {
+            expected: kind,
+            found,
+        });
+    }
+    let mut reader = &serialized[2..];
+    let value = T::deserialize_uncompressed(&mut reader)?;
+    if !reader.is_empty() {
+        return Err(SnarkError::TrailingBytes(reader.len()));
+    }
+    Ok(value)
+}
+
+/// Proves with a tagged proving key, returning a proof tagged the same way.
+/// The solution is [`validate`]d first, so a wrong one fails here rather
+/// than at verification; a malformed puzzle is
+/// [`SnarkError::InvalidPuzzle`], as in [`verify_tagged`].
+pub fn prove_tagged<const N: usize, R: RngCore + CryptoRng>(
+    pk: &[u8],
+    puzzle: [[u8; N]; N],
+    solution: [[u8; N]; N],
+    rng: &mut R,
+) -> Result<Vec<u8>, SnarkError> {
+    fn prove<B: ProvingBackend, const N: usize, R: RngCore + CryptoRng>(
+        pk: &[u8],
+        puzzle: [[u8; N]; N],
+        solution: [[u8; N]; N],
+        rng: &mut R,
+    ) -> Result<Vec<u8>, SnarkError> {
+        let pk = deserialize_tagged::<B, _>(Kind::ProvingKey, pk)?;
+        serialize_tagged::<B, _>(Kind::Proof, &B::prove(&pk, puzzle, solution, rng)?)
+    }
+    validate_puzzle(&puzzle).map_err(SnarkError::InvalidPuzzle)?;
+    validate(&puzzle, &solution)?;
+    match Backend::of(pk)? {
+        Backend::Groth16Bls12_381 => prove::<Groth16Bls12_381, N, R>(pk, puzzle, solution, rng),
+        Backend::Groth16Bn254 => prove::<Groth16Bn254, N, R>(pk, puzzle, solution, rng),
+        Backend::MarlinBls12_381 => prove::<MarlinBls12_381, N, R>(pk, puzzle, solution, rng),
+        Backend::Gm17Bls12_381 => prove::<Gm17Bls12_381, N, R>(pk, puzzle, solution, rng),
+    }
+}
+
+/// Verifies a tagged proof with a verifying key of the same backend. A
+/// puzzle with cells other than blanks and digits is an error rather than a
+/// rejected proof, see [`validate_puzzle`].
+pub fn verify_tagged<const N: usize>(
+    vk: &[u8],
+    puzzle: &[[u8; N]; N],
+    proof: &[u8],
+) -> Result<bool, SnarkError> {
+    fn verify<B: ProvingBackend, const N: usize>(
+        vk: &[u8],
+        puzzle: &[[u8; N]; N],
+        proof: &[u8],
+    ) -> Result<bool, SnarkError> {
+        let vk = deserialize_tagged::<B, _>(Kind::VerifyingKey, vk)?;
+        let proof = deserialize_tagged::<B, _>(Kind::Proof, proof)?;
+        B::verify(&vk, puzzle, &proof)
+    }
+    validate_puzzle(puzzle).map_err(SnarkError::InvalidPuzzle)?;
+    match Backend::of(vk)? {
+        Backend::Groth16Bls12_381 => verify::<Groth16Bls12_381, N>(vk, puzzle, proof),
+        Backend::Groth16Bn254 => verify::<Groth16Bn254, N>(vk, puzzle, proof),
+        Backend::MarlinBls12_381 => verify::<MarlinBls12_381, N>(vk, puzzle, proof),
+        Backend::Gm17Bls12_381 => verify::<Gm17Bls12_381, N>(vk, puzzle, proof),
+    }
+}
+
+/// [`prove_tagged`] with keys from [`Backend::setup_with_context`], binding
+/// the proof to `context`.
+pub fn prove_with_context_tagged<const N: usize, R: RngCore + CryptoRng>(
+    pk: &[u8],
+    puzzle: [[u8; N]; N],
+    solution: [[u8; N]; N],
+    context: &Context,
+    rng: &mut R,
+) -> Result<Vec<u8>, SnarkError> {
+    fn prove<B: ProvingBackend, const N: usize, R: RngCore + CryptoRng>(
+        pk: &[u8],
+        puzzle: [[u8; N]; N],
+        solution: [[u8; N]; N],
+        context: &Context,
+        rng: &mut R,
+    ) -> Result<Vec<u8>, SnarkError> {
+        let pk = deserialize_tagged::<B, _>(Kind::ContextProvingKey, pk)?;
+        let proof = B::prove_with_context(&pk, puzzle, solution, context, rng)?;
+        serialize_tagged::<B, _>(Kind::ContextProof, &proof)
+    }
+    validate_puzzle(&puzzle).map_err(SnarkError::InvalidPuzzle)?;
+    validate(&puzzle, &solution)?;
+    match Backend::of(pk)? {
+        Backend::Groth16Bls12_381 => {
+            prove::<Groth16Bls12_381, N, R>(pk, puzzle, solution, context, rng)
+        }
+        Backend::Groth16Bn254 => prove::<Groth16Bn254, N, R>(pk, puzzle, solution, context, rng),
+        Backend::MarlinBls12_381 => {
+            prove::<MarlinBls12_381, N, R>(pk, puzzle, solution, context, rng)
+        }
+        Backend::Gm17Bls12_381 => prove::<Gm17Bls12_381, N, R>(pk, puzzle, solution, context, rng),
+    }
+}
+
+/// [`verify_tagged`] for proofs of [`prove_with_context_tagged`], which
+/// only verify under the `context` they were made for.
+pub fn verify_with_context_tagged<const N: usize>(
+    vk: &[u8],
+    puzzle: &[[u8; N]; N],
+    context: &Context,
+    proof: &[u8],
+) -> Result<bool, SnarkError> {
+    fn verify<B: ProvingBackend, const N: usize>(
+        vk: &[u8],
+        puzzle: &[[u8; N]; N],
+        context: &Context,
+        proof: &[u8],
+    ) -> Result<bool, SnarkError> {
+        let vk = deserialize_tagged::<B, _>(Kind::ContextVerifyingKey, vk)?;
+        let proof = deserialize_tagged::<B, _>(Kind::ContextProof, proof)?;
+        B::verify_with_context(&vk, puzzle, context, &proof)
+    }
+    validate_puzzle(puzzle).map_err(SnarkError::InvalidPuzzle)?;
+    match Backend::of(vk)? {
+        Backend::Groth16Bls12_381 => verify::<Groth16Bls12_381, N>(vk, puzzle, context, proof),
+        Backend::Groth16Bn254 => verify::<Groth16Bn254, N>(vk, puzzle, context, proof),
+        Backend::MarlinBls12_381 => verify::<MarlinBls12_381, N>(vk, puzzle, context, proof),
+        Backend::Gm17Bls12_381 => verify::<Gm17Bls12_381, N>(vk, puzzle, context, proof),
+    }
+}
+
+/// Rerandomizes a tagged proof with a verifying key of the same backend,
+/// with or without a context.
+pub fn rerandomize_tagged<R: RngCore + CryptoRng>(
+    vk: &[u8],
+    proof: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>, SnarkError> {
+    fn rerandomize<B: ProvingBackend, R: RngCore + CryptoRng>(
+        vk: &[u8],
+        proof: &[u8],
+        rng: &mut R,
+    ) -> Result<Vec<u8>, SnarkError> {
+        let (vk_kind, proof_kind) = match Kind::of(vk)? {
+            Kind::ContextVerifyingKey => (Kind::ContextVerifyingKey, Kind::ContextProof),
+            _ => (Kind::VerifyingKey, Kind::Proof),
+        };
+        let vk = deserialize_tagged::<B, _>(vk_kind, vk)?;
+        let proof = deserialize_tagged::<B, _>(proof_kind, proof)?;
+        serialize_tagged::<B, _>(proof_kind, &B::rerandomize(&vk, &proof, rng)?)
+    }
+    match Backend::of(vk)? {
+        Backend::Groth16Bls12_381 => rerandomize::<Groth16Bls12_381, R>(vk, proof, rng),
+        Backend::Groth16Bn254 => rerandomize::<Groth16Bn254, R>(vk, proof, rng),
+        Backend::MarlinBls12_381 => rerandomize::<MarlinBls12_381, R>(vk, proof, rng),
+        Backend::Gm17Bls12_381 => rerandomize::<Gm17Bls12_381, R>(vk, proof, rng),
+    }
+}
+
+/// Turns a tagged verifying key into a tagged prepared verifying key, to be
+/// stored and loaded by [`verify_many_tagged`] instead.
+pub fn prepare_tagged(vk: &[u8]) -> Result<Vec<u8>, SnarkError> {
+    fn prepare<B: ProvingBackend>(vk: &[u8]) -> Result<Vec<u8>, SnarkError> {
+        let vk = deserialize_tagged::<B, _>(Kind::VerifyingKey, vk)?;
+        serialize_tagged::<B, _>(Kind::PreparedVerifyingKey, &B::prepare(&vk)?)
+    }
+    match Backend::of(vk)? {
+        Backend::Groth16Bls12_381 => prepare::<Groth16Bls12_381>(vk),
+        Backend::Groth16Bn254 => prepare::<Groth16Bn254>(vk),
+        Backend::MarlinBls12_381 => prepare::<MarlinBls12_381>(vk),
+        Backend::Gm17Bls12_381 => prepare::<Gm17Bls12_381>(vk),
+    }
+}
+
+/// Verifies tagged proofs, each against its puzzle, with a tagged prepared
+/// verifying key that is only loaded once. Puzzles are checked as in
+/// [`verify_tagged`].
+pub fn verify_many_tagged<const N: usize>(
+    pvk: &[u8],
+    proofs: &[([[u8; N]; N], Vec<u8>)],
+) -> Result<Vec<bool>, SnarkError> {
+    fn verify<B: ProvingBackend, const N: usize>(
+        pvk: &[u8],
+        proofs: &[([[u8; N]; N], Vec<u8>)],
+    ) -> Result<Vec<bool>, SnarkError> {
+        let pvk = deserialize_tagged::<B, _>(Kind::PreparedVerifyingKey, pvk)?;
+        proofs
+            .iter()
+            .map(|(puzzle, proof)| {
+                let proof = deserialize_tagged::<B, _>(Kind::Proof, proof)?;
+                B::verify_prepared(&pvk, puzzle, &proof)
+            })
+            .collect()
+    }
+    for (puzzle, _) in proofs {
+        validate_puzzle(puzzle).map_err(SnarkError::InvalidPuzzle)?;
+    }
+    match Backend::of(pvk)? {
+        Backend::Groth16Bls12_381 => verify::<Groth16Bls12_381, N>(pvk, proofs),
+        Backend::Groth16Bn254 => verify::<Groth16Bn254, N>(pvk, proofs),
+        Backend::MarlinBls12_381 => verify::<MarlinBls12_381, N>(pvk, proofs),
+        Backend::Gm17Bls12_381 => verify::<Gm17Bls12_381, N>(pvk, proofs),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::PipelineError;
+    use crate::fixtures::{PUZZLE, PUZZLE_9, SOLUTION, SOLUTION_9};
+    use rand::prelude::*;
+
+    #[test]
+    fn every_backend_round_trips() -> Result<(), PipelineError> {
+        let rng = &mut thread_rng();
+        let mut wrong_puzzle = PUZZLE_9;
+        wrong_puzzle[0][0] = 1;
+
+        for backend in Backend::ALL {
+            let (pk, vk) = backend.setup::<9, _>(rng).map_err(PipelineError::Setup)?;
+            assert_eq!(Backend::of(&pk).unwrap(), backend);
+            assert_eq!(Backend::of(&vk).unwrap(), backend);
+
+            let proof =
+                prove_tagged(&pk, PUZZLE_9, SOLUTION_9, rng).map_err(PipelineError::Prove)?;
+            assert_eq!(Backend::of(&proof).unwrap(), backend);
+
+            let mut wrong_solution = SOLUTION_9;
+            wrong_solution[0].swap(0, 1);
+            assert!(matches!(
+                prove_tagged(&pk, PUZZLE_9, wrong_solution, rng),
+                Err(SnarkError::InvalidSolution(
+                    ValidationError::DuplicateInCol {
+                        col: 0,
+                        digit: 9,
+                        ..
+                    }
+                ))
+            ));
+
+            match verify_tagged(&vk, &PUZZLE_9, &proof) {
+                Ok(true) => {}
+                Ok(false) => panic!("{backend}: proof rejected but should have been accepted"),
+                Err(err) => panic!("{backend}: failed to verify proof with vk: {err}"),
+            };
+            match verify_tagged(&vk, &wrong_puzzle, &proof) {
+                Ok(false) => {}
+                Ok(true) => panic!("{backend}: proof accepted but should have been rejected"),
+                Err(err) => panic!("{backend}: failed to verify proof with vk: {err}"),
+            };
+        }
+        Ok(())
+    }
+
+    /// `value` serialized, then deserialized and serialized again, which
+    /// must give the same bytes.
+    fn round_trip<T: CanonicalSerialize + CanonicalDeserialize>(value: &T) -> T {
+        let mut serialized = Vec::new();
+        value.serialize_uncompressed(&mut serialized).unwrap();
+        let deserialized = T::deserialize_uncompressed(&serialized[..]).unwrap();
+        let mut reserialized = Vec::new();
+        deserialized
+            .serialize_uncompressed(&mut reserialized)
+            .unwrap();
+        assert_eq!(serialized, reserialized);
+        deserialized
+    }
+
+    /// Proves a 4x4 board with `B` and checks that its keys and proofs
+    /// still work after a serialization round trip.
+    fn serialized_keys_work<B: ProvingBackend>() -> Result<(), SnarkError> {
+        let rng = &mut thread_rng();
+        let (pk, vk) = B::setup::<4, _>(rng)?;
+        let proof = B::prove(&pk, PUZZLE, SOLUTION, rng)?;
+        let (p, v, pr) = (round_trip(&pk), round_trip(&vk), round_trip(&proof));
+
+        let fresh = B::prove(&p, PUZZLE, SOLUTION, rng)?;
+        for (vk, proof) in [(&vk, &proof), (&v, &pr), (&v, &fresh)] {
+            assert!(
+                B::verify(vk, &PUZZLE, proof)?,
+                "{}: proof rejected but should have been accepted",
+                B::BACKEND
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn every_backend_serializes() -> Result<(), SnarkError> {
+        serialized_keys_work::<Groth16Bls12_381>()?;
+        serialized_keys_work::<Groth16Bn254>()?;
+        serialized_keys_work::<MarlinBls12_381>()?;
+        serialized_keys_work::<Gm17Bls12_381>()
+    }
+
+    #[test]
+    fn prepared_key_verifies_many() {
+        let rng = &mut thread_rng();
+        let mut wrong_puzzle = PUZZLE;
+        wrong_puzzle[0][1] = 2;
+
+        for backend in Backend::ALL {
+            let (pk, vk) = backend.setup::<4, _>(rng).unwrap();
+            let pvk = prepare_tagged(&vk).unwrap();
+            assert_eq!(Backend::of(&pvk).unwrap(), backend);
+
+            let proof = prove_tagged(&pk, PUZZLE, SOLUTION, rng).unwrap();
+            let proofs = [
+                (PUZZLE, proof.clone()),
+                (wrong_puzzle, proof.clone()),
+                (PUZZLE, proof),
+            ];
+            assert_eq!(
+                verify_many_tagged(&pvk, &proofs).unwrap(),
+                [true, false, true]
+            );
+            // An unprepared key is not a prepared one, even where preparing
+            // changes nothing.
+            assert!(matches!(
+                verify_many_tagged(&vk, &proofs),
+                Err(SnarkError::KindMismatch {
+                    expected: Kind::PreparedVerifyingKey,
+                    found: Kind::VerifyingKey,
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn malformed_puzzles_are_errors() {
+        let rng = &mut thread_rng();
+        let mut malformed = PUZZLE;
+        malformed[3][2] = 9;
+        let out_of_range = ValidationError::GivenOutOfRange {
+            row: 3,
+            col: 2,
+            value: 9,
+        };
+
+        let (pk, vk) = Groth16Bls12_381::setup::<4, _>(rng).unwrap();
+        let tagged_pk = serialize_tagged::<Groth16Bls12_381, _>(Kind::ProvingKey, &pk).unwrap();
+        let tagged_vk = serialize_tagged::<Groth16Bls12_381, _>(Kind::VerifyingKey, &vk).unwrap();
+        assert!(matches!(
+            prove_tagged(&tagged_pk, malformed, SOLUTION, rng),
+            Err(SnarkError::InvalidPuzzle(err)) if err == out_of_range
+        ));
+        let (context_pk, _) = Backend::Groth16Bls12_381
+            .setup_with_context::<4, _>(rng)
+            .unwrap();
+        assert!(matches!(
+            prove_with_context_tagged(&context_pk, malformed, SOLUTION, &[0; 32], rng),
+            Err(SnarkError::InvalidPuzzle(err)) if err == out_of_range
+        ));
+
+        let proof = Groth16Bls12_381::prove(&pk, PUZZLE, SOLUTION, rng).unwrap();
+        // The circuit itself rejects the puzzle as well.
+        assert!(!Groth16Bls12_381::verify(&vk, &malformed, &proof).unwrap());
+        let proof = serialize_tagged::<Groth16Bls12_381, _>(Kind::Proof, &proof).unwrap();
+        assert!(matches!(
+            verify_tagged(&tagged_vk, &malformed, &proof),
+            Err(SnarkError::InvalidPuzzle(err)) if err == out_of_range
+        ));
+        let pvk = prepare_tagged(&tagged_vk).unwrap();
+        assert!(matches!(
+            verify_many_tagged(&pvk, &[(PUZZLE, proof.clone()), (malformed, proof)]),
+            Err(SnarkError::InvalidPuzzle(err)) if err == out_of_range
+        ));
+    }
+
+    #[test]
+    fn rerandomized_proofs_verify() {
+        let rng = &mut thread_rng();
+        let mut wrong_puzzle = PUZZLE_9;
+        wrong_puzzle[0][0] = 1;
+
+        for backend in Backend::ALL {
+            let (pk, vk) = backend.setup::<9, _>(rng).unwrap();
+            let proof = prove_tagged(&pk, PUZZLE_9, SOLUTION_9, rng).unwrap();
+            if matches!(backend, Backend::MarlinBls12_381 | Backend::Gm17Bls12_381) {
+                assert!(matches!(
+                    rerandomize_tagged(&vk, &proof, rng),
+                    Err(SnarkError::NotRerandomizable(found)) if found == backend
+                ));
+                continue;
+            }
+            let rerandomized = rerandomize_tagged(&vk, &proof, rng).unwrap();
+            assert_ne!(rerandomized, proof);
+            assert_ne!(rerandomize_tagged(&vk, &proof, rng).unwrap(), rerandomized);
+            assert!(verify_tagged(&vk, &PUZZLE_9, &rerandomized).unwrap());
+            assert!(!verify_tagged(&vk, &wrong_puzzle, &rerandomized).unwrap());
+        }
+    }
+
+    #[test]
+    fn universal_srs_indexes_every_size() {
+        let rng = &mut thread_rng();
+        let bound = MarlinBls12_381::bound(SudokuCircuit::<9>::default()).unwrap();
+        let srs = MarlinBls12_381::universal_setup(&bound, rng).unwrap();
+
+        let (pk, vk) = MarlinBls12_381::index::<4>(&srs).unwrap();
+        let proof = MarlinBls12_381::prove(&pk, PUZZLE, SOLUTION, rng).unwrap();
+        assert!(MarlinBls12_381::verify(&vk, &PUZZLE, &proof).unwrap());
+        // Indexing is deterministic, so anyone holding the SRS can check a
+        // verifying key.
+        assert_eq!(
+            MarlinBls12_381::index::<4>(&srs).unwrap().1.index_comms,
+            vk.index_comms
+        );
+
+        let (pk, vk) = MarlinBls12_381::index::<9>(&srs).unwrap();
+        let proof = MarlinBls12_381::prove(&pk, PUZZLE_9, SOLUTION_9, rng).unwrap();
+        assert!(MarlinBls12_381::verify(&vk, &PUZZLE_9, &proof).unwrap());
+
+        // A circuit past the bound needs a larger SRS.
+        let small = MarlinBls12_381::universal_setup(
+            &MarlinBls12_381::bound(SudokuCircuit::<4>::default()).unwrap(),
+            rng,
+        )
+        .unwrap();
+        assert!(matches!(
+            MarlinBls12_381::index::<9>(&small),
+            Err(SnarkError::Marlin(ark_marlin::Error::IndexTooLarge))
+        ));
+    }
+
+    #[test]
+    fn mismatched_tags_rejected() {
+        let rng = &mut thread_rng();
+        let (_, vk) = Groth16Bn254::setup::<9, _>(rng).unwrap();
+        let vk = serialize_tagged::<Groth16Bn254, _>(Kind::VerifyingKey, &vk).unwrap();
+        assert!(matches!(
+            deserialize_tagged::<Groth16Bls12_381, VerifyingKeyOf<Groth16Bls12_381>>(
+                Kind::VerifyingKey,
+                &vk
+            ),
+            Err(SnarkError::BackendMismatch {
+                expected: Backend::Groth16Bls12_381,
+                found: Backend::Groth16Bn254,
+            })
+        ));
+        assert!(matches!(
+            Backend::of(&[0xff]),
+            Err(SnarkError::UnknownBackend(0xff))
+        ));
+        assert!(matches!(
+            Backend::of(&[]),
+            Err(SnarkError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn mismatched_kinds_rejected() {
+        let rng = &mut thread_rng();
+        let (pk, vk) = Backend::Groth16Bn254.setup::<4, _>(rng).unwrap();
+        assert_eq!(Kind::of(&pk).unwrap(), Kind::ProvingKey);
+        let proof = prove_tagged(&pk, PUZZLE, SOLUTION, rng).unwrap();
+        assert_eq!(Kind::of(&proof).unwrap(), Kind::Proof);
+        assert!(verify_tagged(&vk, &PUZZLE, &proof).unwrap());
+
+        assert!(matches!(
+            verify_tagged(&pk, &PUZZLE, &proof),
+            Err(SnarkError::KindMismatch {
+                expected: Kind::VerifyingKey,
+                found: Kind::ProvingKey,
+            })
+        ));
+        assert!(matches!(
+            verify_tagged(&vk, &PUZZLE, &vk),
+            Err(SnarkError::KindMismatch {
+                expected: Kind::Proof,
+                found: Kind::VerifyingKey,
+            })
+        ));
+        let (_, context_vk) = Backend::Groth16Bn254
+            .setup_with_context::<4, _>(rng)
+            .unwrap();
+        assert!(matches!(
+            verify_tagged(&context_vk, &PUZZLE, &proof),
+            Err(SnarkError::KindMismatch {
+                expected: Kind::VerifyingKey,
+                found: Kind::ContextVerifyingKey,
+            })
+        ));
+
+        let mut padded = proof.clone();
+        padded.push(0);
+        assert!(matches!(
+            verify_tagged(&vk, &PUZZLE, &padded),
+            Err(SnarkError::TrailingBytes(1))
+        ));
+        let mut unknown = proof;
+        unknown[1] = 0xff;
+        assert!(matches!(
+            verify_tagged(&vk, &PUZZLE, &unknown),
+            Err(SnarkError::UnknownKind(0xff))
+        ));
+        assert!(matches!(
+            Kind::of(&[Backend::Groth16Bn254.tag()]),
+            Err(SnarkError::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn seeded_setup_is_reproducible() {
+        let rng = &mut thread_rng();
+        for backend in Backend::ALL {
+            let keys = backend
+                .setup_in_mode::<4, _>(SetupMode::Test, Some(36), rng)
+                .unwrap();
+            assert_eq!(
+                keys,
+                backend
+                    .setup_in_mode::<4, _>(SetupMode::Test, Some(36), rng)
+                    .unwrap()
+            );
+            assert_ne!(
+                keys,
+                backend
+                    .setup_in_mode::<4, _>(SetupMode::Test, Some(37), rng)
+                    .unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn setup_modes_police_seeds() {
+        let rng = &mut thread_rng();
+        let backend = Backend::Groth16Bn254;
+        assert!(matches!(
+            backend.setup_in_mode::<4, _>(SetupMode::Production, Some(36), rng),
+            Err(SnarkError::SeededProductionSetup)
+        ));
+        assert!(matches!(
+            backend.setup_in_mode::<4, _>(SetupMode::Test, None, rng),
+            Err(SnarkError::UnseededTestSetup)
+        ));
+        let (pk, vk) = backend
+            .setup_in_mode::<4, _>(SetupMode::Production, None, rng)
+            .unwrap();
+        assert_ne!(
+            (pk, vk),
+            backend
+                .setup_in_mode::<4, _>(SetupMode::Production, None, rng)
+                .unwrap()
+        );
+    }
+}
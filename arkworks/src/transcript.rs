@@ -0,0 +1,42 @@
+//! Fiat-Shamir transcript over Keccak-256, shared by the protocols that
+//! derive their challenges from the puzzles and proofs they are run on.
+
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use sha3::{Digest, Keccak256};
+
+pub(crate) struct Transcript(Keccak256);
+
+impl Transcript {
+    /// Starts a transcript for the protocol named `label` over `puzzles`.
+    pub(crate) fn new<const N: usize>(label: &[u8], puzzles: &[[[u8; N]; N]]) -> Self {
+        let mut hasher = Keccak256::new();
+        hasher.update(label);
+        hasher.update((N as u64).to_le_bytes());
+        hasher.update((puzzles.len() as u64).to_le_bytes());
+        for puzzle in puzzles {
+            hasher.update(puzzle.as_flattened());
+        }
+        Transcript(hasher)
+    }
+
+    pub(crate) fn append(&mut self, value: &impl CanonicalSerialize) {
+        let mut bytes = Vec::with_capacity(value.uncompressed_size());
+        value
+            .serialize_uncompressed(&mut bytes)
+            .expect("writing to a Vec cannot fail");
+        self.0.update(bytes);
+    }
+
+    /// Returns a non-zero challenge and absorbs it.
+    pub(crate) fn challenge<F: PrimeField>(&mut self) -> F {
+        loop {
+            let digest = self.0.clone().finalize();
+            self.0.update(digest);
+            let challenge = F::from_le_bytes_mod_order(&digest);
+            if !challenge.is_zero() {
+                return challenge;
+            }
+        }
+    }
+}
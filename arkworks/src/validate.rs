@@ -0,0 +1,266 @@
+//! Native checks of a solution before proving it.
+//!
+//! A proof of a wrong solution is still a proof; it only fails to verify, and
+//! says nothing about why. [`validate`] checks natively what
+//! [`SudokuCircuit::enforce_solves`](crate::circuit::SudokuCircuit) enforces,
+//! in the same order, and reports the first check that fails and where.
+//! [`validate_puzzle`] checks only the public puzzle, for verifiers.
+
+/// The first constraint of [`SudokuCircuit`](crate::circuit::SudokuCircuit)
+/// a solution breaks. Rows, columns and digits are as in the grids, so
+/// counting starts at 0 for rows and columns and at 1 for digits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// A puzzle cell is neither blank (0) nor a digit in `1..=N`.
+    GivenOutOfRange { row: usize, col: usize, value: u8 },
+    /// A solution cell is not a digit in `1..=N`.
+    OutOfRange { row: usize, col: usize, value: u8 },
+    /// A given of the puzzle differs from the solution.
+    GivenMismatch { row: usize, col: usize },
+    DuplicateInRow {
+        row: usize,
+        digit: u8,
+        cols: (usize, usize),
+    },
+    DuplicateInCol {
+        col: usize,
+        digit: u8,
+        rows: (usize, usize),
+    },
+    /// A digit appears twice in a subgrid, at the `(row, col)` in `cells`.
+    DuplicateInBox {
+        digit: u8,
+        cells: ((usize, usize), (usize, usize)),
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::GivenOutOfRange { row, col, value } => write!(
+                f,
+                "Puzzle cell ({row}, {col}) holds {value}, which is neither blank nor a digit"
+            ),
+            ValidationError::OutOfRange { row, col, value } => {
+                write!(f, "Cell ({row}, {col}) holds {value}, which is not a digit")
+            }
+            ValidationError::GivenMismatch { row, col } => {
+                write!(f, "Cell ({row}, {col}) differs from the puzzle")
+            }
+            ValidationError::DuplicateInRow { row, digit, cols } => write!(
+                f,
+                "Row {row} holds {digit} in columns {} and {}",
+                cols.0, cols.1
+            ),
+            ValidationError::DuplicateInCol { col, digit, rows } => write!(
+                f,
+                "Column {col} holds {digit} in rows {} and {}",
+                rows.0, rows.1
+            ),
+            ValidationError::DuplicateInBox { digit, cells } => write!(
+                f,
+                "Subgrid holds {digit} in cells {:?} and {:?}",
+                cells.0, cells.1
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Checks that every cell of `puzzle` is blank (0) or a digit, as the
+/// circuit does before anything else. Verifiers should reject other puzzles
+/// outright: no proof for them exists.
+pub fn validate_puzzle<const N: usize>(puzzle: &[[u8; N]; N]) -> Result<(), ValidationError> {
+    for (row, cells) in puzzle.iter().enumerate() {
+        for (col, &value) in cells.iter().enumerate() {
+            if value as usize > N {
+                return Err(ValidationError::GivenOutOfRange { row, col, value });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `solution` solves `puzzle`, as the circuit would.
+pub fn validate<const N: usize>(
+    puzzle: &[[u8; N]; N],
+    solution: &[[u8; N]; N],
+) -> Result<(), ValidationError> {
+    validate_puzzle(puzzle)?;
+
+    for (row, cells) in solution.iter().enumerate() {
+        for (col, &value) in cells.iter().enumerate() {
+            if !(1..=N).contains(&(value as usize)) {
+                return Err(ValidationError::OutOfRange { row, col, value });
+            }
+        }
+    }
+
+    for row in 0..N {
+        for col in 0..N {
+            if puzzle[row][col] != 0 && puzzle[row][col] != solution[row][col] {
+                return Err(ValidationError::GivenMismatch { row, col });
+            }
+        }
+    }
+
+    for (row, cells) in solution.iter().enumerate() {
+        if let Some((digit, cols)) = duplicate(cells.iter().copied().enumerate()) {
+            return Err(ValidationError::DuplicateInRow { row, digit, cols });
+        }
+    }
+
+    for col in 0..N {
+        if let Some((digit, rows)) = duplicate(solution.iter().map(|cells| cells[col]).enumerate())
+        {
+            return Err(ValidationError::DuplicateInCol { col, digit, rows });
+        }
+    }
+
+    // As in the circuit, boards whose size isn't a perfect square have no
+    // subgrids.
+    let b = N.isqrt();
+    if b * b != N {
+        return Ok(());
+    }
+    for i in (0..N).step_by(b) {
+        for j in (0..N).step_by(b) {
+            let cells = (i..i + b)
+                .flat_map(|row| (j..j + b).map(move |col| (row, col)))
+                .map(|(row, col)| ((row, col), solution[row][col]));
+            if let Some((digit, cells)) = duplicate(cells) {
+                return Err(ValidationError::DuplicateInBox { digit, cells });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The first digit that appears twice among `cells`, with the positions of
+/// both.
+fn duplicate<P: Copy>(cells: impl Iterator<Item = (P, u8)> + Clone) -> Option<(u8, (P, P))> {
+    cells
+        .clone()
+        .enumerate()
+        .find_map(|(i, (position, digit))| {
+            cells
+                .clone()
+                .take(i)
+                .find(|(_, prior)| *prior == digit)
+                .map(|(prior, _)| (digit, (prior, position)))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::SudokuCircuit;
+    use crate::fixtures::{Grid, PUZZLE, SOLUTION};
+    use ark_bls12_381::Fr;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+
+    fn satisfies_circuit(puzzle: Grid, solution: Grid) -> bool {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        SudokuCircuit {
+            puzzle: Some(puzzle),
+            solution: Some(solution),
+        }
+        .generate_constraints(cs.clone())
+        .unwrap();
+        cs.is_satisfied().unwrap()
+    }
+
+    #[test]
+    fn reports_first_broken_check() {
+        assert_eq!(validate(&PUZZLE, &SOLUTION), Ok(()));
+
+        // The puzzle is checked first, even when the solution is also wrong.
+        let mut puzzle = PUZZLE;
+        puzzle[1][2] = 200;
+        let mut solution = SOLUTION;
+        solution[0][0] = 0;
+        assert_eq!(
+            validate(&puzzle, &solution),
+            Err(ValidationError::GivenOutOfRange {
+                row: 1,
+                col: 2,
+                value: 200
+            })
+        );
+
+        let mut solution = SOLUTION;
+        solution[2][3] = 5;
+        assert_eq!(
+            validate(&PUZZLE, &solution),
+            Err(ValidationError::OutOfRange {
+                row: 2,
+                col: 3,
+                value: 5
+            })
+        );
+
+        let mut puzzle = PUZZLE;
+        puzzle[3][0] = 3;
+        assert_eq!(
+            validate(&puzzle, &SOLUTION),
+            Err(ValidationError::GivenMismatch { row: 3, col: 0 })
+        );
+
+        let mut solution = SOLUTION;
+        solution[1][3] = 4;
+        assert_eq!(
+            validate(&[[0; 4]; 4], &solution),
+            Err(ValidationError::DuplicateInRow {
+                row: 1,
+                digit: 4,
+                cols: (1, 3)
+            })
+        );
+
+        // Rows are fine, but the columns repeat.
+        let solution = [[1, 2, 3, 4], [3, 4, 1, 2], [2, 1, 4, 3], [3, 4, 1, 2]];
+        assert_eq!(
+            validate(&[[0; 4]; 4], &solution),
+            Err(ValidationError::DuplicateInCol {
+                col: 0,
+                digit: 3,
+                rows: (1, 3)
+            })
+        );
+
+        // Rows and columns are fine, but the top-left subgrid holds two 2s.
+        let solution = [[1, 2, 3, 4], [2, 3, 4, 1], [3, 4, 1, 2], [4, 1, 2, 3]];
+        assert_eq!(
+            validate(&[[0; 4]; 4], &solution),
+            Err(ValidationError::DuplicateInBox {
+                digit: 2,
+                cells: ((0, 1), (1, 0))
+            })
+        );
+    }
+
+    #[test]
+    fn agrees_with_circuit() {
+        let mut cases = vec![(PUZZLE, SOLUTION), ([[0; 4]; 4], SOLUTION)];
+        for row in 0..4 {
+            for col in 0..4 {
+                for value in [0, 2, 4, 5, 255] {
+                    let mut solution = SOLUTION;
+                    solution[row][col] = value;
+                    cases.push((PUZZLE, solution));
+                    let mut puzzle = PUZZLE;
+                    puzzle[row][col] = value;
+                    cases.push((puzzle, SOLUTION));
+                }
+            }
+        }
+        for (puzzle, solution) in cases {
+            assert_eq!(
+                validate(&puzzle, &solution).is_ok(),
+                satisfies_circuit(puzzle, solution),
+                "{puzzle:?} {solution:?}"
+            );
+        }
+    }
+}